@@ -0,0 +1,59 @@
+//! 格式化性能基准：数 MB 的压缩 HTML/CSS/JS/JSON 输入
+//! Formatting benchmarks: multi-MB minified HTML/CSS/JS/JSON inputs
+//!
+//! 运行 / Run: `cargo bench --bench format`
+//! 与旧版本比较时先在旧版本上运行 `cargo bench --bench format -- --save-baseline old`，
+//! 再在新版本上运行 `cargo bench --bench format -- --baseline old`
+//! To compare with an older revision run `cargo bench --bench format -- --save-baseline old` there first, then
+//! `cargo bench --bench format -- --baseline old` on the new one
+
+use code_formatter::FormatOptions;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// 每种语言重复拼接的压缩样本
+/// The minified sample of each language that gets repeated
+const SAMPLES: &[(&str, &str)] = &[
+    (
+        "js",
+        "function f(a,b){if(a>b){return a*b+1}else{for(var i=0;i<10;i++){b+=i?a:b}}return{x:a,y:[1,2,3],z:\"s\\\"q\"}}\
+         const g=async(x)=>{try{await x()}catch(e){console.log(e)}};",
+    ),
+    (
+        "css",
+        ".a .b>c,d:hover{color:#FFF;margin:0 auto;background:url(data:image/png;base64,AAAA)}@media (max-width:600px){.x{display:none}}",
+    ),
+    ("html", "<div class=\"a\"><p>Some text <b>bold</b> and more text here</p><ul><li>one</li><li>two</li></ul><!-- note --></div>"),
+    ("json", "{\"name\":\"x\",\"list\":[1,2,3,{\"a\":true,\"b\":null}],\"nested\":{\"k\":\"v\",\"n\":-1.5e3}},"),
+];
+
+/// 输入大小（字节）
+/// Input size (bytes)
+const SIZE: usize = 2 * 1024 * 1024;
+
+/// 把样本重复到约 `SIZE` 字节，得到一个完整的文件
+/// Repeat the sample to about `SIZE` bytes, giving a complete file
+fn input(file_type: &str, sample: &str) -> String {
+    let body = sample.repeat(SIZE / sample.len() + 1);
+    match file_type {
+        "html" => format!("<!DOCTYPE html><html><body>{}</body></html>", body),
+        "json" => format!("[{}{{}}]", body),
+        _ => body,
+    }
+}
+
+fn format_minified(c: &mut Criterion) {
+    let options = FormatOptions::default();
+    let mut group = c.benchmark_group("format_minified");
+    group.sample_size(10);
+    for (file_type, sample) in SAMPLES {
+        let source = input(file_type, sample);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(file_type), &source, |b, source| {
+            b.iter(|| code_formatter::format(source, file_type, &options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, format_minified);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+# 生成 src/gb18030.bin：GB18030 双字节映射表与四字节 BMP 区段表（数据来自 Python 内置的 gb18030 编解码器）
+# Generate src/gb18030.bin: the GB18030 two-byte table and the four-byte BMP range table (data from Python's gb18030 codec)
+#
+# 格式（小端 u16）：
+# Layout (little-endian u16):
+#   126 * 190 项：首字节 0x81-0xFE × 尾字节 0x40-0x7E、0x80-0xFE 对应的 Unicode 码位
+#   126 * 190 entries: code point of lead byte 0x81-0xFE × trail byte 0x40-0x7E, 0x80-0xFE
+#   之后每两项一组：(区段首个码位, 区段首个四字节线性序号)
+#   then pairs of (first code point of the range, first four-byte linear index of the range)
+import struct
+from pathlib import Path
+
+TRAILS = list(range(0x40, 0x7F)) + list(range(0x80, 0xFF))
+
+
+def linear(b):
+    return (b[0] - 0x81) * 12600 + (b[1] - 0x30) * 1260 + (b[2] - 0x81) * 10 + (b[3] - 0x30)
+
+
+def main():
+    data = bytearray()
+    for lead in range(0x81, 0xFF):
+        for trail in TRAILS:
+            data += struct.pack("<H", ord(bytes([lead, trail]).decode("gb18030")))
+
+    ranges = []
+    for cp in range(0x80, 0x10000):
+        if 0xD800 <= cp <= 0xDFFF:
+            continue
+        encoded = chr(cp).encode("gb18030")
+        if len(encoded) != 4:
+            continue
+        index = linear(encoded)
+        if ranges and ranges[-1][2] == cp - 1 and ranges[-1][3] == index - 1:
+            ranges[-1][2:] = [cp, index]
+        else:
+            ranges.append([cp, index, cp, index])
+    for first_cp, first_index, _, _ in ranges:
+        data += struct.pack("<HH", first_cp, first_index)
+
+    Path(__file__).resolve().parent.parent.joinpath("src", "gb18030.bin").write_bytes(data)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,116 @@
+//! 标准输入批处理
+//! Stdin batch protocol
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, Write};
+use crate::*;
+
+/// 把 JSON 值转换为配置值（请求中的 options 与配置文件的设置取值规则相同）
+/// Convert a JSON value into a config value (request options follow the same rules as config file settings)
+fn json_to_config_value(value: &JsonValue) -> Option<ConfigValue> {
+    match value {
+        JsonValue::Scalar(text) if text.starts_with('"') => json_unescape(text).map(ConfigValue::Str),
+        JsonValue::Scalar(text) if text == "true" || text == "false" => Some(ConfigValue::Bool(text == "true")),
+        JsonValue::Scalar(text) => text.parse().ok().map(ConfigValue::Int),
+        JsonValue::Array(items) => items.iter().map(json_to_config_value).collect::<Option<_>>().map(ConfigValue::Array),
+        JsonValue::Object(_) => None,
+    }
+}
+
+/// 处理一行批处理请求 `{"path": ..., "content": ..., "options": {...}}`，返回请求的路径与格式化结果
+/// Handle one batch request line `{"path": ..., "content": ..., "options": {...}}`, returning the request path and the
+/// formatted result
+fn handle_batch_request(line: &str, cli: &Cli, base_options: &FormatOptions) -> (Option<String>, Result<FormatOutcome>) {
+    let request = match parse_json(line) {
+        Ok(JsonValue::Object(members)) => members,
+        Ok(_) => return (None, Err(ConfigError(tr!("请求应为 JSON 对象", "The request must be a JSON object")).into())),
+        Err(err) => return (None, Err(err)),
+    };
+    let field = |name: &str| request.iter().find(|(key, _)| json_unescape(key).as_deref() == Some(name)).map(|(_, value)| value);
+    let string = |name: &str| match field(name) {
+        Some(JsonValue::Scalar(text)) => json_unescape(text),
+        _ => None,
+    };
+    let path = string("path");
+    let result = (|| {
+        let content = string("content").ok_or_else(|| ConfigError(tr!("请求缺少字符串字段 content", "The request lacks the string field content")))?;
+        let path = path.as_deref().unwrap_or("-");
+        let mut options = file_options(cli, base_options, Config::discover(cli.config.as_deref(), path)?)?;
+        match field("options") {
+            Some(JsonValue::Object(members)) => {
+                for (key, value) in members {
+                    let key = json_unescape(key).unwrap_or_default();
+                    let setting = json_to_config_value(value)
+                        .ok_or_else(|| tr!("{} 的取值无效", "invalid value for {}", key))
+                        .and_then(|value| Setting::parse(&key, &value))
+                        .map_err(ConfigError)?;
+                    setting.apply(&mut options);
+                }
+            }
+            None => {}
+            Some(_) => return Err(ConfigError(tr!("options 应为 JSON 对象", "options must be a JSON object")).into()),
+        }
+        let (has_bom, text) = strip_bom(&content);
+        let text = text.replace('\r', "");
+        match external_command(options.file_type.as_deref().unwrap_or(path), &options.external) {
+            Some(command) => {
+                let formatted = apply_bom_policy(run_external_formatter(command, &text, path)?, has_bom, options.bom, TextEncoding::Utf8);
+                Ok(FormatOutcome { changed_ranges: changed_ranges(&content, &formatted), formatted })
+            }
+            None => {
+                let file_type = resolve_file_type(path, options.file_type.as_deref(), &options.extensions, &text)?;
+                format_with_diff(&content, file_type, &options).with_context(|| tr!("代码格式化失败", "Formatting failed"))
+            }
+        }
+    })();
+    (path, result)
+}
+
+/// 标准输入批处理：每行一个 JSON 请求，每个请求在标准输出写一行 JSON 结果
+/// `{"path": ..., "ok": true, "changed": ..., "content": ..., "edits": [...]}` 或 `{"path": ..., "ok": false, "error": ...}`；
+/// `edits` 中每项 `{"start", "end", "line", "text"}` 把请求中原样的 content（含 BOM 与 `\r`）的字节区间替换为 `text`；
+/// 单个请求失败不影响其余请求，读到标准输入结尾时结束
+/// Stdin batch mode: one JSON request per line, and for each request one JSON result line on stdout,
+/// `{"path": ..., "ok": true, "changed": ..., "content": ..., "edits": [...]}` or `{"path": ..., "ok": false, "error": ...}`;
+/// each `{"start", "end", "line", "text"}` in `edits` replaces a byte range of the content exactly as sent (BOM and `\r`
+/// included) with `text`; a failed request doesn't affect the others, and the run ends at the end of stdin
+pub(crate) fn run_stdin_batch(cli: &Cli) -> Result<ExitStatus> {
+    let base_options = FormatOptions::from(cli);
+    let mut stdout = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.with_context(|| tr!("无法读取标准输入", "Cannot read stdin"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (path, result) = handle_batch_request(&line, cli, &base_options);
+        let path = path.as_deref().map_or_else(|| "null".to_string(), json_escape);
+        let response = match result {
+            Ok(FormatOutcome { formatted, changed_ranges }) => {
+                let edits: Vec<String> = changed_ranges
+                    .iter()
+                    .map(|range| {
+                        format!(
+                            "{{\"start\":{},\"end\":{},\"line\":{},\"text\":{}}}",
+                            range.old.start,
+                            range.old.end,
+                            range.lines.start,
+                            json_escape(&formatted[range.new.clone()])
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"path\":{},\"ok\":true,\"changed\":{},\"content\":{},\"edits\":[{}]}}",
+                    path,
+                    !changed_ranges.is_empty(),
+                    json_escape(&formatted),
+                    edits.join(",")
+                )
+            }
+            Err(err) => format!("{{\"path\":{},\"ok\":false,\"error\":{}}}", path, json_escape(&format!("{:#}", err))),
+        };
+        // 每个结果立即写出，调用方可以逐条读取
+        // Each result is written out right away so the caller can read them one by one
+        writeln!(stdout, "{}", response).and_then(|_| stdout.flush()).with_context(|| tr!("无法写入标准输出", "Cannot write to stdout"))?;
+    }
+    Ok(ExitStatus::Clean)
+}
@@ -0,0 +1,628 @@
+//! 命令行参数与程序入口
+//! Command line arguments and program entry point
+
+use anyhow::Result;
+use clap::{ArgAction, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::fmt;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use crate::*;
+
+/// 压缩代码格式化工具：符合行业规范的 HTML/CSS/JS/TS/JSON 格式化（高可读性）
+/// Code formatter for compressed code: Industry-standard HTML/CSS/JS/TS/JSON formatting (high readability)
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub(crate) struct Cli {
+    /// 子命令（省略时执行格式化）
+    /// Subcommand (formatting runs when omitted)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// 输入文件路径（必填，可指定多个；`-` 表示标准输入）
+    /// Input file paths (required, may be repeated; `-` for stdin)
+    #[arg(short = 'i', long = "input", required_unless_present = "stdin_batch", num_args = 1.., help = "输入代码的文件或目录路径，可指定多个（多个文件或目录需配合 --write 或 --check），目录递归查找可识别扩展名的文件，- 表示从标准输入读取")]
+    input: Vec<String>,
+
+    /// 输出文件路径（必填，--check/--write 时不需要；`-` 表示标准输出）
+    /// Output file path (required unless --check/--write; `-` for stdout)
+    #[arg(short = 'o', long = "output", required_unless_present_any = ["check", "write", "stdin_batch", "report_eol"], help = "格式化后代码的输出文件路径，- 表示输出到标准输出")]
+    pub(crate) output: Option<String>,
+
+    /// 原地格式化输入文件（可选）
+    /// Format the input file in place (optional)
+    #[arg(short = 'w', long = "write", conflicts_with_all = ["output", "check"], help = "原地格式化：把结果写回输入文件")]
+    pub(crate) write: bool,
+
+    /// 覆盖前备份原文件（可选，默认后缀 .bak）
+    /// Back up the original file before overwriting it (optional, default suffix .bak)
+    #[arg(long = "backup", num_args = 0..=1, require_equals = true, default_missing_value = ".bak", value_name = "SUFFIX", help = "覆盖已有文件前把原文件备份到同目录的 <文件名><后缀>，默认后缀 .bak")]
+    pub(crate) backup: Option<String>,
+
+    /// 只演练不写入（可选）
+    /// Rehearse without writing (optional)
+    #[arg(long = "dry-run", conflicts_with = "check", help = "完整执行格式化并报告将写入的文件与字节变化，但不修改任何文件")]
+    pub(crate) dry_run: bool,
+
+    /// 输出差异（可选）
+    /// Print differences (optional)
+    #[arg(long = "diff", help = "在 --check 或 --dry-run 模式下输出统一格式的差异")]
+    pub(crate) diff: bool,
+
+    /// 指定代码类型（可选），不依赖扩展名
+    /// Code type override (optional), independent of the extension
+    #[arg(long = "type", help = "代码类型：html/css/js/ts/json（或插件提供的类型），或一个用于推断类型的文件名（如 src/app.ts）；读取标准输入或无扩展名文件时使用")]
+    pub(crate) file_type: Option<String>,
+
+    /// 配置文件路径（可选，默认从输入文件所在目录向上查找 .code_formatter.toml）
+    /// Configuration file path (optional, defaults to searching .code_formatter.toml upwards from the input's directory)
+    #[arg(long = "config", global = true, help = "配置文件路径，默认从输入文件所在目录向上查找 .code_formatter.toml")]
+    pub(crate) config: Option<String>,
+
+    /// WASM 插件目录（可选）
+    /// WASM plugins directory (optional)
+    #[cfg(feature = "plugins")]
+    #[arg(long = "plugins", global = true, value_name = "DIR", help = "加载该目录中的 .wasm 格式化插件，为新的代码类型提供格式化器")]
+    plugins: Option<String>,
+
+    /// 仅检查是否已格式化，不写入文件（可选）
+    /// Only check whether the file is formatted, without writing (optional)
+    #[arg(long = "check", help = "仅检查文件是否已格式化，不写入任何文件；需要格式化时返回非零退出码")]
+    pub(crate) check: bool,
+
+    /// 缩进宽度（可选，默认 4）：空格缩进时为空格数，tab 缩进时为对齐用的 tab 宽度
+    /// Indentation width (optional, defaults to 4): number of spaces, or the tab width used for alignment with tabs
+    #[arg(short = 'n', long = "indent", default_value_t = 4, help = "缩进宽度，默认 4；使用 tab 缩进时作为 tab 宽度计算行长")]
+    pub(crate) indent: u8,
+
+    /// 文件末尾换行策略（可选，默认 always）
+    /// Final newline policy (optional, defaults to always)
+    #[arg(long = "final-newline", value_enum, default_value_t = FinalNewline::Always, help = "文件末尾换行：always（保证一个）/never（去掉）/preserve（与输入一致），默认 always")]
+    pub(crate) final_newline: FinalNewline,
+
+    /// 去除每行行尾空白（可选）
+    /// Strip trailing whitespace on every line (optional)
+    #[arg(long = "trim-trailing-whitespace", help = "去除输出中每行的行尾空白（包括 pre/模板字符串等原样保留的内容）")]
+    pub(crate) trim_trailing_whitespace: bool,
+
+    /// 输入文件编码（可选，默认自动检测）
+    /// Input file encoding (optional, detected automatically by default)
+    #[arg(long = "encoding", value_enum, default_value_t = TextEncoding::Auto, help = "输入文件编码：auto/utf-8/gbk/gb18030/latin1，默认 auto；输出使用相同编码")]
+    pub(crate) encoding: TextEncoding,
+
+    /// UTF-8 BOM 的处理方式（可选，默认 keep）
+    /// How the UTF-8 BOM is handled (optional, defaults to keep)
+    #[arg(long = "bom", value_enum, default_value_t = BomPolicy::Keep, help = "UTF-8 BOM：keep（输入有则保留）/strip（去掉）/add（总是添加），默认 keep")]
+    pub(crate) bom: BomPolicy,
+
+    /// 缩进字符（可选，默认空格）
+    /// Indentation character (optional, defaults to spaces)
+    #[arg(long = "indent-style", value_enum, default_value_t = IndentStyle::Spaces, help = "缩进方式：tabs/spaces，默认 spaces")]
+    pub(crate) indent_style: IndentStyle,
+
+    /// 单行最大长度（可选，默认 80）
+    /// Maximum line length (optional, defaults to 80)
+    #[arg(short = 'l', long = "line-length", default_value_t = 80, help = "单行最大字符长度，默认 80")]
+    pub(crate) line_length: usize,
+
+    /// 规范化 DOCTYPE 与 meta charset（可选）
+    /// Normalize DOCTYPE and meta charset (optional)
+    #[arg(long = "normalize-doctype", help = "将旧式 DOCTYPE 改写为 <!DOCTYPE html> 并置于首行，同时规范 meta charset")]
+    pub(crate) normalize_doctype: bool,
+
+    /// HTML 属性值的引号（可选，默认保持原样）
+    /// Quotes of HTML attribute values (optional, kept as written by default)
+    #[arg(long = "attr-quotes", value_enum, default_value_t = AttrQuotes::Preserve, help = "HTML 属性值的引号：double/single（统一引号、给无引号的值加引号、布尔属性去掉多余的值）/preserve，默认 preserve")]
+    pub(crate) attr_quotes: AttrQuotes,
+
+    /// 注释折行（可选）
+    /// Reflow comments (optional)
+    #[arg(long = "wrap-comments", help = "将过长的注释文本按单行最大长度重新折行（HTML、CSS、JS/TS；反引号内的代码与缩进的代码块保持不变）")]
+    pub(crate) wrap_comments: bool,
+
+    /// HTML 文本的折行方式（可选，默认 always）
+    /// Wrapping of HTML text (optional, defaults to always)
+    #[arg(long = "prose-wrap", value_enum, default_value_t = ProseWrap::Always, help = "HTML 文本的折行方式：always（超出行宽时在空白处折行）/never（从不折行）/preserve（保留原文的换行，不再折行），默认 always")]
+    pub(crate) prose_wrap: ProseWrap,
+
+    /// <head> 与 <body> 是否相对 <html> 缩进（可选，默认缩进）
+    /// Whether <head> and <body> are indented under <html> (optional, indented by default)
+    #[arg(long = "indent-root-elements", action = ArgAction::Set, default_value_t = true, value_name = "BOOL", help = "<head> 与 <body> 是否相对 <html> 缩进一级：true/false，默认 true；false 时二者与 <html> 一样顶格")]
+    pub(crate) indent_root_elements: bool,
+
+    /// 按 JS 规则格式化事件处理属性（可选）
+    /// Format event handler attributes with the JS rules (optional)
+    #[arg(long = "format-event-handlers", help = "按 JS 空格规则格式化 onclick 等 on* 属性的值（结果始终为单行）；style 属性总是按 CSS 声明规则格式化")]
+    pub(crate) format_event_handlers: bool,
+
+    /// CSS 声明排序方式（可选，默认不排序）
+    /// CSS declaration sort order (optional, defaults to none)
+    #[arg(long = "sort-properties", value_enum, default_value_t = SortProperties::None, help = "CSS 声明排序方式：alphabetical/concentric/none，默认 none")]
+    pub(crate) sort_properties: SortProperties,
+
+    /// JSON 对象键的排序方式（可选，默认不排序）
+    /// JSON object key order (optional, defaults to none)
+    #[arg(long = "sort-keys", value_enum, num_args = 0..=1, default_value_t = SortKeys::None, default_missing_value = "alphabetical", help = "JSON 对象键的排序方式：alphabetical（不带值时的默认）/package-json（package.json 顶层字段按惯用顺序，依赖表按字母序）/none，默认 none")]
+    pub(crate) sort_keys: SortKeys,
+
+    /// CSS 单行规则策略（可选，默认 auto）
+    /// CSS single-line rule policy (optional, defaults to auto)
+    #[arg(long = "css-single-line-rules", value_enum, default_value_t = CssSingleLineRules::Auto, help = "CSS 规则压缩为单行的策略：never/auto/always，默认 auto")]
+    pub(crate) css_single_line_rules: CssSingleLineRules,
+
+    /// 规范化 CSS 颜色（可选）
+    /// Normalize CSS colors (optional)
+    #[arg(long = "normalize-colors", help = "十六进制颜色转小写，规范 rgb()/rgba()/hsl()/hsla() 内的空格")]
+    pub(crate) normalize_colors: bool,
+
+    /// 缩写十六进制颜色（可选，需配合 --normalize-colors）
+    /// Shorten hex colors (optional, requires --normalize-colors)
+    #[arg(long = "short-hex-colors", requires = "normalize_colors", help = "将 #ffffff 缩写为 #fff（需配合 --normalize-colors）")]
+    pub(crate) short_hex_colors: bool,
+
+    /// 小数的前导零（可选，默认保持原样）
+    /// Leading zero of decimals (optional, defaults to preserve)
+    #[arg(long = "leading-zero", value_enum, default_value_t = LeadingZero::Preserve, help = "CSS 小数前导零：add（0.5em）/remove（.5em）/preserve，默认 preserve")]
+    pub(crate) leading_zero: LeadingZero,
+
+    /// 去掉零长度的单位（可选）
+    /// Strip units from zero lengths (optional)
+    #[arg(long = "strip-zero-units", help = "将 0px 等零长度写为 0（calc() 等函数与自定义属性内不处理）")]
+    pub(crate) strip_zero_units: bool,
+
+    /// 保证 data URI 原样保留（可选）
+    /// Guarantee data URIs are preserved byte-for-byte (optional)
+    #[arg(long = "preserve-data-uris", help = "校验输出中的 data URI 与输入逐字节一致，不一致时报错")]
+    pub(crate) preserve_data_uris: bool,
+
+    /// 选择器列表拆分为每行一个（可选）
+    /// Split selector lists one per line (optional)
+    #[arg(long = "split-selectors", help = "逗号分隔的选择器列表每行一个，逗号放在行尾")]
+    pub(crate) split_selectors: bool,
+
+    /// JS/TS 中 else/catch/finally 的位置（可选，默认与 } 同行）
+    /// Placement of else/catch/finally in JS/TS (optional, defaults to the same line as })
+    #[arg(long = "else-placement", value_enum, default_value_t = ElsePlacement::SameLine, help = "JS/TS 中 else/catch/finally 的位置：same-line（} else {）/next-line（} 后换行），默认 same-line")]
+    pub(crate) else_placement: ElsePlacement,
+
+    /// switch 中的 case/default 标签缩进一级（可选）
+    /// Indent case/default labels one level inside switch (optional)
+    #[arg(long = "indent-case-labels", help = "JS/TS 中 switch 的 case/default 标签相对 switch 缩进一级（默认与 switch 对齐），标签下的语句再缩进一级")]
+    pub(crate) indent_case_labels: bool,
+
+    /// 运行结束后输出统计摘要（可选）
+    /// Print a statistics summary after the run (optional)
+    #[arg(long = "summary", help = "运行结束后输出统计摘要：文件数、字节数、行数与耗时")]
+    summary: bool,
+
+    /// 报告格式（可选）
+    /// Report format (optional)
+    #[arg(long = "report", value_enum, help = "输出机器可读的运行报告：json/sarif/github（sarif 与 github 需配合 --check；--report-eol 只支持 json）")]
+    pub(crate) report: Option<ReportFormat>,
+
+    /// 报告输出文件（可选，默认标准输出）
+    /// Report output file (optional, defaults to stdout)
+    #[arg(long = "report-file", requires = "report", help = "报告写入的文件路径，默认输出到标准输出")]
+    pub(crate) report_file: Option<String>,
+
+    /// 遇到第一个出错的文件即停止（可选）
+    /// Stop at the first file that fails (optional)
+    #[arg(long = "fail-fast", help = "多文件处理时遇到第一个出错的文件即停止，默认继续处理其余文件并在最后汇总错误")]
+    fail_fast: bool,
+
+    /// 与平台和环境无关的可复现输出（可选）
+    /// Reproducible output independent of platform and environment (optional)
+    #[arg(long = "seedless", help = "保证输出与报告在不同平台与环境下逐字节一致：报告中的路径统一用 /，耗时记为 0，未指定 --lang 时消息使用英文")]
+    seedless: bool,
+
+    /// 目录遍历时跟随符号链接（可选）
+    /// Follow symlinks while walking directories (optional)
+    #[arg(long = "follow-symlinks", help = "输入为目录时跟随其中的符号链接，默认跳过")]
+    follow_symlinks: bool,
+
+    /// 目录遍历时包含隐藏文件与目录（可选）
+    /// Include hidden files and directories while walking directories (optional)
+    #[arg(long = "hidden", help = "输入为目录时包含以 . 开头的隐藏文件与目录，默认跳过")]
+    hidden: bool,
+
+    /// 目录遍历的最大深度（可选，默认不限）
+    /// Maximum depth of directory traversal (optional, unlimited by default)
+    #[arg(long = "max-depth", value_name = "N", help = "输入为目录时的最大遍历深度，目录中直接包含的文件深度为 1，默认不限")]
+    max_depth: Option<usize>,
+
+    /// 文件大小上限（可选）
+    /// File size limit (optional)
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size, help = "跳过超过此大小的文件并给出警告（如 500K、2M），默认不限")]
+    max_file_size: Option<u64>,
+
+    /// 只处理在此之后修改过的文件（可选）
+    /// Only process files modified after this point (optional)
+    #[arg(long = "since", value_name = "TIME|REF|PATH", help = "只处理在此之后修改过的文件：已有文件的路径（取其修改时间）、Unix 秒数、UTC 时间（如 2024-05-01 或 2024-05-01T08:30:00），或 git 引用（取其提交时间）")]
+    since: Option<String>,
+
+    /// 时间戳文件（可选）
+    /// Stamp file (optional)
+    #[arg(long = "stamp", value_name = "PATH", help = "只处理在该文件修改时间之后修改过的文件（文件不存在时处理全部），运行无错误后把它更新为本次运行的开始时间；--check 时仅在全部文件已格式化时更新")]
+    stamp: Option<String>,
+
+    /// 空白规范检查模式（可选）
+    /// Whitespace hygiene report mode (optional)
+    #[arg(long = "report-eol", conflicts_with_all = ["output", "check", "dry_run", "stdin_batch"], help = "不格式化，只检查并列出每个文件的混合换行符、tab 与空格混用的缩进以及行尾空白；配合 --write 时就地修复（换行符与缩进统一为文件中占多数的写法）")]
+    report_eol: bool,
+
+    /// 标准输入批处理模式（可选）
+    /// Stdin batch mode (optional)
+    #[arg(long = "stdin-batch", conflicts_with_all = ["input", "output", "write", "check"], help = "从标准输入逐行读取 JSON 请求 {\"path\", \"content\", \"options\"}，每个请求在标准输出写一行 JSON 结果，供构建工具在一个进程中格式化多个内存缓冲区")]
+    stdin_batch: bool,
+
+    /// 输入有结构错误时尽量格式化（可选）
+    /// Format malformed input on a best-effort basis (optional)
+    #[arg(long = "lenient", help = "输入有结构错误（多余的 }、未闭合的标签、被截断的文件等）时尽量格式化其余部分并给出警告，而不是报错")]
+    pub(crate) lenient: bool,
+
+    /// 嵌套层数上限（可选，默认 256）
+    /// Nesting depth limit (optional, defaults to 256)
+    #[arg(long = "max-nesting", default_value_t = 256, help = "括号与 HTML 块级元素嵌套层数的上限，超过时报错而不是输出过深的缩进，默认 256")]
+    pub(crate) max_nesting: usize,
+
+    /// 单行词法单元数上限（可选，默认 100000）
+    /// Limit on tokens per line (optional, defaults to 100000)
+    #[arg(long = "max-line-tokens", default_value_t = 100_000, help = "JS/TS/CSS 中一条语句（两个 ; { } 之间）的词法单元数上限，超过时报错，默认 100000")]
+    pub(crate) max_line_tokens: usize,
+
+    /// 校验格式化结果是稳定的（可选）
+    /// Verify the formatted result is stable (optional)
+    #[arg(long = "verify-stable", help = "对格式化结果再格式化一次，两次结果不同时报错并输出差异（保证格式化是幂等的）")]
+    pub(crate) verify_stable: bool,
+
+    /// 导致非零退出码的最低诊断级别（可选，默认 errors）
+    /// Lowest diagnostic severity that causes a non-zero exit code (optional, defaults to errors)
+    #[arg(long = "fail-on", value_enum, default_value_t = FailOn::Errors, help = "何种诊断导致非零退出码：warnings/errors/never")]
+    fail_on: FailOn,
+
+    /// 只输出错误（可选）
+    /// Only print errors (optional)
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose", help = "只输出错误信息")]
+    pub(crate) quiet: bool,
+
+    /// 输出详细信息（可选，可重复）
+    /// Print detailed information (optional, repeatable)
+    #[arg(short = 'v', long = "verbose", global = true, action = ArgAction::Count, help = "-v 输出每个文件的详细信息，-vv 额外输出格式化器的 token 与状态跟踪")]
+    pub(crate) verbose: u8,
+
+    /// 样式预设（可选）
+    /// Style preset (optional)
+    #[arg(long = "preset", value_name = "NAME", help = "样式预设：default/compact/readable/2-space，或配置文件 [presets.<名称>] 中定义的预设；命令行显式给出的选项优先")]
+    pub(crate) preset: Option<String>,
+
+    /// 命令行上显式给出的参数（用于判断预设与配置不能覆盖哪些选项）
+    /// Arguments given explicitly on the command line (options that presets and the config must not override)
+    #[arg(skip)]
+    pub(crate) explicit: Vec<String>,
+
+    /// 界面语言（可选，默认根据 LANG 环境变量）
+    /// Interface language (optional, defaults from the LANG environment variable)
+    #[arg(long = "lang", global = true, value_enum, help = "界面语言：zh/en，默认根据 LC_ALL/LC_MESSAGES/LANG 环境变量")]
+    lang: Option<Lang>,
+}
+
+/// 导致运行失败的诊断级别
+/// Diagnostic severity that fails the run
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum FailOn {
+    /// 警告与错误都导致失败
+    /// Both warnings and errors fail the run
+    Warnings,
+    /// 仅错误导致失败
+    /// Only errors fail the run
+    Errors,
+    /// 诊断不影响退出码
+    /// Diagnostics never affect the exit code
+    Never,
+}
+
+/// 进程退出码：0 全部通过，1 有文件需要格式化，2 解析/格式化错误，3 I/O 或配置错误
+/// Process exit code: 0 all clean, 1 files need formatting, 2 parse/format errors, 3 I/O or configuration errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ExitStatus {
+    Clean = 0,
+    NeedsFormatting = 1,
+    FormatError = 2,
+    IoOrConfigError = 3,
+}
+
+impl ExitStatus {
+    /// 根据错误链判断退出码：I/O 与配置错误为 3，其余为 2
+    /// Classify an error chain: I/O and configuration errors map to 3, everything else to 2
+    pub(crate) fn of_error(err: &anyhow::Error) -> Self {
+        if err.chain().any(|cause| cause.is::<std::io::Error>() || cause.is::<ConfigError>()) {
+            ExitStatus::IoOrConfigError
+        } else {
+            ExitStatus::FormatError
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+/// 配置错误：无效的参数组合、不支持的文件类型等
+/// Configuration error: invalid option combinations, unsupported file types, etc.
+#[derive(Debug)]
+pub(crate) struct ConfigError(pub(crate) String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 子命令
+/// Subcommands
+#[derive(Subcommand, Debug)]
+pub(crate) enum Command {
+    /// 压缩代码：删除注释与多余空白（格式化的逆操作）
+    /// Minify code: strip comments and unnecessary whitespace (the reverse of formatting)
+    Minify {
+        /// 输入文件路径（必填，`-` 表示标准输入）
+        /// Input file path (required, `-` for stdin)
+        #[arg(short = 'i', long = "input", help = "输入代码的文件路径，- 表示从标准输入读取")]
+        input: String,
+
+        /// 输出文件路径（必填，`-` 表示标准输出）
+        /// Output file path (required, `-` for stdout)
+        #[arg(short = 'o', long = "output", help = "压缩后代码的输出文件路径，- 表示输出到标准输出")]
+        output: String,
+
+        /// 指定代码类型（可选），不依赖扩展名
+        /// Code type override (optional), independent of the extension
+        #[arg(long = "type", help = "代码类型：html/css/js/ts/json（或插件提供的类型），或一个用于推断类型的文件名")]
+        file_type: Option<String>,
+    },
+    /// 校验语法结构：报告不平衡的括号、未闭合的字符串/注释与不配对的 HTML 标签，不修改文件
+    /// Validate the syntax structure: report unbalanced brackets, unclosed strings/comments and unpaired HTML tags without modifying files
+    Validate {
+        /// 输入文件路径（必填，可指定多个；`-` 表示标准输入）
+        /// Input file paths (required, may be repeated; `-` for stdin)
+        #[arg(short = 'i', long = "input", required = true, num_args = 1.., help = "要校验的文件路径，可指定多个，- 表示从标准输入读取")]
+        input: Vec<String>,
+
+        /// 指定代码类型（可选），不依赖扩展名
+        /// Code type override (optional), independent of the extension
+        #[arg(long = "type", help = "代码类型：html/css/js/ts/json（或插件提供的类型），或一个用于推断类型的文件名")]
+        file_type: Option<String>,
+    },
+    /// 输出格式化器内部的词法单元与状态流（标签开闭、字符串/注释/括号、缩进层级变化），便于附在问题报告中
+    /// Dump the internal token and state stream of the formatter (tag open/close, strings/comments/brackets, indent level
+    /// changes), for attaching to bug reports
+    DebugTokens {
+        /// 输入文件路径（必填的位置参数，`-` 表示标准输入）
+        /// Input file path (required positional argument, `-` for stdin)
+        #[arg(value_name = "FILE", help = "输入代码的文件路径，- 表示从标准输入读取")]
+        input: String,
+
+        /// 指定代码类型（可选），不依赖扩展名
+        /// Code type override (optional), independent of the extension
+        #[arg(long = "type", help = "代码类型：html/css/js/ts/json（或插件提供的类型），或一个用于推断类型的文件名")]
+        file_type: Option<String>,
+    },
+}
+
+/// 执行一次格式化运行，返回退出码
+/// Execute one formatting run and return its exit status
+pub(crate) fn run(cli: &Cli) -> Result<ExitStatus> {
+    // 报告或格式化结果占用标准输出时，日志改写到标准错误，保证输出可被直接解析
+    // When a report or the formatted result occupies stdout, logs go to stderr so the output stays machine-readable
+    let stdout_reserved = match &cli.command {
+        Some(Command::Minify { output, .. }) => output == "-",
+        Some(Command::Validate { .. } | Command::DebugTokens { .. }) => false,
+        None => (cli.report.is_some() && cli.report_file.is_none()) || cli.output.as_deref() == Some("-") || cli.stdin_batch,
+    };
+    init_logging(cli.log_level(), stdout_reserved);
+    #[cfg(feature = "plugins")]
+    if let Some(dir) = &cli.plugins {
+        load_plugins(dir)?;
+    }
+    if let Some(Command::Minify { input, output, file_type }) = &cli.command {
+        let config = Config::discover(cli.config.as_deref(), input)?;
+        run_minify(input, output, file_type.as_deref(), &config)?;
+        return Ok(ExitStatus::Clean);
+    }
+    if let Some(Command::Validate { input, file_type }) = &cli.command {
+        return Ok(run_validate(input, file_type.as_deref(), cli.config.as_deref()));
+    }
+    if let Some(Command::DebugTokens { input, file_type }) = &cli.command {
+        return run_debug_tokens(input, file_type.as_deref(), cli);
+    }
+    if cli.stdin_batch {
+        return run_stdin_batch(cli);
+    }
+    if cli.input.is_empty() {
+        return Err(ConfigError(tr!("缺少输入文件路径", "Missing input file path")).into());
+    }
+    let walk = WalkOptions { follow_symlinks: cli.follow_symlinks, hidden: cli.hidden, max_depth: cli.max_depth, config: cli.config.clone() };
+    let mut inputs = expand_inputs(&cli.input, &walk)?;
+    // 报告中的路径在各平台上统一使用 /
+    // Paths in reports use / on every platform
+    if cli.seedless && std::path::MAIN_SEPARATOR == '\\' {
+        inputs.iter_mut().for_each(|input| *input = input.replace('\\', "/"));
+    }
+    if inputs.len() > 1 || cli.input.iter().any(|input| Path::new(input).is_dir()) {
+        if inputs.iter().any(|input| input == "-") {
+            return Err(ConfigError(tr!("标准输入不能与其他输入文件一起使用", "Standard input cannot be combined with other input files")).into());
+        }
+        if !cli.write && !cli.check && !cli.report_eol {
+            return Err(ConfigError(tr!("多个输入文件或目录时需要 --write 或 --check", "Several input files or a directory require --write or --check")).into());
+        }
+    }
+    if cli.write && inputs.iter().any(|input| input == "-") {
+        return Err(ConfigError(tr!("--write 不能用于标准输入", "--write cannot be used with standard input")).into());
+    }
+    if cli.report_eol {
+        if matches!(cli.report, Some(ReportFormat::Sarif | ReportFormat::Github)) {
+            return Err(ConfigError(tr!("--report-eol 只支持 --report json", "--report-eol only supports --report json")).into());
+        }
+        return run_eol_report(&inputs, cli);
+    }
+    if cli.diff && !cli.check && !cli.dry_run {
+        return Err(ConfigError(tr!("--diff 需要配合 --check 或 --dry-run 使用", "--diff requires --check or --dry-run")).into());
+    }
+    if matches!(cli.report, Some(ReportFormat::Sarif | ReportFormat::Github)) && !cli.check {
+        return Err(ConfigError(tr!("--report sarif/github 需要配合 --check 使用", "--report sarif/github requires --check")).into());
+    }
+    if cli.output.is_none() && !cli.check && !cli.write {
+        return Err(ConfigError(tr!("缺少输出文件路径", "Missing output file path")).into());
+    }
+    let base_options = FormatOptions::from(cli);
+    // 早于 --since 与时间戳文件中较晚者的文件不处理
+    // Files not modified after the later of --since and the stamp file are left out
+    let run_started_at = std::time::SystemTime::now();
+    let since = match &cli.since {
+        Some(value) => Some(resolve_since(value)?),
+        None => None,
+    };
+    let stamp_time = cli.stamp.as_deref().and_then(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok());
+    let since = since.max(stamp_time);
+
+    // 逐个处理文件：出错的文件记录下来并继续，--fail-fast 时在第一个错误处停止
+    // Process files one by one: failures are recorded and the run continues, --fail-fast stops at the first error
+    let started = Instant::now();
+    let mut summary = RunSummary::default();
+    let mut outcomes = Vec::new();
+    let mut config_path = None;
+    for (index, input) in inputs.iter().enumerate() {
+        let file_started = Instant::now();
+        let metadata = std::fs::metadata(input).ok().filter(|_| input != "-");
+        if let (Some(modified), Some(since)) = (metadata.as_ref().and_then(|metadata| metadata.modified().ok()), since) {
+            if modified <= since {
+                let cutoff = since.duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64);
+                log(
+                    LogLevel::Verbose,
+                    tr!("[INFO] 跳过 {}：{} 之后未修改", "[INFO] Skipping {}: not modified since {}", input, format_utc_time(cutoff)),
+                );
+                summary.skipped += 1;
+                continue;
+            }
+        }
+        let size = metadata.map(|metadata| metadata.len());
+        if let (Some(size), Some(limit)) = (size, cli.max_file_size) {
+            if size > limit {
+                log(LogLevel::Normal, tr!("[WARN] 跳过 {}：{} 字节，超过 --max-file-size（{} 字节）", "[WARN] Skipping {}: {} bytes exceeds --max-file-size ({} bytes)", input, size, limit));
+                summary.skipped += 1;
+                continue;
+            }
+        }
+        let mode = cli.output_mode(input);
+        let result = Config::discover(cli.config.as_deref(), input).and_then(|config| {
+            if config.path.is_some() && config.path != config_path {
+                log(LogLevel::Verbose, tr!("[INFO] 使用配置文件：{}", "[INFO] Using config file: {}", config.path.as_deref().unwrap_or_default()));
+            }
+            config_path = config.path.clone();
+            format_file(input, mode, &file_options(cli, &base_options, config)?)
+        });
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                eprintln!("{}", render_error(&err, input));
+                FileOutcome::failed(input, mode.target().unwrap_or_default(), &err, file_started.elapsed())
+            }
+        };
+        let failed = outcome.status == FileStatus::Errored;
+        summary.record(&outcome);
+        outcomes.push(outcome);
+        if failed && cli.fail_fast {
+            let remaining = inputs.len() - index - 1;
+            summary.skipped += remaining;
+            if remaining > 0 {
+                log(LogLevel::Normal, tr!("[INFO] --fail-fast：跳过剩余 {} 个文件", "[INFO] --fail-fast: skipped the remaining {} files", remaining));
+            }
+            break;
+        }
+    }
+
+    // 耗时随机器与负载变化，--seedless 时记为 0
+    // Timings vary with the machine and its load, --seedless records them as 0
+    let elapsed = if cli.seedless { Duration::ZERO } else { started.elapsed() };
+    if cli.seedless {
+        outcomes.iter_mut().for_each(|outcome| outcome.duration = Duration::ZERO);
+    }
+    if cli.summary {
+        summary.print(elapsed);
+    }
+    match cli.report {
+        Some(ReportFormat::Json) => write_report(&render_json_report(&outcomes, &summary, elapsed), cli.report_file.as_deref())?,
+        Some(ReportFormat::Sarif) => write_report(&render_sarif_report(&outcomes), cli.report_file.as_deref())?,
+        Some(ReportFormat::Github) => write_report(&render_github_report(&outcomes), cli.report_file.as_deref())?,
+        None => {}
+    }
+
+    // 汇总退出码：--fail-on 决定诊断是否计入，需要格式化始终计入
+    // Combine exit statuses: --fail-on decides whether diagnostics count, needing formatting always does
+    let status = outcomes
+        .iter()
+        .map(|outcome| {
+            let status = match outcome.exit_status {
+                ExitStatus::FormatError | ExitStatus::IoOrConfigError if cli.fail_on == FailOn::Never => ExitStatus::Clean,
+                status => status,
+            };
+            if cli.fail_on == FailOn::Warnings && outcome.has(Severity::Warning) {
+                status.max(ExitStatus::FormatError)
+            } else {
+                status
+            }
+        })
+        .max()
+        .unwrap_or(ExitStatus::Clean);
+    // 有文件出错、只是演练或检查发现未格式化的文件时，下次仍需处理这些文件，不更新时间戳
+    // When a file failed, the run was a rehearsal, or a check found unformatted files, those files still need handling
+    // next time, so the stamp stays as it is
+    if let Some(stamp) = &cli.stamp {
+        let pending = outcomes.iter().any(|outcome| outcome.exit_status != ExitStatus::Clean);
+        if summary.errored == 0 && !pending && !cli.dry_run {
+            touch_stamp(stamp, run_started_at)?;
+        }
+    }
+    Ok(status)
+}
+
+/// 命令行入口：解析参数、执行并返回退出码（二进制程序的 `main` 只调用此函数）
+/// Command line entry point: parse the arguments, run and return the exit code (the binary's `main` only calls this)
+pub fn cli_main() -> ExitCode {
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let lossy: Vec<String> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    // --seedless 时消息不随区域设置变化
+    // With --seedless messages don't follow the locale
+    let seedless = lossy.iter().skip(1).take_while(|arg| *arg != "--").any(|arg| arg == "--seedless");
+    set_lang(Lang::from_args(&lossy).unwrap_or_else(|| if seedless { Lang::En } else { Lang::from_env() }));
+    let parsed = localized_command(current_lang()).try_get_matches_from(&args).and_then(|matches| {
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        cli.explicit = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(clap::parser::ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect();
+        Ok(cli)
+    });
+    let cli = match parsed {
+        Ok(cli) => cli,
+        Err(err) => {
+            // --help/--version 正常退出，参数错误视为配置错误
+            // --help/--version exit normally, argument errors count as configuration errors
+            let _ = err.print();
+            return if err.use_stderr() { ExitStatus::IoOrConfigError } else { ExitStatus::Clean }.into();
+        }
+    };
+    match run(&cli) {
+        Ok(status) => status.into(),
+        Err(err) => {
+            eprintln!("{}", tr!("错误：{:?}", "Error: {:?}", err));
+            ExitStatus::of_error(&err).into()
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! 配置文件（TOML 子集）
+//! Configuration file (TOML subset)
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use crate::*;
+
+/// 默认配置文件名，从输入文件所在目录向上查找
+/// Default configuration file name, searched upwards from the input file's directory
+pub(crate) const CONFIG_FILE_NAME: &str = ".code_formatter.toml";
+
+/// 配置文件中的值
+/// Value in the configuration file
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ConfigValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    pub(crate) fn type_name(&self) -> String {
+        match self {
+            ConfigValue::Str(_) => tr!("字符串", "a string"),
+            ConfigValue::Int(_) => tr!("整数", "an integer"),
+            ConfigValue::Bool(_) => tr!("布尔值", "a boolean"),
+            ConfigValue::Array(_) => tr!("数组", "an array"),
+        }
+    }
+}
+
+/// 配置文件中的一项：所在表、键、值与行号
+/// One entry of the configuration file: table, key, value and line number
+#[derive(Debug, Clone)]
+struct ConfigEntry {
+    table: String,
+    key: String,
+    value: ConfigValue,
+    line: usize,
+}
+
+/// 解析后的配置
+/// Parsed configuration
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Config {
+    /// 配置文件路径（未使用配置文件时为 None）
+    /// Path of the configuration file (None when no file is used)
+    pub(crate) path: Option<String>,
+    /// 自定义扩展名（小写，不含点）→ 代码类型
+    /// Custom extension (lowercase, without dot) → code type
+    pub(crate) extensions: Vec<(String, &'static str)>,
+    /// 文件名模式 → 外部格式化命令
+    /// File name pattern → external formatter command
+    pub(crate) external: Vec<(String, String)>,
+    /// 使用的预设名称
+    /// Name of the preset to use
+    pub(crate) preset: Option<String>,
+    /// 用户定义的预设：名称 → 设置
+    /// User-defined presets: name → settings
+    pub(crate) presets: Vec<(String, Vec<Setting>)>,
+    /// 按代码类型覆盖的设置：代码类型 → 设置
+    /// Settings overridden per code type: code type → settings
+    pub(crate) overrides: Vec<(&'static str, Vec<Setting>)>,
+}
+
+/// 解析引号包围的键或字符串，返回内容与剩余文本
+/// Parse a quoted key or string, returning the content and the remaining text
+fn parse_config_string(text: &str) -> Option<(String, &str)> {
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut value = String::new();
+    let mut chars = text[1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Some((value, &text[i + 2..])),
+            // 单引号为字面量字符串，不处理转义
+            // Single quotes are literal strings without escapes
+            '\\' if quote == '"' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                escaped => value.push(escaped),
+            },
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+/// 解析一个值，返回值与剩余文本
+/// Parse one value, returning it and the remaining text
+pub(crate) fn parse_config_value(text: &str) -> Option<(ConfigValue, &str)> {
+    let text = text.trim_start();
+    if text.starts_with(['"', '\'']) {
+        let (value, rest) = parse_config_string(text)?;
+        return Some((ConfigValue::Str(value), rest));
+    }
+    if let Some(mut rest) = text.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Some((ConfigValue::Array(items), after));
+            }
+            let (item, after) = parse_config_value(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    }
+    let end = text.find(|c: char| c == ',' || c == ']' || c.is_whitespace()).unwrap_or(text.len());
+    let value = match &text[..end] {
+        "true" => ConfigValue::Bool(true),
+        "false" => ConfigValue::Bool(false),
+        number => ConfigValue::Int(number.replace('_', "").parse().ok()?),
+    };
+    Some((value, &text[end..]))
+}
+
+/// 解析 TOML 子集：`[表]`、`键 = 值`（字符串、整数、布尔值、单行数组）与 `#` 注释
+/// Parse the TOML subset: `[table]`, `key = value` (strings, integers, booleans, single-line arrays) and `#` comments
+fn parse_config(text: &str, path: &str) -> Result<Vec<ConfigEntry>> {
+    let mut entries = Vec::new();
+    let mut table = String::new();
+    for (index, raw_line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ConfigError(tr!("配置文件 {} 第 {} 行：{}", "Config file {} line {}: {}", path, line_number, message));
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header.split('#').next().unwrap_or_default().trim_end();
+            table = name.strip_suffix(']').ok_or_else(|| error(tr!("表头缺少 ]", "table header is missing ]")))?.trim().to_string();
+            continue;
+        }
+
+        let (key, rest) = if line.starts_with(['"', '\'']) {
+            parse_config_string(line).ok_or_else(|| error(tr!("键的引号未闭合", "unclosed quote in key")))?
+        } else {
+            let end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')).unwrap_or(line.len());
+            (line[..end].to_string(), &line[end..])
+        };
+        if key.is_empty() {
+            return Err(error(tr!("缺少键名", "missing key")).into());
+        }
+        let rest = rest.trim_start().strip_prefix('=').ok_or_else(|| error(tr!("键后缺少 =", "missing = after key")))?;
+        let (value, rest) = parse_config_value(rest).ok_or_else(|| error(tr!("无法解析的值", "invalid value")))?;
+        let rest = rest.trim();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(error(tr!("值后有多余内容：{}", "unexpected content after value: {}", rest)).into());
+        }
+        entries.push(ConfigEntry { table: table.clone(), key, value, line: line_number });
+    }
+    Ok(entries)
+}
+
+impl Config {
+    /// 读取并校验配置文件
+    /// Read and validate a configuration file
+    fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| tr!("无法读取配置文件：{}", "Cannot read config file: {}", path))?;
+        let mut config = Config { path: Some(path.to_string()), ..Config::default() };
+        for entry in parse_config(&text, path)? {
+            let error = |message: String| ConfigError(tr!("配置文件 {} 第 {} 行：{}", "Config file {} line {}: {}", path, entry.line, message));
+            match entry.table.as_str() {
+                // [extensions]：扩展名 = "代码类型"
+                // [extensions]: extension = "code type"
+                "extensions" => {
+                    let ConfigValue::Str(name) = &entry.value else {
+                        return Err(error(tr!("扩展名映射应为字符串，实际为{}", "extension mapping must be a string, found {}", entry.value.type_name())).into());
+                    };
+                    let file_type = file_type_from_name(name)
+                        .ok_or_else(|| error(tr!("未知的代码类型：{}，仅支持 {}", "unknown code type: {}, only {} are supported", name, supported_types())))?;
+                    let extension = entry.key.trim_start_matches('.').to_lowercase();
+                    config.extensions.retain(|(existing, _)| *existing != extension);
+                    config.extensions.push((extension, file_type));
+                }
+                // [external]："文件名模式" = "外部格式化命令"
+                // [external]: "file name pattern" = "external formatter command"
+                "external" => {
+                    let ConfigValue::Str(command) = &entry.value else {
+                        return Err(error(tr!("外部格式化命令应为字符串，实际为{}", "external formatter command must be a string, found {}", entry.value.type_name())).into());
+                    };
+                    if command.split_whitespace().next().is_none() {
+                        return Err(error(tr!("外部格式化命令为空", "external formatter command is empty")).into());
+                    }
+                    config.external.retain(|(pattern, _)| *pattern != entry.key);
+                    config.external.push((entry.key.clone(), command.clone()));
+                }
+                // preset = "预设名称"
+                // preset = "preset name"
+                "" if entry.key == "preset" => {
+                    let ConfigValue::Str(name) = &entry.value else {
+                        return Err(error(tr!("预设名称应为字符串，实际为{}", "preset name must be a string, found {}", entry.value.type_name())).into());
+                    };
+                    config.preset = Some(name.clone());
+                }
+                // [presets.名称]：设置 = 值
+                // [presets.name]: setting = value
+                table if table.starts_with("presets.") => {
+                    let name = table.trim_start_matches("presets.").trim_matches(|c| c == '"' || c == '\'');
+                    let setting = Setting::parse(&entry.key, &entry.value).map_err(error)?;
+                    let index = match config.presets.iter().position(|(preset, _)| preset == name) {
+                        Some(index) => index,
+                        None => {
+                            config.presets.push((name.to_string(), Vec::new()));
+                            config.presets.len() - 1
+                        }
+                    };
+                    let settings = &mut config.presets[index].1;
+                    settings.retain(|existing| existing.key() != setting.key());
+                    settings.push(setting);
+                }
+                // [代码类型]：设置 = 值，只作用于该类型的文件
+                // [code type]: setting = value, only applies to files of that type
+                table if file_type_from_name(table).is_some() => {
+                    let file_type = file_type_from_name(table).unwrap_or_default();
+                    let setting = Setting::parse(&entry.key, &entry.value).map_err(error)?;
+                    let index = match config.overrides.iter().position(|(existing, _)| *existing == file_type) {
+                        Some(index) => index,
+                        None => {
+                            config.overrides.push((file_type, Vec::new()));
+                            config.overrides.len() - 1
+                        }
+                    };
+                    let settings = &mut config.overrides[index].1;
+                    settings.retain(|existing| existing.key() != setting.key());
+                    settings.push(setting);
+                }
+                table => {
+                    return Err(error(tr!("未知的配置项：{}", "unknown setting: {}", if table.is_empty() { entry.key.clone() } else { format!("{}.{}", table, entry.key) })).into())
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// 确定使用的配置：显式指定的文件，或从 `start` 所在目录向上查找默认配置文件
+    /// Determine the configuration: the explicit file, or the default file searched upwards from the directory of `start`
+    pub(crate) fn discover(explicit: Option<&str>, start: &str) -> Result<Self> {
+        if let Some(path) = explicit {
+            return Config::load(path);
+        }
+        let start = if start == "-" { Path::new(".") } else { Path::new(start).parent().unwrap_or(Path::new(".")) };
+        let start = std::fs::canonicalize(if start.as_os_str().is_empty() { Path::new(".") } else { start });
+        let Ok(start) = start else {
+            return Ok(Config::default());
+        };
+        for dir in start.ancestors() {
+            let candidate = dir.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Config::load(&candidate.to_string_lossy());
+            }
+        }
+        Ok(Config::default())
+    }
+}
@@ -0,0 +1,888 @@
+//! CSS 格式化（递归块结构 + 完整缩进）
+//! CSS Formatting (recursive block structure + full indentation)
+
+use anyhow::Result;
+use crate::*;
+
+/// CSS 语法树节点
+/// CSS syntax tree node
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CssNode {
+    /// 注释 `/* ... */`
+    /// Comment `/* ... */`
+    Comment(String),
+    /// 与前一个语句、声明或块写在同一行的注释（如 `color: red; /* brand */`），输出时留在该行末尾
+    /// A comment on the same line as the preceding statement, declaration or block (such as `color: red; /* brand */`),
+    /// kept at the end of that line
+    TrailingComment(String),
+    /// 以分号结束、不带块的语句（如 `@import url(a.css);`）
+    /// Statement terminated by a semicolon without a block (e.g. `@import url(a.css);`)
+    Statement(String),
+    /// 块内声明（如 `color:red`），属性与值分开保存
+    /// Declaration inside a block (e.g. `color:red`), property and value kept apart
+    Declaration { property: String, value: String },
+    /// 带块的规则：选择器规则或 at-rule（块内可继续嵌套）
+    /// Rule with a block: selector rule or at-rule (blocks may nest further)
+    Block { prelude: String, children: Vec<CssNode> },
+}
+
+/// 原样读取引号字符串的剩余部分（开头的引号已读取，支持转义）
+/// Copy the rest of a quoted string verbatim (opening quote already consumed, escapes supported)
+pub(crate) fn take_css_string<I: Iterator<Item = char>>(quote: char, chars: &mut std::iter::Peekable<I>, out: &mut String) {
+    while let Some(c) = chars.next() {
+        out.push(c);
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+            }
+        } else if c == quote {
+            break;
+        }
+    }
+}
+
+/// 原样读取 `url(` 之后直到对应 `)` 的内容（data URI 中的 `;`、`:` 与空白不能改动）
+/// Copy everything after `url(` up to its closing `)` verbatim (`;`, `:` and whitespace inside data URIs must not change)
+pub(crate) fn take_css_url_body<I: Iterator<Item = char>>(chars: &mut std::iter::Peekable<I>, out: &mut String) {
+    while let Some(c) = chars.next() {
+        out.push(c);
+        match c {
+            '"' | '\'' => take_css_string(c, chars, out),
+            ')' => break,
+            _ => {}
+        }
+    }
+}
+
+/// 文本是否以 `url` 结尾（紧接着的 `(` 开始一个 URL）
+/// Whether text ends with `url` (so a following `(` starts a URL)
+pub(crate) fn ends_with_url(text: &str) -> bool {
+    text.len() >= 3 && text.get(text.len() - 3..).is_some_and(|tail| tail.eq_ignore_ascii_case("url"))
+}
+
+/// 递归解析 CSS：遇到 `{` 进入子块，遇到 `}` 返回上一层
+/// Parse CSS recursively: `{` descends into a child block, `}` returns to the parent
+pub(crate) fn parse_css_nodes<I: Iterator<Item = char>>(chars: &mut std::iter::Peekable<I>, depth: usize) -> Vec<CssNode> {
+    let mut nodes = Vec::new();
+    let mut buffer = String::new();
+    let mut paren_depth = 0usize;
+    // 上一个节点结束后是否换过行（决定其后的注释是否与它同行）
+    // Whether a line break followed the end of the previous node (decides whether a comment after it shares its line)
+    let mut line_broken = true;
+
+    // 缓冲区内容作为语句/声明结束
+    // Finish the buffered text as a statement/declaration
+    let flush = |buffer: &mut String, nodes: &mut Vec<CssNode>| {
+        let text = buffer.trim().to_string();
+        buffer.clear();
+        if text.is_empty() {
+            return;
+        }
+        match text.split_once(':') {
+            Some((property, value)) if depth > 0 && !text.starts_with('@') => nodes.push(CssNode::Declaration {
+                property: property.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => nodes.push(CssNode::Statement(text)),
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut comment = String::from("/*");
+                while let Some(cc) = chars.next() {
+                    comment.push(cc);
+                    if cc == '*' && chars.peek() == Some(&'/') {
+                        comment.push(chars.next().unwrap());
+                        break;
+                    }
+                }
+                // 声明值中间的注释留在值里；紧跟在节点后同一行的注释附在该节点的行尾
+                // A comment in the middle of a declaration value stays in the value; one right after a node on the same
+                // line is attached to the end of that node's line
+                if depth > 0 && buffer.contains(':') {
+                    if !buffer.ends_with(' ') {
+                        buffer.push(' ');
+                    }
+                    buffer.push_str(&comment);
+                } else if !line_broken && buffer.trim().is_empty() && nodes.last().is_some_and(|node| !matches!(node, CssNode::Comment(_))) {
+                    nodes.push(CssNode::TrailingComment(comment));
+                } else {
+                    nodes.push(CssNode::Comment(comment));
+                }
+            }
+            '"' | '\'' => {
+                // 字符串原样保留（支持转义）
+                // Strings are kept verbatim (escapes supported)
+                buffer.push(c);
+                take_css_string(c, chars, &mut buffer);
+            }
+            '(' if ends_with_url(&buffer) => {
+                buffer.push(c);
+                take_css_url_body(chars, &mut buffer);
+            }
+            '(' => {
+                paren_depth += 1;
+                buffer.push(c);
+            }
+            ')' => {
+                paren_depth = paren_depth.saturating_sub(1);
+                buffer.push(c);
+            }
+            ';' if paren_depth == 0 => {
+                flush(&mut buffer, &mut nodes);
+                line_broken = false;
+            }
+            '{' => {
+                let prelude = buffer.trim().to_string();
+                buffer.clear();
+                paren_depth = 0;
+                let children = parse_css_nodes(chars, depth + 1);
+                nodes.push(CssNode::Block { prelude, children });
+                line_broken = false;
+            }
+            '}' => {
+                flush(&mut buffer, &mut nodes);
+                return nodes;
+            }
+            _ => {
+                line_broken |= c == '\n';
+                if c.is_whitespace() && buffer.ends_with(&[' ', '\t'][..]) {
+                    continue;
+                }
+                buffer.push(if c.is_whitespace() { ' ' } else { c });
+            }
+        }
+    }
+    flush(&mut buffer, &mut nodes);
+    nodes
+}
+
+/// 规范选择器/at-rule 前导中的空白：逗号后一个空格，组合符两侧各一个空格，
+/// at-rule 括号内的 `:` 后加空格；括号、方括号与字符串内的内容不改动组合符
+/// Normalize whitespace in selector/at-rule preludes: one space after commas, one space around
+/// combinators, a space after `:` inside at-rule parentheses; combinators inside parentheses,
+/// brackets and strings are left alone
+pub(crate) fn format_css_prelude(prelude: &str) -> String {
+    let is_at_rule = prelude.starts_with('@');
+    let mut result = String::new();
+    let mut chars = prelude.chars().peekable();
+    let mut paren_depth = 0usize;
+    let mut bracket_depth = 0usize;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                result.push(c);
+                take_css_string(c, &mut chars, &mut result);
+            }
+            '(' if ends_with_url(&result) => {
+                result.push(c);
+                take_css_url_body(&mut chars, &mut result);
+            }
+            '(' => {
+                paren_depth += 1;
+                result.push(c);
+            }
+            ')' => {
+                paren_depth = paren_depth.saturating_sub(1);
+                result.push(c);
+            }
+            '[' => {
+                bracket_depth += 1;
+                result.push(c);
+            }
+            ']' => {
+                bracket_depth = bracket_depth.saturating_sub(1);
+                result.push(c);
+            }
+            ',' => {
+                while result.ends_with(' ') {
+                    result.pop();
+                }
+                result.push_str(", ");
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            ':' if is_at_rule && paren_depth > 0 => {
+                result.push_str(": ");
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            '>' | '+' | '~' if !is_at_rule && paren_depth == 0 && bracket_depth == 0 => {
+                while result.ends_with(' ') {
+                    result.pop();
+                }
+                if !result.is_empty() {
+                    result.push(' ');
+                }
+                result.push(c);
+                result.push(' ');
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            c if c.is_whitespace() => {
+                if !result.ends_with(' ') && !result.ends_with('(') {
+                    result.push(' ');
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result.trim().to_string()
+}
+
+/// CSS 属性值的词法单元
+/// Lexical token of a CSS property value
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CssValueToken {
+    /// 引号字符串（含引号，原样保留）
+    /// Quoted string (quotes included, kept verbatim)
+    Str(String),
+    /// `url(...)` 整体（原样保留）
+    /// A whole `url(...)` (kept verbatim)
+    Url(String),
+    /// 标识符、数字、十六进制颜色、函数名等
+    /// Identifier, number, hex color, function name, etc.
+    Word(String),
+    Open,
+    Close,
+    Comma,
+    Space,
+}
+
+/// 将属性值拆分为词法单元；字符串与 `url(...)` 内容不再细分
+/// Split a property value into tokens; strings and `url(...)` bodies are never split further
+pub(crate) fn tokenize_css_value(value: &str) -> Vec<CssValueToken> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if !matches!(c, '"' | '\'' | '(' | ')' | ',') && !c.is_whitespace() {
+            word.push(c);
+            continue;
+        }
+        if c == '(' && word.eq_ignore_ascii_case("url") {
+            let mut url = std::mem::take(&mut word);
+            url.push(c);
+            take_css_url_body(&mut chars, &mut url);
+            tokens.push(CssValueToken::Url(url));
+            continue;
+        }
+        if !word.is_empty() {
+            tokens.push(CssValueToken::Word(std::mem::take(&mut word)));
+        }
+        match c {
+            '"' | '\'' => {
+                let mut text = String::from(c);
+                take_css_string(c, &mut chars, &mut text);
+                tokens.push(CssValueToken::Str(text));
+            }
+            '(' => tokens.push(CssValueToken::Open),
+            ')' => tokens.push(CssValueToken::Close),
+            ',' => tokens.push(CssValueToken::Comma),
+            _ => {
+                if tokens.last() != Some(&CssValueToken::Space) {
+                    tokens.push(CssValueToken::Space);
+                }
+            }
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(CssValueToken::Word(word));
+    }
+    tokens
+}
+
+/// 将词法单元拼回属性值
+/// Join value tokens back into a property value
+pub(crate) fn join_css_value(tokens: &[CssValueToken]) -> String {
+    let mut result = String::new();
+    for token in tokens {
+        match token {
+            CssValueToken::Str(text) | CssValueToken::Url(text) | CssValueToken::Word(text) => result.push_str(text),
+            CssValueToken::Open => result.push('('),
+            CssValueToken::Close => result.push(')'),
+            CssValueToken::Comma => result.push(','),
+            CssValueToken::Space => result.push(' '),
+        }
+    }
+    result
+}
+
+/// 颜色规范化：十六进制颜色转小写（可选缩写），颜色函数参数统一为 `rgb(0, 0, 0)` 形式
+/// Color normalization: lowercase hex colors (optionally shortened), color function arguments become `rgb(0, 0, 0)`
+fn normalize_css_colors(tokens: Vec<CssValueToken>, shorten: bool) -> Vec<CssValueToken> {
+    const COLOR_FUNCTIONS: &[&str] = &["rgb", "rgba", "hsl", "hsla", "hwb", "lab", "lch", "oklab", "oklch"];
+    let mut result: Vec<CssValueToken> = Vec::with_capacity(tokens.len());
+    // 每层括号是否属于颜色函数
+    // Whether each open parenthesis belongs to a color function
+    let mut paren_stack: Vec<bool> = Vec::new();
+
+    for token in tokens {
+        let in_color = paren_stack.last() == Some(&true);
+        match token {
+            CssValueToken::Word(word) if is_hex_color(&word) => {
+                let mut hex = word.to_ascii_lowercase();
+                let digits: Vec<char> = hex[1..].chars().collect();
+                if shorten && (digits.len() == 6 || digits.len() == 8) && digits.chunks(2).all(|p| p[0] == p[1]) {
+                    hex = std::iter::once('#').chain(digits.chunks(2).map(|p| p[0])).collect();
+                }
+                result.push(CssValueToken::Word(hex));
+            }
+            CssValueToken::Open => {
+                let is_color = matches!(result.last(), Some(CssValueToken::Word(name)) if COLOR_FUNCTIONS.contains(&name.to_ascii_lowercase().as_str()));
+                if is_color {
+                    if let Some(CssValueToken::Word(name)) = result.last_mut() {
+                        *name = name.to_ascii_lowercase();
+                    }
+                }
+                paren_stack.push(is_color);
+                result.push(CssValueToken::Open);
+            }
+            CssValueToken::Close => {
+                if in_color && result.last() == Some(&CssValueToken::Space) {
+                    result.pop();
+                }
+                paren_stack.pop();
+                result.push(CssValueToken::Close);
+            }
+            CssValueToken::Comma if in_color => {
+                if result.last() == Some(&CssValueToken::Space) {
+                    result.pop();
+                }
+                result.push(CssValueToken::Comma);
+                result.push(CssValueToken::Space);
+            }
+            CssValueToken::Space if in_color && matches!(result.last(), Some(CssValueToken::Open | CssValueToken::Space)) => {}
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// 规范值中的空格：逗号后一个空格、括号内侧不留空格；字符串与 `url(...)` 不受影响
+/// Normalize spacing in a value: one space after commas, none inside parentheses; strings and `url(...)` are untouched
+pub(crate) fn normalize_css_value_spacing(tokens: Vec<CssValueToken>) -> Vec<CssValueToken> {
+    let mut result: Vec<CssValueToken> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match token {
+            CssValueToken::Space if matches!(result.last(), None | Some(CssValueToken::Open | CssValueToken::Space)) => {}
+            CssValueToken::Close | CssValueToken::Comma => {
+                if result.last() == Some(&CssValueToken::Space) {
+                    result.pop();
+                }
+                let is_comma = token == CssValueToken::Comma;
+                result.push(token);
+                if is_comma {
+                    result.push(CssValueToken::Space);
+                }
+            }
+            other => result.push(other),
+        }
+    }
+    if result.last() == Some(&CssValueToken::Space) {
+        result.pop();
+    }
+    result
+}
+
+/// 提取内容中所有 data URI（含 `url(` 前缀或引号内的形式）
+/// Extract all data URIs in the content (inside `url(` or quotes)
+fn extract_data_uris(content: &str) -> Vec<String> {
+    let mut uris = Vec::new();
+    let lower = content.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(pos) = lower[search_from..].find("data:") {
+        let start = search_from + pos;
+        let opener = content[..start].trim_end().chars().last();
+        let end_char = match opener {
+            Some('"') => Some('"'),
+            Some('\'') => Some('\''),
+            Some('(') => Some(')'),
+            _ => None,
+        };
+        let end = end_char
+            .and_then(|ec| content[start..].find(ec))
+            .map(|i| start + i)
+            .unwrap_or(start + 5);
+        if end_char.is_some() {
+            uris.push(content[start..end].to_string());
+        }
+        search_from = end.max(start + 5);
+    }
+    uris
+}
+
+/// 是否为十六进制颜色（`#rgb`、`#rgba`、`#rrggbb`、`#rrggbbaa`）
+/// Whether a word is a hex color (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`)
+fn is_hex_color(word: &str) -> bool {
+    word.strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// 可以安全省略单位的长度单位（时间、角度、百分比等不在此列）
+/// Length units that may safely be dropped from a zero (time, angle, percentage etc. are excluded)
+const CSS_LENGTH_UNITS: &[&str] = &[
+    "px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "q", "in", "pt", "pc",
+];
+
+/// 数学函数：内部的数字保持原样
+/// Math functions: numbers inside them are left as written
+const CSS_MATH_FUNCTIONS: &[&str] = &["calc", "min", "max", "clamp", "var", "env"];
+
+/// 数字规范化：前导零与零长度单位；数学函数内不处理
+/// Numeric normalization: leading zeros and zero-length units; skipped inside math functions
+fn normalize_css_numbers(tokens: Vec<CssValueToken>, leading_zero: LeadingZero, strip_zero_units: bool) -> Vec<CssValueToken> {
+    let mut result: Vec<CssValueToken> = Vec::with_capacity(tokens.len());
+    let mut paren_stack: Vec<bool> = Vec::new();
+
+    for token in tokens {
+        match token {
+            CssValueToken::Open => {
+                let is_math = matches!(result.last(), Some(CssValueToken::Word(name)) if CSS_MATH_FUNCTIONS.contains(&name.to_ascii_lowercase().as_str()));
+                paren_stack.push(is_math || paren_stack.last() == Some(&true));
+                result.push(CssValueToken::Open);
+            }
+            CssValueToken::Close => {
+                paren_stack.pop();
+                result.push(CssValueToken::Close);
+            }
+            CssValueToken::Word(word) if paren_stack.last() != Some(&true) && !word.starts_with('#') => {
+                let parts: Vec<String> = word
+                    .split('/')
+                    .map(|part| normalize_css_number(part, leading_zero, strip_zero_units))
+                    .collect();
+                result.push(CssValueToken::Word(parts.join("/")));
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// 规范化单个数字（带可选符号与单位）；不是数字时原样返回
+/// Normalize a single number (optional sign and unit); non-numbers are returned unchanged
+fn normalize_css_number(word: &str, leading_zero: LeadingZero, strip_zero_units: bool) -> String {
+    let (sign, rest) = match word.chars().next() {
+        Some(c @ ('+' | '-')) => (c.to_string(), &word[1..]),
+        _ => (String::new(), word),
+    };
+    let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+    let (number, unit) = rest.split_at(number_len);
+    let valid_number = !number.is_empty()
+        && number.matches('.').count() <= 1
+        && number.chars().any(|c| c.is_ascii_digit())
+        && unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%');
+    if !valid_number {
+        return word.to_string();
+    }
+
+    let mut number = number.to_string();
+    match leading_zero {
+        LeadingZero::Add if number.starts_with('.') => number.insert(0, '0'),
+        LeadingZero::Remove if number.starts_with("0.") => {
+            number.remove(0);
+        }
+        _ => {}
+    }
+    let is_zero = number.chars().all(|c| c == '0' || c == '.');
+    let unit = if strip_zero_units && is_zero && CSS_LENGTH_UNITS.contains(&unit.to_ascii_lowercase().as_str()) {
+        number = "0".to_string();
+        ""
+    } else {
+        unit
+    };
+    format!("{}{}{}", sign, number, unit)
+}
+
+/// 格式化属性值：按选项依次执行值级别的规范化
+/// Format a property value: apply the value-level normalizations enabled in the options
+fn format_css_value(property: &str, value: &str, options: &FormatOptions) -> String {
+    // 自定义属性的值可以是任意内容，不做改动
+    // Custom property values may be arbitrary, leave them untouched
+    if property.starts_with("--") {
+        return value.to_string();
+    }
+    let mut tokens = normalize_css_value_spacing(tokenize_css_value(value));
+    if options.normalize_colors {
+        tokens = normalize_css_colors(tokens, options.short_hex_colors);
+    }
+    // flex 简写中的 0 基准值在部分浏览器中必须带单位
+    // A zero flex-basis in the flex shorthand must keep its unit in some browsers
+    let strip_zero_units = options.strip_zero_units && !property.eq_ignore_ascii_case("flex") && !property.eq_ignore_ascii_case("flex-basis");
+    if options.leading_zero != LeadingZero::Preserve || strip_zero_units {
+        tokens = normalize_css_numbers(tokens, options.leading_zero, strip_zero_units);
+    }
+    join_css_value(&tokens)
+}
+
+/// 拆出值末尾的 `!important`（兼容 `! important`、大小写不同的写法）
+/// Split a trailing `!important` off a value (tolerating `! important` and any letter case)
+pub(crate) fn split_important(value: &str) -> (&str, bool) {
+    let trimmed = value.trim_end();
+    let split_at = trimmed.len().saturating_sub(9);
+    if trimmed.get(split_at..).is_some_and(|tail| tail.eq_ignore_ascii_case("important")) {
+        let before = trimmed[..split_at].trim_end();
+        if let Some(rest) = before.strip_suffix('!') {
+            return (rest.trim_end(), true);
+        }
+    }
+    (value, false)
+}
+
+/// 在顶层逗号处拆分选择器列表（括号、方括号与字符串内的逗号不拆）
+/// Split a selector list at top-level commas (commas inside parentheses, brackets and strings stay)
+fn split_selector_list(prelude: &str) -> Vec<String> {
+    let mut selectors = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    for c in prelude.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    selectors.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        current.push(c);
+    }
+    selectors.push(current.trim().to_string());
+    selectors.retain(|s| !s.is_empty());
+    selectors
+}
+
+/// 格式化单个声明（`!important` 前恰好一个空格）
+/// Format a single declaration (exactly one space before `!important`)
+pub(crate) fn format_css_declaration(property: &str, value: &str, options: &FormatOptions) -> String {
+    let (value, important) = if property.starts_with("--") { (value, false) } else { split_important(value) };
+    let mut declaration = format!("{}: {}", property, format_css_value(property, value, options));
+    if important {
+        declaration.push_str(" !important");
+    }
+    declaration
+}
+
+/// 拆分浏览器厂商前缀，返回（去前缀的属性名, 是否带前缀）
+/// Split off a vendor prefix, returning (unprefixed property, whether it was prefixed)
+fn split_vendor_prefix(property: &str) -> (&str, bool) {
+    for prefix in ["-webkit-", "-moz-", "-ms-", "-o-"] {
+        if let Some(base) = property.strip_prefix(prefix) {
+            return (base, true);
+        }
+    }
+    (property, false)
+}
+
+/// Concentric CSS 顺序：由外到内排列属性（按前缀匹配，如 `border` 覆盖 `border-top-width`）
+/// Concentric CSS order: properties from the outside in (prefix match, e.g. `border` covers `border-top-width`)
+const CONCENTRIC_ORDER: &[&str] = &[
+    "all", "box-sizing", "display", "position", "top", "right", "bottom", "left", "inset",
+    "flex", "grid", "order", "align", "justify", "place", "gap", "columns", "column", "float",
+    "clear", "transform", "transition", "animation", "visibility", "opacity", "z-index",
+    "margin", "outline", "border", "box-shadow", "background", "cursor", "padding", "width",
+    "min-width", "max-width", "height", "min-height", "max-height", "overflow", "list-style",
+    "caption-side", "table-layout", "border-collapse", "border-spacing", "empty-cells",
+    "vertical-align", "text-align", "text-indent", "text-transform", "text-decoration",
+    "text-rendering", "text-shadow", "text-overflow", "line-height", "word", "letter-spacing",
+    "white-space", "color", "font", "content", "quotes",
+];
+
+/// 属性在 Concentric 顺序中的位置，未知属性排在最后
+/// Position of a property in the concentric order, unknown properties go last
+fn concentric_rank(property: &str) -> usize {
+    CONCENTRIC_ORDER
+        .iter()
+        .rposition(|entry| property == *entry || property.starts_with(&format!("{}-", entry)))
+        .unwrap_or(CONCENTRIC_ORDER.len())
+}
+
+/// 对每个块内连续的声明排序（注释作为分隔不参与排序），带前缀的写法紧挨在标准属性之前；
+/// 不排序时也会把同一属性的各厂商前缀写法归拢到一起
+/// Sort each run of consecutive declarations in a block (comments act as separators),
+/// keeping vendor-prefixed forms right before their unprefixed property; without sorting,
+/// vendor-prefixed variants of a property are still grouped together
+fn sort_css_declarations(nodes: &mut Vec<CssNode>, mode: SortProperties) {
+    let mut start = 0;
+    while start < nodes.len() {
+        let len = nodes[start..]
+            .iter()
+            .take_while(|n| matches!(n, CssNode::Declaration { .. } | CssNode::TrailingComment(_)))
+            .count();
+        if len > 1 {
+            let run: Vec<CssNode> = nodes.drain(start..start + len).collect();
+            let run = order_declaration_run(run, mode);
+            nodes.splice(start..start, run);
+        }
+        if let CssNode::Block { children, .. } = &mut nodes[start] {
+            sort_css_declarations(children, mode);
+        }
+        start += len.max(1);
+    }
+}
+
+/// 对一段连续声明排序或归拢厂商前缀；声明行尾的注释随声明一起移动
+/// Sort a run of consecutive declarations, or group their vendor prefixes; trailing comments move with their declaration
+fn order_declaration_run(run: Vec<CssNode>, mode: SortProperties) -> Vec<CssNode> {
+    // 每个单元是一条声明及其行尾注释
+    // Each unit is one declaration followed by its trailing comments
+    type Unit = Vec<CssNode>;
+    let mut units: Vec<Unit> = Vec::new();
+    for node in run {
+        match (&node, units.last_mut()) {
+            (CssNode::TrailingComment(_), Some(unit)) => unit.push(node),
+            _ => units.push(vec![node]),
+        }
+    }
+    let bases: Vec<(String, bool)> = units
+        .iter()
+        .map(|unit| match &unit[0] {
+            CssNode::Declaration { property, .. } => {
+                let property = property.to_lowercase();
+                let (base, prefixed) = split_vendor_prefix(&property);
+                (base.to_string(), prefixed)
+            }
+            _ => (String::new(), false),
+        })
+        .collect();
+
+    let mut keyed: Vec<((usize, String, bool, usize), Unit)> = units
+        .into_iter()
+        .enumerate()
+        .map(|(i, unit)| {
+            let (base, prefixed) = &bases[i];
+            let key = match mode {
+                SortProperties::Alphabetical => (0, base.clone(), !prefixed, i),
+                SortProperties::Concentric => (concentric_rank(base), base.clone(), !prefixed, i),
+                SortProperties::None => {
+                    // 有前缀写法的属性整组放在首次出现的位置
+                    // Properties with prefixed variants move as a group to their first occurrence
+                    let has_prefixed = bases.iter().any(|(b, p)| b == base && *p);
+                    let group = if has_prefixed { bases.iter().position(|(b, _)| b == base).unwrap_or(i) } else { i };
+                    (group, String::new(), has_prefixed && !prefixed, i)
+                }
+            };
+            (key, unit)
+        })
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    keyed.into_iter().flat_map(|(_, unit)| unit).collect()
+}
+
+/// 递归输出 CSS 节点
+/// Emit CSS nodes recursively
+fn emit_css_nodes(nodes: &[CssNode], level: usize, indent_unit: &str, options: &FormatOptions, result: &mut String) {
+    let indent = indent_unit.repeat(level);
+    for node in nodes {
+        match node {
+            CssNode::TrailingComment(comment) => {
+                if result.ends_with('\n') {
+                    result.pop();
+                }
+                result.push(' ');
+                result.push_str(comment);
+                result.push('\n');
+            }
+            CssNode::Comment(comment) => {
+                let columns = options.indent_width() * level;
+                match reflow_block_comment(comment, columns, options.line_length).filter(|_| options.wrap_comments) {
+                    Some(lines) => {
+                        for line in lines {
+                            result.push_str(&indent);
+                            result.push_str(&line);
+                            result.push('\n');
+                        }
+                    }
+                    None => {
+                        result.push_str(&indent);
+                        result.push_str(comment);
+                        result.push('\n');
+                    }
+                }
+            }
+            CssNode::Statement(text) => {
+                result.push_str(&indent);
+                result.push_str(&format_css_prelude(text));
+                result.push_str(";\n");
+            }
+            CssNode::Declaration { property, value } => {
+                result.push_str(&indent);
+                result.push_str(&format_css_declaration(property, value, options));
+                result.push_str(";\n");
+            }
+            CssNode::Block { prelude, children } => {
+                let prelude = format_css_prelude(prelude);
+                let selectors = if options.split_selectors && !prelude.starts_with('@') {
+                    split_selector_list(&prelude)
+                } else {
+                    vec![prelude.clone()]
+                };
+                // 拆分后的选择器列表：除最后一个外每行一个，逗号在行尾
+                // Split selector list: one per line except the last, comma at line end
+                if selectors.len() > 1 {
+                    for selector in &selectors[..selectors.len() - 1] {
+                        result.push_str(&indent);
+                        result.push_str(selector);
+                        result.push_str(",\n");
+                    }
+                }
+                let prelude = selectors.last().cloned().unwrap_or_default();
+                let head = if prelude.is_empty() { "{".to_string() } else { format!("{} {{", prelude) };
+
+                // 只含声明的规则按策略压缩为一行（auto：不超过 3 条；always：不限条数；均需不超过行宽）
+                // Declaration-only rules are compacted per policy (auto: at most 3; always: any count; both within line length)
+                let only_declarations = children.iter().all(|n| matches!(n, CssNode::Declaration { .. }));
+                let may_compact = match options.css_single_line_rules {
+                    CssSingleLineRules::Never => false,
+                    CssSingleLineRules::Auto => children.len() <= 3,
+                    CssSingleLineRules::Always => true,
+                };
+                if only_declarations && !children.is_empty() && may_compact && selectors.len() == 1 {
+                    let declarations: Vec<String> = children
+                        .iter()
+                        .filter_map(|n| match n {
+                            CssNode::Declaration { property, value } => Some(format_css_declaration(property, value, options)),
+                            _ => None,
+                        })
+                        .collect();
+                    let line = format!("{} {}; }}", head, declarations.join("; "));
+                    if options.indent_width() * level + display_width(&line) <= options.line_length {
+                        result.push_str(&indent);
+                        result.push_str(&line);
+                        result.push('\n');
+                        continue;
+                    }
+                }
+
+                result.push_str(&indent);
+                result.push_str(&head);
+                result.push('\n');
+                emit_css_nodes(children, level + 1, indent_unit, options, result);
+                result.push_str(&indent);
+                result.push_str("}\n");
+            }
+        }
+    }
+}
+
+/// 顶层节点的分组类别，不同类别之间以空行分隔
+/// Group kind of a top-level node, groups of different kinds are separated by a blank line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CssTopLevelGroup {
+    Charset,
+    Import,
+    Namespace,
+    FontFace,
+    Other,
+}
+
+/// 顶层节点所属的分组
+/// Group a top-level node belongs to
+fn css_top_level_group(node: &CssNode) -> CssTopLevelGroup {
+    let head = match node {
+        CssNode::Statement(text) => text.as_str(),
+        CssNode::Block { prelude, .. } => prelude.as_str(),
+        _ => "",
+    };
+    let keyword: String = head
+        .chars()
+        .take_while(|c| *c == '@' || c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match keyword.as_str() {
+        "@charset" => CssTopLevelGroup::Charset,
+        "@import" => CssTopLevelGroup::Import,
+        "@namespace" => CssTopLevelGroup::Namespace,
+        "@font-face" => CssTopLevelGroup::FontFace,
+        _ => CssTopLevelGroup::Other,
+    }
+}
+
+/// 整理顶层节点：`@charset` 置于最前，`@import`、`@namespace` 依次紧随其后
+/// Organize top-level nodes: `@charset` goes first, followed by `@import` and then `@namespace`
+fn organize_css_top_level(nodes: Vec<CssNode>) -> Vec<CssNode> {
+    // 依次为 @charset、@import、@namespace 与其余节点；注释随其后的节点移动，行尾注释随其前的节点移动
+    // @charset, @import, @namespace and the rest in turn; comments move with the node after them, trailing comments
+    // with the node before them
+    let mut groups: [Vec<CssNode>; 4] = Default::default();
+    let mut pending = Vec::new();
+    let mut last = 3;
+    for node in nodes {
+        let index = match (css_top_level_group(&node), &node) {
+            (_, CssNode::Comment(_)) => {
+                pending.push(node);
+                continue;
+            }
+            (_, CssNode::TrailingComment(_)) => {
+                groups[last].push(node);
+                continue;
+            }
+            // 只保留第一个 @charset，其余的会被浏览器忽略
+            // Only the first @charset is kept, browsers ignore the others
+            (CssTopLevelGroup::Charset, CssNode::Statement(_)) if groups[0].iter().all(|n| !matches!(n, CssNode::Statement(_))) => 0,
+            (CssTopLevelGroup::Charset, CssNode::Statement(_)) => {
+                last = 3;
+                continue;
+            }
+            (CssTopLevelGroup::Import, CssNode::Statement(_)) => 1,
+            (CssTopLevelGroup::Namespace, CssNode::Statement(_)) => 2,
+            _ => 3,
+        };
+        groups[index].append(&mut pending);
+        groups[index].push(node);
+        last = index;
+    }
+    groups[3].append(&mut pending);
+    groups.into_iter().flatten().collect()
+}
+
+pub(crate) fn format_css(content: &str, options: &FormatOptions) -> Result<String> {
+    let indent_unit = options.indent_unit();
+    let mut nodes = organize_css_top_level(parse_css_nodes(&mut content.chars().peekable(), 0));
+    sort_css_declarations(&mut nodes, options.sort_properties);
+    if log_enabled(LogLevel::Trace) {
+        for node in &nodes {
+            log(LogLevel::Trace, format_args!("css: {:?}", node));
+        }
+    }
+
+    // 顶层逐个输出，分组变化时插入空行（注释归入其后节点的分组）
+    // Emit top-level nodes one by one, inserting a blank line when the group changes (comments join the group of the next node)
+    let mut result = String::with_capacity(content.len() * 2);
+    let mut previous_group = None;
+    for (i, node) in nodes.iter().enumerate() {
+        if matches!(node, CssNode::TrailingComment(_)) {
+            emit_css_nodes(std::slice::from_ref(node), 0, &indent_unit, options, &mut result);
+            continue;
+        }
+        let group = nodes[i..]
+            .iter()
+            .find(|n| !matches!(n, CssNode::Comment(_) | CssNode::TrailingComment(_)))
+            .map(css_top_level_group);
+        if previous_group.is_some() && group != previous_group {
+            result.push('\n');
+        }
+        previous_group = group;
+        emit_css_nodes(std::slice::from_ref(node), 0, &indent_unit, options, &mut result);
+    }
+
+    // data URI 保证：输入中的每个 data URI 都必须原样出现在输出中
+    // Data URI guarantee: every data URI of the input must appear verbatim in the output
+    if options.preserve_data_uris {
+        for uri in extract_data_uris(content) {
+            if !result.contains(&uri) {
+                return Err(FormatError::DataUriChanged { uri }.into());
+            }
+        }
+    }
+    Ok(result)
+}
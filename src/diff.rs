@@ -0,0 +1,277 @@
+//! 行级差异
+//! Line-level differences
+
+use crate::*;
+
+/// 一段差异：原内容的 `[old_start, old_end)` 行被替换为新内容的 `[new_start, new_end)` 行（行号从 1 开始）
+/// One difference: lines `[old_start, old_end)` of the original are replaced by lines `[new_start, new_end)` of the new content (1-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DiffHunk {
+    old_start: usize,
+    old_end: usize,
+    new_start: usize,
+    new_end: usize,
+}
+
+impl DiffHunk {
+    /// 该差异在原内容中覆盖的行范围（闭区间）；纯插入时取插入位置所在行
+    /// Line range (inclusive) the difference covers in the original; pure insertions use the line at the insertion point
+    pub(crate) fn old_line_range(&self) -> (usize, usize) {
+        if self.old_end > self.old_start {
+            (self.old_start, self.old_end - 1)
+        } else {
+            let line = self.old_start.saturating_sub(1).max(1);
+            (line, line)
+        }
+    }
+}
+
+/// 动态规划求最长公共子序列时允许的最大规模，超过后整段视为一处差异
+/// Largest size for the LCS dynamic programming; beyond it the whole middle counts as one difference
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// 计算两段文本之间的行级差异；行连同换行符一起比较，因此只差 `\r`、BOM 或末尾换行的行也算作差异
+/// Compute the line-level differences between two texts; lines are compared with their line breaks, so lines differing
+/// only in `\r`, the BOM or the final newline count as differences too
+pub(crate) fn diff_lines(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    diff_sequences(&old_lines, &new_lines)
+}
+
+/// 计算两个行序列之间的差异
+/// Compute the differences between two sequences of lines
+fn diff_sequences(old_lines: &[&str], new_lines: &[&str]) -> Vec<DiffHunk> {
+    // 去掉公共前缀与后缀，只对中间部分做比较
+    // Strip the common prefix and suffix, only the middle part is compared
+    let prefix = old_lines.iter().zip(new_lines).take_while(|(a, b)| a == b).count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_lines[prefix..old_lines.len() - suffix];
+    let new_mid = &new_lines[prefix..new_lines.len() - suffix];
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return Vec::new();
+    }
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        return vec![DiffHunk {
+            old_start: prefix + 1,
+            old_end: prefix + old_mid.len() + 1,
+            new_start: prefix + 1,
+            new_end: prefix + new_mid.len() + 1,
+        }];
+    }
+
+    // lcs[i][j]：old_mid[i..] 与 new_mid[j..] 的最长公共子序列长度
+    // lcs[i][j]: length of the longest common subsequence of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut current: Option<DiffHunk> = None;
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert(DiffHunk {
+            old_start: prefix + i + 1,
+            old_end: prefix + i + 1,
+            new_start: prefix + j + 1,
+            new_end: prefix + j + 1,
+        });
+        if j < m && (i == n || lcs[i * (m + 1) + j + 1] >= lcs[(i + 1) * (m + 1) + j]) {
+            j += 1;
+            hunk.new_end = prefix + j + 1;
+        } else {
+            i += 1;
+            hunk.old_end = prefix + i + 1;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+/// 格式化前后不同的一处：原内容的字节区间 `old` 应替换为格式化结果的字节区间 `new`，`lines` 为其在原内容中涉及的行（从 1 开始，左闭右开）
+/// One place that differs after formatting: the byte range `old` of the original is to be replaced by the byte range
+/// `new` of the formatted result, `lines` being the lines it touches in the original (1-based, half-open)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub old: std::ops::Range<usize>,
+    pub new: std::ops::Range<usize>,
+    pub lines: std::ops::Range<usize>,
+}
+
+/// 格式化结果及其相对原内容的改动，供编辑器以最小编辑应用（保留光标与撤销历史）
+/// A formatting result with its changes relative to the original, so editors can apply minimal edits (keeping cursors
+/// and undo history)
+#[derive(Debug, Clone)]
+pub struct FormatOutcome {
+    pub formatted: String,
+    pub changed_ranges: Vec<Span>,
+}
+
+/// 一行中与另一行不同的部分：去掉首尾相同的字符后，两边剩余的字节区间（相对行首）
+/// The part of a line that differs from another: the byte ranges (relative to the line start) left on both sides after
+/// dropping the characters they share at the start and the end
+fn differing_part(old: &str, new: &str) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+    let prefix: usize = old.chars().zip(new.chars()).take_while(|(a, b)| a == b).map(|(c, _)| c.len_utf8()).sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+/// 计算把 `old` 变为 `new` 所需的改动，偏移都基于传入的原始文本（含 BOM 与 `\r`）：
+/// 按去掉 `\r` 后的内容逐行求差异，再去掉每处差异首尾相同的字符；只差 `\r` 的行单独给出删除 `\r` 的改动，
+/// 因此 CRLF 文件不会整体算作一处改动；按顺序把各处 `old` 区间替换为对应的 `new` 片段即得到 `new`
+/// Compute the changes turning `old` into `new`, all offsets referring to the raw texts as passed (BOM and `\r` included):
+/// lines are diffed on their content without `\r`, then the characters each difference shares at its start and end are
+/// dropped; lines differing only in `\r` get their own edits removing it, so a CRLF file doesn't become one big change;
+/// replacing every `old` range in order by its `new` slice yields `new`
+pub(crate) fn changed_ranges(old: &str, new: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let (old_bom, new_bom) = (old.starts_with(UTF8_BOM), new.starts_with(UTF8_BOM));
+    let (old_base, new_base) = (if old_bom { UTF8_BOM.len_utf8() } else { 0 }, if new_bom { UTF8_BOM.len_utf8() } else { 0 });
+    if old_bom != new_bom {
+        spans.push(Span { old: 0..old_base, new: 0..new_base, lines: 1..2 });
+    }
+    // 行保留换行符，这样只差结尾换行的内容也算作改动
+    // Lines keep their line breaks so content differing only in the final line break counts as a change
+    let old_lines: Vec<&str> = old[old_base..].split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new[new_base..].split_inclusive('\n').collect();
+    let offsets = |lines: &[&str], base: usize| -> Vec<usize> {
+        std::iter::once(base).chain(lines.iter().scan(base, |offset, line| {
+            *offset += line.len();
+            Some(*offset)
+        })).collect()
+    };
+    let (old_offsets, new_offsets) = (offsets(&old_lines, old_base), offsets(&new_lines, new_base));
+    let old_keys: Vec<String> = old_lines.iter().map(|line| line.replace('\r', "")).collect();
+    let new_keys: Vec<String> = new_lines.iter().map(|line| line.replace('\r', "")).collect();
+    let old_keys: Vec<&str> = old_keys.iter().map(String::as_str).collect();
+    let new_keys: Vec<&str> = new_keys.iter().map(String::as_str).collect();
+
+    // 区间 [old_from, old_to) × [new_from, new_to) 内的改动；相同内容的行逐行比较（只可能差 `\r`）
+    // The changes within [old_from, old_to) × [new_from, new_to); lines with the same content are compared one by one
+    // (they can only differ in `\r`)
+    let mut push = |old_lines_range: std::ops::Range<usize>, new_lines_range: std::ops::Range<usize>| {
+        let old_range = old_offsets[old_lines_range.start]..old_offsets[old_lines_range.end];
+        let new_range = new_offsets[new_lines_range.start]..new_offsets[new_lines_range.end];
+        let (old_part, new_part) = differing_part(&old[old_range.clone()], &new[new_range.clone()]);
+        if old_part.is_empty() && new_part.is_empty() {
+            return;
+        }
+        spans.push(Span {
+            old: old_range.start + old_part.start..old_range.start + old_part.end,
+            new: new_range.start + new_part.start..new_range.start + new_part.end,
+            lines: old_lines_range.start + 1..old_lines_range.end.max(old_lines_range.start + 1) + 1,
+        });
+    };
+    let (mut i, mut j) = (0, 0);
+    for hunk in diff_sequences(&old_keys, &new_keys) {
+        while i < hunk.old_start - 1 {
+            push(i..i + 1, j..j + 1);
+            i += 1;
+            j += 1;
+        }
+        push(hunk.old_start - 1..hunk.old_end - 1, hunk.new_start - 1..hunk.new_end - 1);
+        (i, j) = (hunk.old_end - 1, hunk.new_end - 1);
+    }
+    while i < old_lines.len() {
+        push(i..i + 1, j..j + 1);
+        i += 1;
+        j += 1;
+    }
+    spans
+}
+
+/// 按 BOM 策略给格式化结果加上或去掉 BOM（BOM 只用于 UTF-8 输出）
+/// Add or drop the BOM on a formatted result per the BOM policy (the BOM only applies to UTF-8 output)
+pub(crate) fn apply_bom_policy(formatted: String, has_bom: bool, policy: BomPolicy, encoding: TextEncoding) -> String {
+    match policy {
+        BomPolicy::Add if encoding == TextEncoding::Utf8 => format!("{}{}", UTF8_BOM, formatted),
+        BomPolicy::Keep if has_bom => format!("{}{}", UTF8_BOM, formatted),
+        _ => formatted,
+    }
+}
+
+/// 统一差异格式中每段差异前后保留的上下文行数
+/// Context lines kept around each difference in the unified diff format
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// 生成统一格式的差异（`--- a/路径`、`+++ b/路径` 与 `@@` 段），可直接用于 `git apply`；
+/// `hunks` 须由 [`diff_lines`] 对同样的两段文本求得，行按原样输出（含 `\r`），没有换行符的末行后跟 `\ No newline at end of file`
+/// Render a unified diff (`--- a/path`, `+++ b/path` and `@@` hunks) that `git apply` accepts; `hunks` must come from
+/// [`diff_lines`] on the same two texts, lines are written as they are (`\r` included) and a last line without a line
+/// break is followed by `\ No newline at end of file`
+pub(crate) fn render_unified_diff(old: &str, new: &str, path: &str, hunks: &[DiffHunk]) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let push_line = |diff: &mut String, marker: char, line: &str| {
+        diff.push(marker);
+        diff.push_str(line);
+        if !line.ends_with('\n') {
+            diff.push_str("\n\\ No newline at end of file\n");
+        }
+    };
+
+    // 间隔不超过两倍上下文的差异合并为一段
+    // Differences separated by at most twice the context are merged into one hunk
+    let mut groups: Vec<Vec<&DiffHunk>> = Vec::new();
+    for hunk in hunks {
+        match groups.last_mut() {
+            Some(group) if hunk.old_start - group.last().map_or(0, |h| h.old_end) <= 2 * DIFF_CONTEXT_LINES => group.push(hunk),
+            _ => groups.push(vec![hunk]),
+        }
+    }
+
+    for group in groups {
+        let (first, last) = (group[0], group[group.len() - 1]);
+        let context_before = (first.old_start - 1).min(DIFF_CONTEXT_LINES);
+        let context_after = (old_lines.len() + 1 - last.old_end).min(DIFF_CONTEXT_LINES);
+        let old_start = first.old_start - context_before;
+        let new_start = first.new_start - context_before;
+        let old_count = last.old_end + context_after - old_start;
+        let new_count = last.new_end + context_after - new_start;
+        diff.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+
+        let mut line = old_start;
+        for hunk in &group {
+            for context in &old_lines[line - 1..hunk.old_start - 1] {
+                push_line(&mut diff, ' ', context);
+            }
+            for removed in &old_lines[hunk.old_start - 1..hunk.old_end - 1] {
+                push_line(&mut diff, '-', removed);
+            }
+            for added in &new_lines[hunk.new_start - 1..hunk.new_end - 1] {
+                push_line(&mut diff, '+', added);
+            }
+            line = hunk.old_end;
+        }
+        for context in &old_lines[line - 1..line - 1 + context_after] {
+            push_line(&mut diff, ' ', context);
+        }
+    }
+    diff
+}
@@ -0,0 +1,131 @@
+//! 词法单元转储（debug-tokens 子命令）
+//! Token dump (debug-tokens subcommand)
+
+use anyhow::Result;
+use std::sync::atomic::Ordering;
+use crate::*;
+
+/// HTML 词法单元流：序号、所在的元素嵌套深度、种类与内容
+/// HTML token stream: index, element nesting depth, kind and content
+pub(crate) fn dump_html_tokens(source: &str, out: &mut String) {
+    out.push_str(&tr!("## 词法单元\n", "## Tokens\n"));
+    let mut depth = 0usize;
+    for (index, token) in tokenize_html(source).iter().enumerate() {
+        let (kind, text) = match token {
+            HtmlToken::Declaration(text) => ("Declaration", text),
+            HtmlToken::Comment(text) => ("Comment", text),
+            HtmlToken::StartTag { raw, .. } => ("StartTag", raw),
+            HtmlToken::EndTag { raw, .. } => {
+                depth = depth.saturating_sub(1);
+                ("EndTag", raw)
+            }
+            HtmlToken::Text(text) => ("Text", text),
+            HtmlToken::RawText(text) => ("RawText", text),
+        };
+        out.push_str(&format!("{:>5}  depth {:<3} {:<12} {:?}\n", index, depth, kind, text));
+        if let HtmlToken::StartTag { name, self_closing: false, .. } = token {
+            if !VOID_ELEMENTS.contains(&name.as_str()) {
+                depth += 1;
+            }
+        }
+    }
+}
+
+/// CSS 语法树
+/// CSS syntax tree
+pub(crate) fn dump_css_tree(source: &str, out: &mut String) {
+    out.push_str(&tr!("## 语法树\n", "## Syntax tree\n"));
+    dump_css_nodes(&parse_css_nodes(&mut source.chars().peekable(), 0), 0, out);
+}
+
+/// CSS 语法树的节点：按嵌套缩进列出
+/// Nodes of the CSS syntax tree: listed indented by nesting
+fn dump_css_nodes(nodes: &[CssNode], depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    for node in nodes {
+        match node {
+            CssNode::Comment(text) => out.push_str(&format!("{}Comment {:?}\n", indent, text)),
+            CssNode::TrailingComment(text) => out.push_str(&format!("{}TrailingComment {:?}\n", indent, text)),
+            CssNode::Statement(text) => out.push_str(&format!("{}Statement {:?}\n", indent, text)),
+            CssNode::Declaration { property, value } => out.push_str(&format!("{}Declaration {:?}: {:?}\n", indent, property, value)),
+            CssNode::Block { prelude, children } => {
+                out.push_str(&format!("{}Block {:?}\n", indent, prelude));
+                dump_css_nodes(children, depth + 1, out);
+            }
+        }
+    }
+}
+
+/// JS/TS/JSON 词法单元流：行:列、括号深度、种类与原文；不含换行的空白省略
+/// JS/TS/JSON token stream: line:column, bracket depth, kind and source text; whitespace without line breaks is left out
+pub(crate) fn dump_js_tokens(source: &str, out: &mut String) {
+    out.push_str(&tr!("## 词法单元\n", "## Tokens\n"));
+    let (tokens, offsets) = tokenize_js_with_offsets(source);
+    let (mut line, mut column, mut position) = (1, 1, 0);
+    let mut depth = 0usize;
+    for (token, &offset) in tokens.iter().zip(&offsets) {
+        for c in source[position..offset].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        position = offset;
+        let (kind, text) = match token {
+            JsToken::Whitespace { newline: false } => continue,
+            JsToken::Whitespace { newline: true } => ("Newline", ""),
+            JsToken::LineComment(text) => ("LineComment", text.as_str()),
+            JsToken::BlockComment(text) => ("BlockComment", text.as_str()),
+            JsToken::Str(text) => ("Str", text.as_str()),
+            JsToken::Template(text) => ("Template", text.as_str()),
+            JsToken::Regex(text) => ("Regex", text.as_str()),
+            JsToken::Word(text) => ("Word", text.as_str()),
+            JsToken::Punct(text) => ("Punct", text.as_str()),
+        };
+        if matches!(text, ")" | "]" | "}") && kind == "Punct" {
+            depth = depth.saturating_sub(1);
+        }
+        out.push_str(&format!("{:>5}:{:<4} depth {:<3} {:<12} {:?}\n", line, column, depth, kind, text));
+        if matches!(text, "(" | "[" | "{") && kind == "Punct" {
+            depth += 1;
+        }
+    }
+}
+
+/// debug-tokens 子命令：依次输出词法单元（或 CSS 语法树）、格式化器的跟踪信息与格式化结果，全部写到标准输出
+/// debug-tokens subcommand: print the tokens (or the CSS syntax tree), the formatter's trace and the formatted result in
+/// turn, all on stdout
+pub(crate) fn run_debug_tokens(input: &str, type_hint: Option<&str>, cli: &Cli) -> Result<ExitStatus> {
+    let config = Config::discover(cli.config.as_deref(), input)?;
+    let options = file_options(cli, &FormatOptions::from(cli), config)?;
+    let (raw_content, _) = read_source(input, options.encoding)?;
+    let content = strip_bom(&raw_content).1.replace("\r", "");
+    let file_type = resolve_file_type(input, type_hint, &options.extensions, &content)?;
+    let options = options.for_type(file_type);
+
+    let mut dump = format!("# code_formatter {} debug-tokens: {} ({})\n\n", env!("CARGO_PKG_VERSION"), input, file_type);
+    language(file_type).ok_or_else(|| unsupported_type(file_type))?.dump(content.trim(), &mut dump);
+    print!("{}", dump);
+
+    // 格式化器的跟踪信息（缩进层级变化等）也写到标准输出，与词法单元放在一起
+    // The formatter's trace (indent level changes and so on) goes to stdout too, next to the tokens
+    println!("\n{}", tr!("## 格式化器跟踪", "## Formatter trace"));
+    init_logging(LogLevel::Trace, true);
+    TRACE_TO_STDOUT.store(true, Ordering::Relaxed);
+    let result = format_code(&content, file_type, &options);
+    TRACE_TO_STDOUT.store(false, Ordering::Relaxed);
+    match result {
+        Ok(formatted) => {
+            println!("\n{}", tr!("## 格式化结果", "## Formatted output"));
+            print!("{}", formatted);
+            Ok(ExitStatus::Clean)
+        }
+        Err(err) => {
+            println!("\n{}", tr!("## 格式化失败", "## Formatting failed"));
+            println!("{:#}", err);
+            Ok(ExitStatus::of_error(&err))
+        }
+    }
+}
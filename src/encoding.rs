@@ -0,0 +1,204 @@
+//! 文本编码（UTF-8 / GBK / GB18030 / Latin-1 的检测与转换）
+//! Text encodings (detection and conversion of UTF-8 / GBK / GB18030 / Latin-1)
+
+use anyhow::{Context, Result};
+use std::io::Read;
+use crate::*;
+
+/// GB18030 映射数据，由 scripts/gen_gb18030_table.py 生成：
+/// 先是 126 × 190 个双字节码位，之后是四字节 BMP 区段的 (首个码位, 首个线性序号)，均为小端 u16
+/// GB18030 mapping data generated by scripts/gen_gb18030_table.py:
+/// 126 × 190 two-byte code points, then (first code point, first linear index) of the four-byte BMP ranges, all little-endian u16
+const GB18030_DATA: &[u8] = include_bytes!("gb18030.bin");
+
+/// 双字节表的项数
+/// Number of entries in the two-byte table
+const GB18030_TWO_BYTE_ENTRIES: usize = 126 * 190;
+
+/// 四字节 BMP 区段之后的线性序号上限，以及增补平面的起始线性序号
+/// Linear index limit of the four-byte BMP ranges, and the first linear index of the supplementary planes
+const GB18030_BMP_LINEAR_END: u32 = 39420;
+const GB18030_SUPPLEMENTARY_LINEAR_START: u32 = 189000;
+
+fn gb18030_u16(index: usize) -> u32 {
+    u16::from_le_bytes([GB18030_DATA[index * 2], GB18030_DATA[index * 2 + 1]]) as u32
+}
+
+/// 四字节 BMP 区段：(首个码位, 首个线性序号)，两列均递增
+/// Four-byte BMP ranges: (first code point, first linear index), both columns ascending
+fn gb18030_ranges() -> impl Iterator<Item = (u32, u32)> {
+    let count = (GB18030_DATA.len() / 2 - GB18030_TWO_BYTE_ENTRIES) / 2;
+    (0..count).map(|i| {
+        let index = GB18030_TWO_BYTE_ENTRIES + i * 2;
+        (gb18030_u16(index), gb18030_u16(index + 1))
+    })
+}
+
+/// 双字节码位 → 字符到字节对的反向表（首次编码时构建）
+/// Reverse table from character to two-byte sequence (built on first encode)
+fn gb18030_reverse_table() -> &'static std::collections::HashMap<char, [u8; 2]> {
+    static TABLE: std::sync::OnceLock<std::collections::HashMap<char, [u8; 2]>> = std::sync::OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = std::collections::HashMap::with_capacity(GB18030_TWO_BYTE_ENTRIES);
+        for index in 0..GB18030_TWO_BYTE_ENTRIES {
+            if let Some(c) = char::from_u32(gb18030_u16(index)) {
+                let trail = (index % 190) as u8 + 0x40;
+                let bytes = [(index / 190) as u8 + 0x81, if trail >= 0x7F { trail + 1 } else { trail }];
+                table.entry(c).or_insert(bytes);
+            }
+        }
+        table
+    })
+}
+
+/// 按 GB18030 解码；遇到非法序列返回 None
+/// Decode as GB18030; returns None on an invalid sequence
+fn decode_gb18030(bytes: &[u8]) -> Option<String> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        if lead < 0x80 {
+            text.push(lead as char);
+            i += 1;
+            continue;
+        }
+        if !(0x81..=0xFE).contains(&lead) || i + 1 >= bytes.len() {
+            return None;
+        }
+        let second = bytes[i + 1];
+        match second {
+            0x40..=0x7E | 0x80..=0xFE => {
+                let trail = second as usize - 0x40 - usize::from(second > 0x7F);
+                text.push(char::from_u32(gb18030_u16((lead as usize - 0x81) * 190 + trail))?);
+                i += 2;
+            }
+            0x30..=0x39 => {
+                let (third, fourth) = (*bytes.get(i + 2)?, *bytes.get(i + 3)?);
+                if !(0x81..=0xFE).contains(&third) || !(0x30..=0x39).contains(&fourth) {
+                    return None;
+                }
+                let linear = (lead as u32 - 0x81) * 12600
+                    + (second as u32 - 0x30) * 1260
+                    + (third as u32 - 0x81) * 10
+                    + (fourth as u32 - 0x30);
+                let code_point = if linear < GB18030_BMP_LINEAR_END {
+                    let (first_cp, first_linear) = gb18030_ranges().take_while(|&(_, start)| start <= linear).last()?;
+                    first_cp + (linear - first_linear)
+                } else if linear >= GB18030_SUPPLEMENTARY_LINEAR_START {
+                    0x10000 + (linear - GB18030_SUPPLEMENTARY_LINEAR_START)
+                } else {
+                    return None;
+                };
+                text.push(char::from_u32(code_point)?);
+                i += 4;
+            }
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+/// 按 GB18030 编码；`two_byte_only` 为 true 时（GBK）遇到无法用双字节表示的字符返回该字符
+/// Encode as GB18030; with `two_byte_only` (GBK) a character without a two-byte form is returned as the error
+fn encode_gb18030(text: &str, two_byte_only: bool) -> std::result::Result<Vec<u8>, char> {
+    let table = gb18030_reverse_table();
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            bytes.push(c as u8);
+            continue;
+        }
+        if let Some(pair) = table.get(&c) {
+            bytes.extend_from_slice(pair);
+            continue;
+        }
+        if two_byte_only {
+            return Err(c);
+        }
+        let code_point = c as u32;
+        let linear = if code_point >= 0x10000 {
+            GB18030_SUPPLEMENTARY_LINEAR_START + (code_point - 0x10000)
+        } else {
+            let (first_cp, first_linear) = gb18030_ranges().take_while(|&(cp, _)| cp <= code_point).last().ok_or(c)?;
+            first_linear + (code_point - first_cp)
+        };
+        bytes.extend_from_slice(&[
+            (linear / 12600) as u8 + 0x81,
+            (linear % 12600 / 1260) as u8 + 0x30,
+            (linear % 1260 / 10) as u8 + 0x81,
+            (linear % 10) as u8 + 0x30,
+        ]);
+    }
+    Ok(bytes)
+}
+
+/// 从 HTML `<meta charset>` 或 CSS `@charset` 声明中读取文件声明的编码
+/// Read the encoding a file declares through HTML `<meta charset>` or CSS `@charset`
+fn declared_charset(bytes: &[u8]) -> Option<TextEncoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(2048)]).to_ascii_lowercase();
+    let start = head.find("charset")? + "charset".len();
+    let name: String = head[start..]
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '=' || c == '"' || c == '\'')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    match name.as_str() {
+        "utf-8" | "utf8" => Some(TextEncoding::Utf8),
+        "gbk" | "gb2312" | "cp936" | "x-gbk" => Some(TextEncoding::Gbk),
+        "gb18030" => Some(TextEncoding::Gb18030),
+        "iso-8859-1" | "latin1" | "latin-1" | "l1" => Some(TextEncoding::Latin1),
+        _ => None,
+    }
+}
+
+/// 按指定编码解码文件内容；auto 时依次尝试 UTF-8、文件声明的编码、GB18030，最后回退到 Latin-1
+/// Decode file content with the given encoding; auto tries UTF-8, the declared encoding and GB18030, falling back to Latin-1
+fn decode_text(bytes: Vec<u8>, encoding: TextEncoding) -> Result<(String, TextEncoding)> {
+    let invalid = |name: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, tr!("内容不是有效的 {} 编码", "Content is not valid {}", name));
+    match encoding {
+        TextEncoding::Auto => {
+            let bytes = match String::from_utf8(bytes) {
+                Ok(text) => return Ok((text, TextEncoding::Utf8)),
+                Err(err) => err.into_bytes(),
+            };
+            let declared = declared_charset(&bytes).filter(|e| *e != TextEncoding::Utf8);
+            for candidate in declared.into_iter().chain([TextEncoding::Gb18030, TextEncoding::Latin1]) {
+                if let Ok(decoded) = decode_text(bytes.clone(), candidate) {
+                    return Ok(decoded);
+                }
+            }
+            Err(invalid("UTF-8").into())
+        }
+        TextEncoding::Utf8 => String::from_utf8(bytes).map(|text| (text, encoding)).map_err(|_| invalid("UTF-8").into()),
+        TextEncoding::Gbk | TextEncoding::Gb18030 => decode_gb18030(&bytes)
+            .map(|text| (text, encoding))
+            .ok_or_else(|| invalid(encoding.name()).into()),
+        TextEncoding::Latin1 => Ok((bytes.iter().map(|&b| b as char).collect(), encoding)),
+    }
+}
+
+/// 按指定编码编码输出内容
+/// Encode output content with the given encoding
+pub(crate) fn encode_text(text: &str, encoding: TextEncoding) -> Result<Vec<u8>> {
+    let unencodable = |c: char| anyhow::anyhow!(tr!("字符 {:?}（U+{:04X}）无法用 {} 编码", "Character {:?} (U+{:04X}) cannot be encoded in {}", c, c as u32, encoding.name()));
+    match encoding {
+        TextEncoding::Auto | TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        TextEncoding::Gbk => encode_gb18030(text, true).map_err(unencodable),
+        TextEncoding::Gb18030 => encode_gb18030(text, false).map_err(unencodable),
+        TextEncoding::Latin1 => text.chars().map(|c| u8::try_from(c).map_err(|_| unencodable(c))).collect(),
+    }
+}
+
+/// 读取源文件并解码为文本，返回实际使用的编码
+/// Read a source file and decode it to text, returning the encoding actually used
+pub(crate) fn read_source(path: &str, encoding: TextEncoding) -> Result<(String, TextEncoding)> {
+    let bytes = if path == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes).with_context(|| tr!("无法读取标准输入", "Cannot read stdin"))?;
+        bytes
+    } else {
+        std::fs::read(path).with_context(|| tr!("无法读取输入文件：{}", "Cannot read input file: {}", path))?
+    };
+    decode_text(bytes, encoding).with_context(|| tr!("无法读取输入文件：{}", "Cannot read input file: {}", path))
+}
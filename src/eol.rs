@@ -0,0 +1,269 @@
+//! 空白规范检查（--report-eol）
+//! Whitespace hygiene report (--report-eol)
+
+use anyhow::Result;
+use crate::*;
+
+/// 一个文件中的空白问题（行号从 1 开始）
+/// Whitespace problems of one file (1-based line numbers)
+#[derive(Debug, Default)]
+struct WhitespaceReport {
+    /// 以 CRLF、LF、单独的 CR 结尾的行
+    /// Lines ending in CRLF, LF and a lone CR
+    crlf: Vec<usize>,
+    lf: Vec<usize>,
+    cr: Vec<usize>,
+    /// 以 tab、空格开头缩进的行，以及缩进中空格后接 tab 的行
+    /// Lines whose indentation starts with a tab or a space, and lines whose indentation has a tab after a space
+    tab_indented: Vec<usize>,
+    space_indented: Vec<usize>,
+    mixed_indent: Vec<usize>,
+    /// 行尾有空白的行
+    /// Lines with trailing whitespace
+    trailing: Vec<usize>,
+}
+
+/// 一项空白问题
+/// One whitespace problem
+#[derive(Debug)]
+struct WhitespaceIssue {
+    /// 问题种类（JSON 报告中的 `kind`）
+    /// Kind of problem (`kind` in the JSON report)
+    kind: &'static str,
+    /// 首个有问题的行
+    /// First line with the problem
+    line: usize,
+    message: String,
+}
+
+/// 按行拆分文本，每行附带其换行符（CRLF、LF、CR 或最后一行的空串）
+/// Split text into lines, each with its line ending (CRLF, LF, CR, or empty for the last line)
+fn lines_with_endings(text: &str) -> Vec<(&str, &str)> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest.find(['\r', '\n']).unwrap_or(rest.len());
+        let ending = if rest[end..].starts_with("\r\n") { 2 } else { (end < rest.len()) as usize };
+        lines.push((&rest[..end], &rest[end..end + ending]));
+        rest = &rest[end + ending..];
+    }
+    lines
+}
+
+/// 行首缩进部分（空格与 tab）
+/// The leading indentation of a line (spaces and tabs)
+fn leading_indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+impl WhitespaceReport {
+    fn scan(text: &str) -> Self {
+        let mut report = WhitespaceReport::default();
+        for (index, (line, ending)) in lines_with_endings(text).into_iter().enumerate() {
+            let number = index + 1;
+            match ending {
+                "\r\n" => report.crlf.push(number),
+                "\n" => report.lf.push(number),
+                "\r" => report.cr.push(number),
+                _ => {}
+            }
+            if line.ends_with([' ', '\t']) {
+                report.trailing.push(number);
+            }
+            // 空白行只算行尾空白；块注释的 ` * ` 续行靠一个空格对齐，不算缩进风格
+            // Blank lines only count as trailing whitespace; ` * ` continuation lines of block comments align with one
+            // space and don't count as an indentation style
+            let indent = leading_indent(line);
+            let body = &line[indent.len()..];
+            if body.is_empty() || body.starts_with('*') {
+                continue;
+            }
+            match indent.chars().next() {
+                Some('\t') => report.tab_indented.push(number),
+                Some(_) => report.space_indented.push(number),
+                None => {}
+            }
+            if indent.contains(" \t") {
+                report.mixed_indent.push(number);
+            }
+        }
+        report
+    }
+
+    fn mixed_endings(&self) -> bool {
+        [&self.crlf, &self.lf, &self.cr].iter().filter(|lines| !lines.is_empty()).count() > 1
+    }
+
+    fn mixed_indentation(&self) -> bool {
+        (!self.tab_indented.is_empty() && !self.space_indented.is_empty()) || !self.mixed_indent.is_empty()
+    }
+
+    /// 发现的问题，没有问题时为空
+    /// The problems found, empty when there are none
+    fn issues(&self) -> Vec<WhitespaceIssue> {
+        let mut issues = Vec::new();
+        if self.mixed_endings() {
+            // 首个不一致处：占多数的换行符之外最早出现的一行
+            // First inconsistency: the earliest line that doesn't use the majority line ending
+            let mut endings = [&self.crlf, &self.lf, &self.cr];
+            endings.sort_by_key(|lines| std::cmp::Reverse(lines.len()));
+            issues.push(WhitespaceIssue {
+                kind: "mixed_line_endings",
+                line: endings[1..].iter().filter_map(|lines| lines.first()).min().copied().unwrap_or_default(),
+                message: tr!("混合换行符：CRLF {} 行，LF {} 行，CR {} 行", "mixed line endings: {} CRLF, {} LF, {} CR lines", self.crlf.len(), self.lf.len(), self.cr.len()),
+            });
+        }
+        if self.mixed_indentation() {
+            let minority = if self.tab_indented.len() < self.space_indented.len() { &self.tab_indented } else { &self.space_indented };
+            let first = minority.iter().chain(&self.mixed_indent).min().copied().unwrap_or_default();
+            issues.push(WhitespaceIssue {
+                kind: "mixed_indentation",
+                line: first,
+                message: tr!(
+                    "缩进混用 tab 与空格：tab 缩进 {} 行，空格缩进 {} 行，空格后接 tab {} 行（首个不一致处位于第 {} 行）",
+                    "indentation mixes tabs and spaces: {} tab-indented, {} space-indented, {} with a tab after a space (first inconsistency at line {})",
+                    self.tab_indented.len(),
+                    self.space_indented.len(),
+                    self.mixed_indent.len(),
+                    first
+                ),
+            });
+        }
+        if let Some(&first) = self.trailing.first() {
+            issues.push(WhitespaceIssue {
+                kind: "trailing_whitespace",
+                line: first,
+                message: tr!("{} 行有行尾空白（首个位于第 {} 行）", "{} lines have trailing whitespace (first at line {})", self.trailing.len(), first),
+            });
+        }
+        issues
+    }
+
+    /// 修复发现的问题：换行符与缩进统一为占多数的写法（持平时用 LF 与 --indent-style），去掉行尾空白；
+    /// 只改动有问题的方面（多行字符串中的空白同样会被修改）
+    /// Fix the problems found: line endings and indentation follow the majority style (LF and --indent-style on a tie),
+    /// and trailing whitespace is removed; only the problematic aspects change (whitespace inside multi-line strings is
+    /// changed as well)
+    fn fix(&self, text: &str, options: &FormatOptions) -> String {
+        let target_ending = if !self.mixed_endings() {
+            None
+        } else if self.crlf.len() > self.lf.len().max(self.cr.len()) {
+            Some("\r\n")
+        } else if self.cr.len() > self.lf.len().max(self.crlf.len()) {
+            Some("\r")
+        } else {
+            Some("\n")
+        };
+        let use_tabs = match self.tab_indented.len().cmp(&self.space_indented.len()) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => options.indent_style == IndentStyle::Tabs,
+        };
+        let width = options.indent_width().max(1);
+        let mut fixed = String::with_capacity(text.len());
+        for (line, ending) in lines_with_endings(text) {
+            let line = if self.trailing.is_empty() { line } else { line.trim_end_matches([' ', '\t']) };
+            let indent = leading_indent(line);
+            let body = &line[indent.len()..];
+            if self.mixed_indentation() && !body.starts_with('*') {
+                // 按 tab 停靠位计算缩进的列数，再用目标写法重写
+                // Measure the indentation in columns using tab stops, then rewrite it in the target style
+                let columns = indent.chars().fold(0, |column, c| if c == '\t' { (column / width + 1) * width } else { column + 1 });
+                if use_tabs {
+                    fixed.push_str(&"\t".repeat(columns / width));
+                    fixed.push_str(&" ".repeat(columns % width));
+                } else {
+                    fixed.push_str(&" ".repeat(columns));
+                }
+                fixed.push_str(body);
+            } else {
+                fixed.push_str(line);
+            }
+            fixed.push_str(if ending.is_empty() { "" } else { target_ending.unwrap_or(ending) });
+        }
+        fixed
+    }
+}
+
+/// --report-eol：逐个文件列出空白问题，--write 时就地修复；有未修复的问题时退出码为 1
+/// --report-eol: list the whitespace problems file by file, fixing them in place with --write; the exit status is 1
+/// when problems remain unfixed
+pub(crate) fn run_eol_report(inputs: &[String], cli: &Cli) -> Result<ExitStatus> {
+    let base_options = FormatOptions::from(cli);
+    let mut status = ExitStatus::Clean;
+    let mut files = Vec::new();
+    for input in inputs {
+        let result = Config::discover(cli.config.as_deref(), input).and_then(|config| {
+            let options = file_options(cli, &base_options, config)?;
+            let (text, encoding) = read_source(input, options.encoding)?;
+            let report = WhitespaceReport::scan(&text);
+            let issues = report.issues();
+            if cli.write && !issues.is_empty() {
+                if let Some(suffix) = &options.backup {
+                    backup_file(input, suffix)?;
+                }
+                write_output(input, &encode_text(&report.fix(&text, &options), encoding)?)?;
+            }
+            Ok(issues)
+        });
+        let file_status = match &result {
+            Ok(issues) if issues.is_empty() => {
+                log(LogLevel::Verbose, tr!("[EOL] 没有空白问题：{}", "[EOL] No whitespace problems: {}", input));
+                "clean"
+            }
+            Ok(issues) => {
+                for issue in issues {
+                    log(LogLevel::Normal, tr!("[EOL] {}：{}", "[EOL] {}: {}", input, issue.message));
+                }
+                if cli.write {
+                    log(LogLevel::Normal, tr!("[EOL] 已修复：{}", "[EOL] Fixed: {}", input));
+                    "fixed"
+                } else {
+                    status = status.max(ExitStatus::NeedsFormatting);
+                    "issues"
+                }
+            }
+            Err(err) => {
+                eprintln!("{}", tr!("错误：{:?}", "Error: {:?}", err));
+                status = status.max(ExitStatus::of_error(err));
+                "errored"
+            }
+        };
+        files.push((input.as_str(), file_status, result));
+    }
+    if cli.report == Some(ReportFormat::Json) {
+        write_report(&render_eol_json_report(&files), cli.report_file.as_deref())?;
+    }
+    Ok(status)
+}
+
+/// 生成空白规范检查的 JSON 报告：每个文件的状态（clean/issues/fixed/errored）与问题列表
+/// Build the JSON report of the whitespace check: each file's status (clean/issues/fixed/errored) and its problems
+fn render_eol_json_report(files: &[(&str, &str, Result<Vec<WhitespaceIssue>>)]) -> String {
+    let count = |status: &str| files.iter().filter(|(_, file_status, _)| *file_status == status).count();
+    let entries: Vec<String> = files
+        .iter()
+        .map(|(path, status, result)| {
+            let details = match result {
+                Ok(issues) => {
+                    let issues: Vec<String> = issues
+                        .iter()
+                        .map(|issue| format!("{{\"kind\": {}, \"line\": {}, \"message\": {}}}", json_escape(issue.kind), issue.line, json_escape(&issue.message)))
+                        .collect();
+                    format!("\"issues\": [{}]", issues.join(", "))
+                }
+                Err(err) => format!("\"issues\": [],\n      \"error\": {}", json_escape(&format!("{:#}", err))),
+            };
+            format!("    {{\n      \"path\": {},\n      \"status\": {},\n      {}\n    }}", json_escape(path), json_escape(status), details)
+        })
+        .collect();
+    format!(
+        "{{\n  \"summary\": {{\n    \"checked\": {},\n    \"clean\": {},\n    \"issues\": {},\n    \"fixed\": {},\n    \"errored\": {}\n  }},\n  \"files\": [\n{}\n  ]\n}}\n",
+        files.len(),
+        count("clean"),
+        count("issues"),
+        count("fixed"),
+        count("errored"),
+        entries.join(",\n"),
+    )
+}
@@ -0,0 +1,85 @@
+//! 外部格式化器
+//! External formatters
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use crate::*;
+
+/// 文件名模式匹配：`*` 匹配任意多个字符，`?` 匹配一个字符
+/// File name pattern matching: `*` matches any number of characters, `?` exactly one
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 查找处理某个文件的外部格式化命令：含 `/` 的模式匹配整个路径，否则只匹配文件名；先配置的优先
+/// Find the external formatter command for a file: patterns containing `/` match the whole path, others only the file
+/// name; the first configured pattern wins
+pub(crate) fn external_command<'a>(path: &str, external: &'a [(String, String)]) -> Option<&'a str> {
+    let normalized = path.replace('\\', "/");
+    let file_name = normalized.rsplit('/').next().unwrap_or_default();
+    external
+        .iter()
+        .find(|(pattern, _)| glob_match(pattern, if pattern.contains('/') { &normalized } else { file_name }))
+        .map(|(_, command)| command.as_str())
+}
+
+/// 运行外部格式化命令：源码从标准输入传入，格式化结果从标准输出读取；命令按空白拆分，`{file}` 替换为文件路径
+/// 命令以非零状态退出视为格式化失败，其标准错误输出作为错误信息
+/// Run an external formatter command: the source goes to its stdin and the result is read from its stdout; the command
+/// is split at whitespace and `{file}` is replaced with the file path
+/// A non-zero exit status counts as a formatting failure, with its stderr as the message
+pub(crate) fn run_external_formatter(command: &str, content: &str, path: &str) -> Result<String> {
+    let mut words = command.split_whitespace().map(|word| word.replace("{file}", path));
+    let program = words.next().ok_or_else(|| ConfigError(tr!("外部格式化命令为空", "External formatter command is empty")))?;
+    let mut child = std::process::Command::new(&program)
+        .args(words)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| tr!("无法启动外部格式化器：{}", "Cannot start the external formatter: {}", command))?;
+
+    // 在单独的线程中写入标准输入，避免输出缓冲区写满时双方互相等待
+    // Feed stdin from a separate thread so neither side blocks when the output buffer fills up
+    let mut stdin = child.stdin.take().context("stdin")?;
+    let input = content.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().with_context(|| tr!("外部格式化器运行失败：{}", "The external formatter failed to run: {}", command))?;
+    // 命令可能不读取全部输入就退出，此时写入出错无关紧要，以退出状态为准
+    // The command may exit without reading all input; a write error is irrelevant then, the exit status decides
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = tr!("外部格式化器 {} 失败（{}）", "External formatter {} failed ({})", command, output.status);
+        match stderr.trim() {
+            "" => anyhow::bail!(message),
+            details => anyhow::bail!("{}{}{}", message, tr!("：", ": "), details),
+        }
+    }
+    String::from_utf8(output.stdout).map_err(|_| anyhow::anyhow!(tr!("外部格式化器 {} 的输出不是有效的 UTF-8", "The output of external formatter {} is not valid UTF-8", command)))
+}
@@ -0,0 +1,985 @@
+//! HTML 格式化（行内/块级元素模型 + 完整缩进）
+//! HTML Formatting (inline/block element model + full indentation)
+
+use anyhow::Result;
+use crate::*;
+
+/// 行内元素：前后及内部的空白有渲染意义，不能随意换行或增删空格
+/// Inline elements: surrounding/inner whitespace is significant, never add or drop spaces
+pub(crate) const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "acronym", "b", "bdi", "bdo", "big", "br", "button", "cite", "code", "data",
+    "del", "dfn", "em", "font", "i", "img", "input", "ins", "kbd", "label", "mark", "meter",
+    "object", "output", "picture", "progress", "q", "ruby", "rp", "rt", "s", "samp", "select",
+    "small", "span", "strike", "strong", "sub", "sup", "svg", "textarea", "time", "tt", "u",
+    "var", "video", "audio", "wbr",
+];
+
+/// 空元素：没有闭合标签
+/// Void elements: never have a closing tag
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// 原样输出内容的元素（脚本、样式、预格式化文本）
+/// Elements whose content is emitted verbatim (scripts, styles, preformatted text)
+pub(crate) const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea"];
+
+/// 布尔属性：出现即为真，`disabled=""` 与 `disabled="disabled"` 都等价于 `disabled`
+/// Boolean attributes: true by presence, `disabled=""` and `disabled="disabled"` both mean `disabled`
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen", "async", "autofocus", "autoplay", "checked", "controls", "default", "defer", "disabled",
+    "formnovalidate", "hidden", "inert", "ismap", "itemscope", "loop", "multiple", "muted", "nomodule", "novalidate",
+    "open", "playsinline", "readonly", "required", "reversed", "selected",
+];
+
+/// HTML 词法单元
+/// HTML lexical token
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum HtmlToken {
+    /// `<!DOCTYPE ...>`、`<?xml ...?>` 等声明
+    /// Declarations such as `<!DOCTYPE ...>` and `<?xml ...?>`
+    Declaration(String),
+    /// `<!-- ... -->`
+    Comment(String),
+    /// 开始标签（名称小写，`raw` 为格式化后的完整标签）
+    /// Start tag (lowercase name, `raw` is the full formatted tag)
+    StartTag { name: String, raw: String, self_closing: bool },
+    /// 闭合标签
+    /// End tag
+    EndTag { name: String, raw: String },
+    /// 普通文本
+    /// Plain text
+    Text(String),
+    /// 原样输出元素的内容
+    /// Content of a raw text element
+    RawText(String),
+}
+
+/// 格式化标签内部的属性（引号内内容保持不变）
+/// Format attributes inside a tag (quoted content is kept as-is)
+fn format_tag_attributes(tag_buf: &str) -> String {
+    let mut formatted_tag = String::new();
+    let mut tag_chars = tag_buf.chars().peekable();
+    while let Some(tc) = tag_chars.next() {
+        match tc {
+            '=' => {
+                if !formatted_tag.ends_with(' ') {
+                    formatted_tag.push(' ');
+                }
+                formatted_tag.push('=');
+                formatted_tag.push(' ');
+                while let Some(&next_tc) = tag_chars.peek() {
+                    if next_tc.is_whitespace() {
+                        tag_chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            '"' | '\'' => {
+                formatted_tag.push(tc);
+                for next_tc in tag_chars.by_ref() {
+                    formatted_tag.push(next_tc);
+                    if next_tc == tc {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if !formatted_tag.ends_with(' ') && !formatted_tag.ends_with('=') {
+                    formatted_tag.push(' ');
+                }
+            }
+            _ => {
+                formatted_tag.push(tc);
+            }
+        }
+    }
+    formatted_tag
+}
+
+/// 将 HTML 拆分为词法单元
+/// Split HTML into lexical tokens
+pub(crate) fn tokenize_html(content: &str) -> Vec<HtmlToken> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = content;
+
+    while let Some(pos) = rest.find('<') {
+        let after = &rest[pos + 1..];
+        let starts_tag = after
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?');
+        if !starts_tag {
+            text.push_str(&rest[..pos + 1]);
+            rest = after;
+            continue;
+        }
+        text.push_str(&rest[..pos]);
+        if !text.is_empty() {
+            tokens.push(HtmlToken::Text(std::mem::take(&mut text)));
+        }
+
+        // 注释：一直读到 -->
+        // Comment: read up to -->
+        if let Some(comment_body) = after.strip_prefix("!--") {
+            let end = comment_body.find("-->").map(|i| i + 3 + 3).unwrap_or(after.len());
+            tokens.push(HtmlToken::Comment(format!("<{}", &after[..end])));
+            rest = &after[end..];
+            continue;
+        }
+
+        // 标签：读到引号外的第一个 >
+        // Tag: read up to the first > outside quotes
+        let mut quote = None;
+        let mut end = after.len();
+        for (i, c) in after.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '>' => {
+                    end = i;
+                    break;
+                }
+                None => {}
+            }
+        }
+        let inner = after[..end].trim();
+        rest = after.get(end + 1..).unwrap_or("");
+
+        if inner.starts_with('!') || inner.starts_with('?') {
+            tokens.push(HtmlToken::Declaration(format!("<{}>", inner)));
+            continue;
+        }
+
+        let is_end = inner.starts_with('/');
+        let body = inner.trim_start_matches('/').trim_start();
+        let name: String = body
+            .chars()
+            .take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>')
+            .collect::<String>()
+            .to_lowercase();
+        let raw = format!("<{}>", format_tag_attributes(inner).trim());
+
+        if is_end {
+            tokens.push(HtmlToken::EndTag { name, raw });
+            continue;
+        }
+
+        let self_closing = inner.ends_with('/');
+        tokens.push(HtmlToken::StartTag { name: name.clone(), raw, self_closing });
+
+        // 原样输出元素：内容读到对应的闭合标签为止
+        // Raw text elements: content runs until the matching closing tag
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            let closing = format!("</{}", name);
+            let close_pos = rest.to_ascii_lowercase().find(&closing).unwrap_or(rest.len());
+            tokens.push(HtmlToken::RawText(rest[..close_pos].to_string()));
+            rest = &rest[close_pos..];
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(HtmlToken::Text(text));
+    }
+    tokens
+}
+
+/// 标签中的单个属性
+/// A single attribute within a tag
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HtmlAttribute {
+    pub(crate) name: String,
+    /// 属性值（不含引号），布尔属性为 None
+    /// Attribute value (without quotes), None for boolean attributes
+    pub(crate) value: Option<String>,
+    /// 原始引号字符，无引号的值为 None
+    /// Original quote character, None for unquoted values
+    pub(crate) quote: Option<char>,
+}
+
+/// 解析标签中的属性列表
+/// Parse the attribute list of a tag
+pub(crate) fn parse_html_attributes(tag: &str) -> Vec<HtmlAttribute> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+    let mut chars = inner.chars().peekable();
+    let mut attributes = Vec::new();
+
+    // 跳过标签名
+    // Skip the tag name
+    while chars.next_if(|c| !c.is_whitespace()).is_some() {}
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == '/').is_some() {}
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=' && *c != '/') {
+            name.push(c);
+        }
+        if name.is_empty() {
+            break;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = None;
+        let mut quote = None;
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let mut text = String::new();
+            if let Some(q) = chars.next_if(|c| *c == '"' || *c == '\'') {
+                quote = Some(q);
+                for c in chars.by_ref() {
+                    if c == q {
+                        break;
+                    }
+                    text.push(c);
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    text.push(c);
+                }
+            }
+            value = Some(text);
+        }
+        attributes.push(HtmlAttribute { name, value, quote });
+    }
+    attributes
+}
+
+/// 按 --attr-quotes 重写开始标签的属性：统一引号、给无引号的值加引号、布尔属性去掉值；
+/// 值中含有首选引号时改用另一种引号，两种都含有时保持原样
+/// Rewrite the attributes of start tags per --attr-quotes: consistent quotes, bare values quoted, boolean attributes
+/// without a value; a value containing the preferred quote uses the other one, and stays as is when it contains both
+fn normalize_attribute_quotes(tokens: &mut [HtmlToken], policy: AttrQuotes) {
+    let preferred = match policy {
+        AttrQuotes::Double => '"',
+        AttrQuotes::Single => '\'',
+        AttrQuotes::Preserve => return,
+    };
+    let other = if preferred == '"' { '\'' } else { '"' };
+    for token in tokens.iter_mut() {
+        let HtmlToken::StartTag { raw, self_closing, .. } = token else {
+            continue;
+        };
+        let tag_name: String = raw[1..].chars().take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>').collect();
+        let mut tag = tag_name.clone();
+        for attribute in parse_html_attributes(raw) {
+            tag.push(' ');
+            tag.push_str(&attribute.name);
+            let Some(value) = attribute.value else {
+                continue;
+            };
+            let lower = attribute.name.to_ascii_lowercase();
+            if BOOLEAN_ATTRIBUTES.contains(&lower.as_str()) && (value.is_empty() || value.eq_ignore_ascii_case(&lower)) {
+                continue;
+            }
+            let quote = if !value.contains(preferred) {
+                preferred
+            } else if !value.contains(other) {
+                other
+            } else {
+                attribute.quote.unwrap_or(preferred)
+            };
+            tag.push_str(&format!("={}{}{}", quote, value, quote));
+        }
+        if *self_closing {
+            tag.push_str(" /");
+        }
+        *raw = format!("<{}>", format_tag_attributes(&tag));
+    }
+}
+
+/// 属性值中是否含有字符实体（如 `&quot;`）或模板语法，这类值按 CSS/JS 格式化会被破坏
+/// Whether an attribute value holds character references (such as `&quot;`) or template syntax, which formatting as
+/// CSS/JS would break
+fn has_entity_or_template(value: &str) -> bool {
+    let entity = value.match_indices('&').any(|(i, _)| {
+        let rest = &value[i + 1..];
+        let name = rest.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '#').count();
+        name > 0 && rest[name..].starts_with(';')
+    });
+    entity || ["{{", "{%", "<%", "<?"].iter().any(|marker| value.contains(marker))
+}
+
+/// 按 CSS 声明规则格式化 `style` 属性的值（`property: value; property: value`）；不是纯声明列表时返回 None
+/// Format the value of a `style` attribute with the CSS declaration rules (`property: value; property: value`); None
+/// when it isn't a plain list of declarations
+fn format_style_attribute(value: &str, options: &FormatOptions) -> Option<String> {
+    if value.contains(['{', '}']) || has_entity_or_template(value) {
+        return None;
+    }
+    let nodes = parse_css_nodes(&mut value.chars().peekable(), 1);
+    let declarations = nodes
+        .iter()
+        .map(|node| match node {
+            CssNode::Declaration { property, value } => Some(format_css_declaration(property, value, options)),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(declarations.join("; "))
+}
+
+/// 按 JS 空格规则格式化事件处理属性的值，多行结果在语句与括号边界处接回一行；无法可靠格式化时返回 None
+/// Format the value of an event handler attribute with the JS spacing rules, joining a multi-line result back into one
+/// line at statement and bracket boundaries; None when it can't be formatted reliably
+fn format_event_handler(value: &str, options: &FormatOptions) -> Option<String> {
+    if value.trim().is_empty() || value.contains(['\n', '`']) || value.contains("//") || has_entity_or_template(value) || !syntax_errors(value, "js").is_empty() {
+        return None;
+    }
+    let formatted = format_js_ts(value.trim(), options).ok()?;
+    let lines: Vec<&str> = formatted.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    // 换行可能结束语句（自动分号插入），只在不影响语义的位置接回
+    // A line break may end a statement (automatic semicolon insertion), so lines are only joined where that can't matter
+    let joinable = lines.windows(2).all(|pair| pair[0].ends_with([';', '{', ',', '(', '[']) || pair[1].starts_with(['}', ')', ']']));
+    joinable.then(|| lines.join(" "))
+}
+
+/// 格式化开始标签中的内嵌语言属性：`style` 按 CSS 声明规则，--format-event-handlers 时 `on*` 按 JS 规则；
+/// 属性值内从不引入换行，格式化结果含有值的引号字符时保持原样
+/// Format the embedded-language attributes of start tags: `style` with the CSS declaration rules, and with
+/// --format-event-handlers `on*` with the JS rules; no line break is ever introduced inside a value, and a value stays as
+/// is when the result would contain its quote character
+fn format_inline_attributes(tokens: &mut [HtmlToken], options: &FormatOptions) {
+    for token in tokens.iter_mut() {
+        let HtmlToken::StartTag { raw, self_closing, .. } = token else {
+            continue;
+        };
+        let mut changed = false;
+        let mut tag: String = raw[1..].chars().take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>').collect();
+        for attribute in parse_html_attributes(raw) {
+            tag.push(' ');
+            tag.push_str(&attribute.name);
+            let Some(value) = attribute.value else {
+                continue;
+            };
+            let lower = attribute.name.to_ascii_lowercase();
+            let formatted = if lower == "style" {
+                format_style_attribute(&value, options)
+            } else if options.format_event_handlers && lower.starts_with("on") {
+                format_event_handler(&value, options)
+            } else {
+                None
+            };
+            let quote = attribute.quote.unwrap_or('"');
+            match formatted {
+                Some(formatted) if formatted != value && !formatted.contains(quote) => {
+                    changed = true;
+                    tag.push_str(&format!("={}{}{}", quote, formatted, quote));
+                }
+                _ => match attribute.quote {
+                    Some(quote) => tag.push_str(&format!("={}{}{}", quote, value, quote)),
+                    None => tag.push_str(&format!("={}", value)),
+                },
+            }
+        }
+        if changed {
+            if *self_closing {
+                tag.push_str(" /");
+            }
+            *raw = format!("<{}>", format_tag_attributes(&tag));
+        }
+    }
+}
+
+/// 判断注释是否为指令类注释（不可重新折行）：格式化/检查指令、条件注释、SSI、许可证头
+/// Whether a comment is directive-like (never reflowed): tool directives, conditional comments, SSI, license headers
+fn is_directive_comment(body: &str) -> bool {
+    let text = body.trim().to_lowercase();
+    ["prettier-", "eslint", "@license", "@preserve", "!", "[if", "<![endif]", "#", "ko ", "/ko"]
+        .iter()
+        .any(|prefix| text.starts_with(prefix))
+        || text.contains("copyright")
+        || text.contains("license")
+}
+
+/// 按单词边界把文本折成不超过指定宽度的多行
+/// Wrap text at word boundaries into lines no wider than the given width
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && display_width(&line) + 1 + display_width(word) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 按单词边界折行注释文本：反引号包围的行内代码（如 `a + b`）作为一个整体，不在其中断行
+/// Wrap comment text at word boundaries; inline code in backticks (such as `a + b`) stays whole and is never broken
+fn wrap_comment_words(text: &str, width: usize) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match words.last_mut() {
+            Some(last) if last.matches('`').count() % 2 == 1 => {
+                last.push('\u{0}');
+                last.push_str(word);
+            }
+            _ => words.push(word.to_string()),
+        }
+    }
+    wrap_words(&words.join(" "), width).iter().map(|line| line.replace('\u{0}', " ")).collect()
+}
+
+/// 注释正文按段落重新折行：空行分段，以 `@`、`-`、`*`、`+` 或数字序号开头的行开始新段落；
+/// `` ``` `` 围起的代码块与缩进 4 格以上的代码行原样保留
+/// Reflow comment text paragraph by paragraph: blank lines separate paragraphs, lines starting with `@`, `-`, `*`, `+`
+/// or a number start a new one; code fenced with `` ``` `` and lines indented by 4 or more are kept verbatim
+fn reflow_comment_lines(lines: &[&str], width: usize) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    let flush = |paragraph: &mut Vec<&str>, result: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            result.extend(wrap_comment_words(&paragraph.join(" "), width));
+            paragraph.clear();
+        }
+    };
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || in_fence || line.starts_with("    ") || line.starts_with('\t') {
+            flush(&mut paragraph, &mut result);
+            if trimmed.starts_with("```") {
+                in_fence = !in_fence;
+            }
+            result.push(line.trim_end().to_string());
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut result);
+            result.push(String::new());
+            continue;
+        }
+        let list_item = trimmed.starts_with(['@', '-', '*', '+'])
+            || trimmed.split_once(['.', ')']).is_some_and(|(number, _)| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()));
+        if list_item {
+            flush(&mut paragraph, &mut result);
+        }
+        paragraph.push(trimmed);
+    }
+    flush(&mut paragraph, &mut result);
+    result
+}
+
+/// 超过行宽的 `/* */` 注释按行宽重新折行，每行以 ` * ` 引导；注释已在行宽内或为指令/许可证注释时返回 None
+/// `indent` 为注释前缩进占用的列数；返回不含缩进的各行
+/// Reflow a `/* */` comment that exceeds the line length, each line led by ` * `; None when the comment already fits
+/// or is a directive/license comment
+/// `indent` is the number of columns taken by the indentation before the comment; the lines are returned without it
+pub(crate) fn reflow_block_comment(comment: &str, indent: usize, max_line_length: usize) -> Option<Vec<String>> {
+    let body = comment.strip_prefix("/*")?.strip_suffix("*/")?;
+    if is_directive_comment(body) || comment.lines().all(|line| indent + display_width(line.trim()) <= max_line_length) {
+        return None;
+    }
+    let doc = body.starts_with('*');
+    let body = if doc { &body[1..] } else { body };
+    // 去掉每行开头的 `*` 引导符及其后的一个空格
+    // Strip the leading `*` gutter of every line and the space after it
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            match trimmed.strip_prefix('*') {
+                Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+                None => trimmed,
+            }
+        })
+        .collect();
+    let start = lines.iter().position(|line| !line.trim().is_empty())?;
+    let end = lines.iter().rposition(|line| !line.trim().is_empty())? + 1;
+    let width = max_line_length.saturating_sub(indent + 3).max(1);
+
+    let mut result = vec![if doc { "/**".to_string() } else { "/*".to_string() }];
+    for line in reflow_comment_lines(&lines[start..end], width) {
+        result.push(if line.is_empty() { " *".to_string() } else { format!(" * {}", line) });
+    }
+    result.push(" */".to_string());
+    Some(result)
+}
+
+/// 超过行宽的连续 `//` 注释按行宽重新折行；都在行宽内或含指令注释（如 `// eslint-disable`、`/// <reference>`）时返回 None
+/// `indent` 为注释前缩进占用的列数；返回不含缩进的各行
+/// Reflow a run of `//` comments that exceeds the line length; None when they all fit or the run contains a directive
+/// (such as `// eslint-disable` or `/// <reference>`)
+/// `indent` is the number of columns taken by the indentation before the comments; the lines are returned without it
+pub(crate) fn reflow_line_comments(comments: &[&str], indent: usize, max_line_length: usize) -> Option<Vec<String>> {
+    let bodies: Vec<&str> = comments.iter().map(|comment| comment.strip_prefix("//").unwrap_or(comment)).collect();
+    if bodies.iter().any(|body| body.starts_with('/') || is_directive_comment(body))
+        || comments.iter().all(|comment| indent + display_width(comment.trim_end()) <= max_line_length)
+    {
+        return None;
+    }
+    let lines: Vec<&str> = bodies.iter().map(|body| body.strip_prefix(' ').unwrap_or(body)).collect();
+    let width = max_line_length.saturating_sub(indent + 3).max(1);
+    let reflowed = reflow_comment_lines(&lines, width);
+    Some(reflowed.into_iter().map(|line| if line.is_empty() { "//".to_string() } else { format!("// {}", line) }).collect())
+}
+
+/// 判断声明是否为 DOCTYPE
+/// Whether a declaration is a DOCTYPE
+fn is_doctype(decl: &str) -> bool {
+    decl.get(..9).is_some_and(|head| head.eq_ignore_ascii_case("<!doctype"))
+}
+
+/// 规范化 DOCTYPE 与 meta charset：
+/// 旧式 DOCTYPE 改写为 `<!DOCTYPE html>` 并移到文档首行，
+/// `<meta http-equiv="Content-Type" content="...; charset=X">` 改写为 `<meta charset="X">`
+/// Normalize DOCTYPE and meta charset:
+/// legacy doctypes become `<!DOCTYPE html>` and move to the first line,
+/// `<meta http-equiv="Content-Type" content="...; charset=X">` becomes `<meta charset="X">`
+pub(crate) fn normalize_doctype(tokens: &mut Vec<HtmlToken>) {
+    if let Some(pos) = tokens.iter().position(|t| matches!(t, HtmlToken::Declaration(d) if is_doctype(d))) {
+        tokens.remove(pos);
+        tokens.insert(0, HtmlToken::Declaration("<!DOCTYPE html>".to_string()));
+    }
+
+    for token in tokens.iter_mut() {
+        let HtmlToken::StartTag { name, raw, .. } = token else {
+            continue;
+        };
+        if name != "meta" {
+            continue;
+        }
+        let attributes = parse_html_attributes(raw);
+        let is_content_type = attributes.iter().any(|a| {
+            a.name.eq_ignore_ascii_case("http-equiv")
+                && a.value.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("content-type"))
+        });
+        if !is_content_type {
+            continue;
+        }
+        let charset = attributes
+            .iter()
+            .find(|a| a.name.eq_ignore_ascii_case("content"))
+            .and_then(|a| a.value.as_deref())
+            .and_then(|v| {
+                let lower = v.to_ascii_lowercase();
+                lower.find("charset=").map(|i| v[i + 8..].trim().to_string())
+            });
+        if let Some(charset) = charset {
+            let tag = format!("meta charset=\"{}\"", charset.to_ascii_lowercase());
+            *raw = format!("<{}>", format_tag_attributes(&tag));
+        }
+    }
+}
+
+/// HTML 输出构建器：块级内容独占一行，行内内容按原有空白拼接
+/// HTML output builder: block content gets its own lines, inline content keeps its original whitespace
+struct HtmlEmitter<'a> {
+    indent_unit: &'a str,
+    /// 一级缩进占用的列数
+    /// Columns taken by one indentation level
+    indent_width: usize,
+    max_line_length: usize,
+    prose_wrap: ProseWrap,
+    result: String,
+    current_indent_level: usize,
+    /// 当前行的内容（不含缩进）
+    /// Content of the current line (without indentation)
+    line: String,
+    /// 行内内容之间是否有待输出的空白
+    /// Whether whitespace is pending between inline content
+    pending_space: bool,
+    /// 当前行开头、尚未换行的块级开始标签（名称, 标签长度）
+    /// Block start tag at the head of the current line that hasn't been broken yet (name, tag length)
+    open_block: Option<(String, usize)>,
+}
+
+impl<'a> HtmlEmitter<'a> {
+    fn new(indent_unit: &'a str, indent_width: usize, max_line_length: usize, prose_wrap: ProseWrap) -> Self {
+        HtmlEmitter {
+            indent_unit,
+            indent_width,
+            max_line_length,
+            prose_wrap,
+            result: String::new(),
+            current_indent_level: 0,
+            line: String::new(),
+            pending_space: false,
+            open_block: None,
+        }
+    }
+
+    /// 当前行的缩进层级
+    /// Indentation level of the current line
+    fn line_indent_level(&self) -> usize {
+        if self.open_block.is_some() {
+            self.current_indent_level.saturating_sub(1)
+        } else {
+            self.current_indent_level
+        }
+    }
+
+    fn line_length(&self) -> usize {
+        self.indent_width * self.line_indent_level() + display_width(&self.line)
+    }
+
+    fn write_line(&mut self, level: usize, text: &str) {
+        self.result.push_str(&self.indent_unit.repeat(level));
+        self.result.push_str(text);
+        self.result.push('\n');
+    }
+
+    /// 把行首的块级开始标签单独输出为一行，剩余内容留在当前行
+    /// Emit the block start tag at the head of the line on its own, keeping the rest on the current line
+    fn split_open_block(&mut self) {
+        if let Some((_, tag_len)) = self.open_block.take() {
+            let rest = self.line.split_off(tag_len);
+            let tag = std::mem::replace(&mut self.line, rest.trim_start().to_string());
+            self.write_line(self.current_indent_level - 1, &tag);
+        }
+    }
+
+    /// 结束当前行
+    /// Finish the current line
+    fn flush_line(&mut self) {
+        self.split_open_block();
+        let line = std::mem::take(&mut self.line);
+        let line = line.trim_end();
+        if !line.is_empty() {
+            self.write_line(self.current_indent_level, line);
+        }
+        self.pending_space = false;
+    }
+
+    /// 追加行内内容，仅在原有空白处换行；--prose-wrap 为 never/preserve 时不因行宽换行
+    /// Append inline content, breaking lines only at existing whitespace; never for the line length with --prose-wrap never/preserve
+    fn push_inline(&mut self, atom: &str) {
+        let at_block_start = self.open_block.as_ref().is_some_and(|(_, len)| self.line.len() == *len);
+        if self.pending_space && !self.line.is_empty() && !at_block_start {
+            if self.prose_wrap == ProseWrap::Always && self.line_length() + 1 + display_width(atom) > self.max_line_length {
+                self.flush_line();
+            } else {
+                self.line.push(' ');
+            }
+        } else if self.open_block.is_some()
+            && self.line_length() + display_width(atom) > self.max_line_length
+        {
+            self.split_open_block();
+        }
+        self.pending_space = false;
+        self.line.push_str(atom);
+    }
+
+    /// 追加文本：连续空白折叠为一个空格，但不删除有意义的空白；--prose-wrap preserve 时原文的换行保留为换行
+    /// Append text: whitespace runs collapse to one space but significant whitespace is never dropped; with
+    /// --prose-wrap preserve the author's line breaks stay line breaks
+    fn push_text(&mut self, text: &str) {
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    self.push_inline(&std::mem::take(&mut word));
+                }
+                if c == '\n' && self.prose_wrap == ProseWrap::Preserve && !self.line.is_empty() {
+                    self.flush_line();
+                }
+                self.pending_space = true;
+            } else {
+                word.push(c);
+            }
+        }
+        if !word.is_empty() {
+            self.push_inline(&word);
+        }
+    }
+
+    /// 独占一行的内容（块级标签、声明、注释）
+    /// Content that gets a line of its own (block tags, declarations, comments)
+    fn push_own_line(&mut self, text: &str) {
+        self.flush_line();
+        self.write_line(self.current_indent_level, text);
+    }
+
+    /// 独占一行的注释：多行注释按当前缩进对齐，可选按行宽重新折行
+    /// Comment on its own lines: multi-line comments align to the current indentation, optionally reflowed
+    fn push_comment(&mut self, comment: &str, wrap: bool) {
+        self.flush_line();
+        let level = self.current_indent_level;
+        let body = comment.trim_start_matches("<!--").trim_end_matches("-->");
+        let fits = comment
+            .lines()
+            .all(|l| self.indent_width * (level + 1) + display_width(l.trim()) <= self.max_line_length)
+            && (comment.contains('\n') || self.indent_width * level + display_width(comment) <= self.max_line_length);
+
+        if wrap && !fits && !is_directive_comment(body) {
+            self.write_line(level, "<!--");
+            let width = self.max_line_length.saturating_sub(self.indent_width * (level + 1));
+            for (i, paragraph) in body.split("\n\n").enumerate() {
+                if i > 0 {
+                    self.result.push('\n');
+                }
+                for line in wrap_words(paragraph, width) {
+                    self.write_line(level + 1, &line);
+                }
+            }
+            self.write_line(level, "-->");
+            return;
+        }
+
+        if !comment.contains('\n') {
+            self.write_line(level, comment);
+            return;
+        }
+
+        // 多行注释：去掉原有的公共缩进后按当前层级重新对齐
+        // Multi-line comment: strip the original common indentation and realign to the current level
+        let mut lines = comment.lines();
+        let first = lines.next().unwrap_or_default().trim();
+        let rest: Vec<&str> = lines.collect();
+        let common_indent = rest
+            .iter()
+            .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with("-->"))
+            .map(|l| l.len() - l.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        self.write_line(level, first);
+        for line in rest {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                self.result.push('\n');
+            } else if trimmed.starts_with("-->") {
+                self.write_line(level, trimmed.trim_end());
+            } else {
+                self.write_line(level + 1, line[common_indent.min(line.len() - trimmed.len())..].trim_end());
+            }
+        }
+    }
+
+    fn open_block_tag(&mut self, name: &str, raw: &str) {
+        self.flush_line();
+        self.line.push_str(raw);
+        self.open_block = Some((name.to_string(), raw.len()));
+        self.current_indent_level += 1;
+    }
+
+    fn close_block_tag(&mut self, name: &str, raw: &str) {
+        let same_line = self.open_block.as_ref().is_some_and(|(open, _)| open == name)
+            && self.line_length() + display_width(raw) <= self.max_line_length;
+        if same_line {
+            // 内容较短：开始标签、内容、闭合标签保持在同一行
+            // Short content: keep start tag, content and end tag on one line
+            self.open_block = None;
+            self.current_indent_level = self.current_indent_level.saturating_sub(1);
+            self.line.push_str(raw);
+            self.flush_line();
+        } else {
+            self.flush_line();
+            self.current_indent_level = self.current_indent_level.saturating_sub(1);
+            self.write_line(self.current_indent_level, raw);
+        }
+    }
+
+    /// 原样输出元素（script/style/pre/textarea）
+    /// Emit a raw text element (script/style/pre/textarea)
+    fn push_raw_element(&mut self, name: &str, start: &str, content: &str, end: &str) {
+        match name {
+            "pre" | "textarea" => {
+                // 预格式化内容：空白与换行都必须保持原样
+                // Preformatted content: whitespace and line breaks must stay untouched
+                let element = format!("{}{}{}", start, content, end);
+                if name == "pre" {
+                    self.push_own_line(&element);
+                } else {
+                    self.push_inline(&element);
+                }
+            }
+            _ => {
+                self.flush_line();
+                let level = self.current_indent_level;
+                let body = content.trim_start_matches(['\n', '\r']).trim_end();
+                if body.is_empty() {
+                    self.write_line(level, &format!("{}{}", start, end));
+                } else if name == "script" && !content.contains('\n') {
+                    // 未格式化的脚本内容原样保留，单行内容与标签留在同一行
+                    // Script content that wasn't formatted stays verbatim, a single-line body stays on the tag line
+                    self.write_line(level, &format!("{}{}{}", start, content, end));
+                } else if name != "script" && !body.contains('\n') {
+                    self.write_line(level, start);
+                    self.write_line(level + 1, body.trim_start());
+                    self.write_line(level, end);
+                } else {
+                    self.write_line(level, start);
+                    self.result.push_str(body);
+                    self.result.push('\n');
+                    self.write_line(level, end);
+                }
+            }
+        }
+    }
+
+    /// 输出内容已格式化的脚本元素：开始与结束标签各占一行，内容每行比标签多缩进一层
+    /// Emit a script element whose content is already formatted: the start and end tags on their own lines, each content
+    /// line indented one level deeper than the tags
+    fn push_formatted_element(&mut self, start: &str, body: &str, end: &str) {
+        self.flush_line();
+        let level = self.current_indent_level;
+        self.write_line(level, start);
+        for line in body.lines() {
+            if line.is_empty() {
+                self.result.push('\n');
+            } else {
+                self.write_line(level + 1, line);
+            }
+        }
+        self.write_line(level, end);
+    }
+
+    fn finish(mut self) -> String {
+        self.flush_line();
+        self.result
+    }
+}
+
+/// `<script>` 内容的种类，由 `type` 属性决定
+/// Kind of `<script>` content, decided by the `type` attribute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ScriptKind {
+    /// 经典脚本与模块脚本，按 JS 格式化
+    /// Classic and module scripts, formatted as JS
+    Js,
+    /// JSON 数据（`application/json`、`importmap`、`*+json` 等），按 JSON 格式化
+    /// JSON payloads (`application/json`, `importmap`, `*+json` and so on), formatted as JSON
+    Json,
+    /// 其他类型（如 `text/template`），内容原样保留
+    /// Any other type (such as `text/template`), the content is kept verbatim
+    Verbatim,
+}
+
+/// 按开始标签的 `type` 属性判断脚本内容的种类
+/// Tell the kind of script content from the `type` attribute of the start tag
+pub(crate) fn script_kind(start_tag: &str) -> ScriptKind {
+    let attributes = parse_html_attributes(start_tag);
+    let script_type = attributes
+        .iter()
+        .find(|attribute| attribute.name.eq_ignore_ascii_case("type"))
+        .and_then(|attribute| attribute.value.as_deref())
+        .unwrap_or("");
+    let mime = script_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "" | "module" | "text/javascript" | "application/javascript" | "text/ecmascript" | "application/ecmascript"
+        | "application/x-javascript" => ScriptKind::Js,
+        "application/json" | "importmap" | "speculationrules" => ScriptKind::Json,
+        other if other.ends_with("+json") => ScriptKind::Json,
+        _ => ScriptKind::Verbatim,
+    }
+}
+
+/// 按脚本种类格式化 `<script>` 的内容；无法安全格式化时返回 None，由调用方原样输出
+/// Format the content of a `<script>` by its kind; returns None when it can't be formatted safely, and the caller then
+/// emits it verbatim
+fn format_script_content(start_tag: &str, content: &str, options: &FormatOptions) -> Option<String> {
+    let body = content.trim();
+    // 旧式的 `<!--` 包裹对 JS 词法分析来说是运算符，保持原样
+    // Old-style `<!--` wrapping reads as operators to the JS tokenizer, leave it alone
+    if body.is_empty() || body.contains("<!--") {
+        return None;
+    }
+    let formatted = match script_kind(start_tag) {
+        ScriptKind::Js => {
+            // 跨行的字符串在重新缩进后内容会改变
+            // Strings spanning lines would change once re-indented
+            let multiline_string = tokenize_js(body)
+                .iter()
+                .any(|token| matches!(token, JsToken::Str(text) | JsToken::Template(text) if text.contains('\n')));
+            if multiline_string || !syntax_errors(body, "js").is_empty() {
+                return None;
+            }
+            format_js_ts(body, options).ok()?
+        }
+        ScriptKind::Json => format_json(body, options).ok()?,
+        ScriptKind::Verbatim => return None,
+    };
+    Some(formatted.trim_end().to_string())
+}
+
+pub(crate) fn format_html(content: &str, options: &FormatOptions) -> Result<String> {
+    let indent_unit = options.indent_unit();
+    let mut emitter = HtmlEmitter::new(&indent_unit, options.indent_width(), options.line_length, options.prose_wrap);
+    emitter.result.reserve(content.len() * 2);
+    let mut tokens = tokenize_html(content);
+    if options.normalize_doctype {
+        normalize_doctype(&mut tokens);
+    }
+    normalize_attribute_quotes(&mut tokens, options.attr_quotes);
+    format_inline_attributes(&mut tokens, options);
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        if log_enabled(LogLevel::Trace) {
+            log(LogLevel::Trace, tr!("html: {:?}（缩进层级 {}）", "html: {:?} (indent level {})", token, emitter.current_indent_level));
+        }
+        match token {
+            HtmlToken::Declaration(decl) if is_doctype(&decl) => {
+                // DOCTYPE 始终顶格输出，不继承当前缩进
+                // DOCTYPE is always emitted at column 0, never inheriting the current indentation
+                emitter.flush_line();
+                emitter.write_line(0, &decl);
+            }
+            HtmlToken::Declaration(decl) => emitter.push_own_line(&decl),
+            HtmlToken::Comment(comment) => {
+                // 行内注释紧贴内容时保持在行内，避免引入空白
+                // A comment touching inline content stays inline so no whitespace is introduced
+                if !emitter.line.is_empty() && !emitter.pending_space && emitter.open_block.is_none() {
+                    emitter.push_inline(&comment);
+                } else {
+                    emitter.push_comment(&comment, options.wrap_comments);
+                }
+            }
+            HtmlToken::StartTag { name, raw, self_closing } => {
+                let is_inline = INLINE_ELEMENTS.contains(&name.as_str());
+                let is_void = VOID_ELEMENTS.contains(&name.as_str()) || self_closing;
+
+                if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                    let content = match tokens.next_if(|t| matches!(t, HtmlToken::RawText(_))) {
+                        Some(HtmlToken::RawText(text)) => text,
+                        _ => String::new(),
+                    };
+                    let end = match tokens.next_if(|t| matches!(t, HtmlToken::EndTag { name: end_name, .. } if *end_name == name)) {
+                        Some(HtmlToken::EndTag { raw, .. }) => raw,
+                        _ => String::new(),
+                    };
+                    let script = if name == "script" { format_script_content(&raw, &content, options) } else { None };
+                    match script {
+                        Some(body) => emitter.push_formatted_element(&raw, &body, &end),
+                        None => emitter.push_raw_element(&name, &raw, &content, &end),
+                    }
+                } else if is_inline {
+                    emitter.push_inline(&raw);
+                    // <br> 本身就是换行，之后换行不影响渲染
+                    // <br> is a line break itself, breaking the line after it doesn't affect rendering
+                    if name == "br" {
+                        emitter.flush_line();
+                    }
+                } else if is_void || (name == "html" && !options.indent_root_elements) {
+                    // 不缩进根元素时 <html> 不增加缩进层级，<head> 与 <body> 随之顶格
+                    // Without root indentation <html> adds no indent level, so <head> and <body> sit at column 0
+                    emitter.push_own_line(&raw);
+                } else {
+                    emitter.open_block_tag(&name, &raw);
+                }
+            }
+            HtmlToken::EndTag { name, raw } => {
+                if INLINE_ELEMENTS.contains(&name.as_str()) {
+                    emitter.push_inline(&raw);
+                } else if name == "html" && !options.indent_root_elements {
+                    emitter.push_own_line(&raw);
+                } else {
+                    emitter.close_block_tag(&name, &raw);
+                }
+            }
+            HtmlToken::Text(text) => emitter.push_text(&text),
+            HtmlToken::RawText(text) => emitter.push_inline(&text),
+        }
+    }
+
+    Ok(emitter.finish())
+}
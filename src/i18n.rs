@@ -0,0 +1,225 @@
+//! 界面语言（消息目录）
+//! Interface language (message catalog)
+
+use clap::{CommandFactory, ValueEnum};
+use std::sync::atomic::{AtomicU8, Ordering};
+use crate::*;
+
+/// 界面语言
+/// Interface language
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lang {
+    /// 中文
+    /// Chinese
+    Zh,
+    /// 英文
+    /// English
+    En,
+}
+
+pub(crate) static LANG: AtomicU8 = AtomicU8::new(Lang::Zh as u8);
+
+impl Lang {
+    /// 根据 LC_ALL/LC_MESSAGES/LANG 推断语言：未设置或为 C/POSIX 时使用中文，zh* 为中文，其余为英文
+    /// Infer the language from LC_ALL/LC_MESSAGES/LANG: Chinese when unset or C/POSIX, Chinese for zh*, English otherwise
+    pub(crate) fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        if locale.is_empty() || locale == "C" || locale == "POSIX" || locale.starts_with("zh") {
+            Lang::Zh
+        } else {
+            Lang::En
+        }
+    }
+
+    /// 在解析命令行之前找出 `--lang`，使帮助信息与参数错误也使用所选语言
+    /// Find `--lang` before the command line is parsed so help and argument errors use the chosen language too
+    pub(crate) fn from_args(args: &[String]) -> Option<Self> {
+        let mut iter = args.iter().skip(1).take_while(|arg| *arg != "--");
+        while let Some(arg) = iter.next() {
+            let value = match arg.strip_prefix("--lang") {
+                Some("") => iter.next().map(String::as_str),
+                Some(rest) => rest.strip_prefix('='),
+                None => None,
+            };
+            if let Some(lang) = value.and_then(|value| Lang::from_str(value, true).ok()) {
+                return Some(lang);
+            }
+        }
+        None
+    }
+}
+
+/// 设置界面语言
+/// Set the interface language
+pub(crate) fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// 当前界面语言
+/// Current interface language
+pub(crate) fn current_lang() -> Lang {
+    if LANG.load(Ordering::Relaxed) == Lang::En as u8 {
+        Lang::En
+    } else {
+        Lang::Zh
+    }
+}
+
+/// 按当前界面语言格式化消息：先写中文格式串，再写英文格式串，之后是共同的参数
+/// Format a message in the current interface language: the Chinese format string, then the English one, then the shared arguments
+macro_rules! tr {
+    ($zh:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match current_lang() {
+            Lang::Zh => format!($zh $(, $arg)*),
+            Lang::En => format!($en $(, $arg)*),
+        }
+    };
+}
+
+/// 程序简介
+/// Program description
+const ABOUT: [&str; 2] = [
+    "压缩代码格式化工具：符合行业规范的 HTML/CSS/JS/TS/JSON 格式化（高可读性）",
+    "Code formatter for compressed code: Industry-standard HTML/CSS/JS/TS/JSON formatting (high readability)",
+];
+
+/// minify 子命令简介
+/// Description of the minify subcommand
+const MINIFY_ABOUT: [&str; 2] = [
+    "压缩代码：删除注释与多余空白（格式化的逆操作）",
+    "Minify code: strip comments and unnecessary whitespace (the reverse of formatting)",
+];
+
+/// debug-tokens 子命令简介
+/// Description of the debug-tokens subcommand
+const DEBUG_TOKENS_ABOUT: [&str; 2] = [
+    "输出格式化器内部的词法单元与状态流（标签开闭、字符串/注释/括号、缩进层级变化），便于附在问题报告中",
+    "Dump the formatter's internal token and state stream (tag open/close, strings/comments/brackets, indent level changes) for bug reports",
+];
+
+/// 参数帮助的英文目录（中文帮助写在参数定义上）
+/// English catalog of the argument help (the Chinese help lives on the argument definitions)
+const HELP_EN: &[(&str, &str)] = &[
+    ("input", "Input file or directory paths; several may be given (several files or a directory require --write or --check), directories are searched recursively for files with a recognized extension, - reads from stdin"),
+    ("output", "Output file path of the formatted code, - writes to stdout"),
+    ("write", "Format in place: write the result back to the input file"),
+    ("backup", "Before overwriting an existing file, back it up to <file name><suffix> in the same directory, default suffix .bak"),
+    ("dry_run", "Run the full formatting and report the files and byte changes that would be written, without modifying any file"),
+    ("diff", "Print a unified diff in --check or --dry-run mode"),
+    ("file_type", "Code type: html/css/js/ts/json (or a type provided by a plugin), or a file name to infer the type from (e.g. src/app.ts); used for stdin or files without an extension"),
+    ("config", "Configuration file path, by default .code_formatter.toml is searched upwards from the input's directory"),
+    #[cfg(feature = "plugins")]
+    ("plugins", "Load the .wasm formatter plugins in this directory, adding formatters for new code types"),
+    ("check", "Only check whether files are formatted without writing anything; exits non-zero when formatting is needed"),
+    ("indent", "Indentation width, default 4; with tab indentation it is the tab width used to measure line length"),
+    ("final_newline", "Final newline: always (exactly one) / never (strip) / preserve (same as input), default always"),
+    ("trim_trailing_whitespace", "Strip trailing whitespace on every output line (including content kept verbatim such as pre/template strings)"),
+    ("encoding", "Input file encoding: auto/utf-8/gbk/gb18030/latin1, default auto; the output uses the same encoding"),
+    ("bom", "UTF-8 BOM: keep (if the input has one) / strip / add (always), default keep"),
+    ("indent_style", "Indentation style: tabs/spaces, default spaces"),
+    ("line_length", "Maximum line length in characters, default 80"),
+    ("normalize_doctype", "Rewrite legacy DOCTYPEs to <!DOCTYPE html> on the first line and normalize meta charset"),
+    ("attr_quotes", "Quotes of HTML attribute values: double/single (consistent quotes, bare values quoted, redundant boolean attribute values dropped) / preserve, default preserve"),
+    ("wrap_comments", "Reflow comment text that exceeds the maximum line length (HTML, CSS, JS/TS; code in backticks and indented code blocks stay untouched)"),
+    ("prose_wrap", "Wrapping of HTML text: always (at whitespace once the line length is exceeded) / never / preserve (keep the author's line breaks, add none), default always"),
+    ("indent_root_elements", "Whether <head> and <body> are indented one level under <html>: true/false, default true; with false they sit at column 0 like <html>"),
+    ("format_event_handlers", "Format the values of on* attributes such as onclick with the JS spacing rules (the result always stays on one line); style attributes are always formatted with the CSS declaration rules"),
+    ("sort_properties", "CSS declaration order: alphabetical/concentric/none, default none"),
+    ("sort_keys", "JSON object key order: alphabetical (the default without a value) / package-json (top-level package.json fields in the conventional order, dependency maps alphabetical) / none, default none"),
+    ("css_single_line_rules", "When CSS rules collapse to one line: never/auto/always, default auto"),
+    ("normalize_colors", "Lowercase hex colors and normalize spacing inside rgb()/rgba()/hsl()/hsla()"),
+    ("short_hex_colors", "Shorten #ffffff to #fff (requires --normalize-colors)"),
+    ("leading_zero", "Leading zero of CSS decimals: add (0.5em) / remove (.5em) / preserve, default preserve"),
+    ("strip_zero_units", "Write zero lengths such as 0px as 0 (not inside functions like calc() or custom properties)"),
+    ("preserve_data_uris", "Verify data URIs in the output match the input byte for byte, failing otherwise"),
+    ("split_selectors", "Put comma-separated selector lists one per line with the comma at the line end"),
+    ("else_placement", "Placement of else/catch/finally in JS/TS: same-line (} else {) / next-line (after the }), default same-line"),
+    ("indent_case_labels", "Indent case/default labels of a JS/TS switch one level (default: aligned with the switch); statements under a label get one more level"),
+    ("summary", "Print a statistics summary after the run: files, bytes, lines and elapsed time"),
+    ("report", "Print a machine-readable run report: json/sarif/github (sarif and github require --check; --report-eol only supports json)"),
+    ("report_file", "File the report is written to, default stdout"),
+    ("fail_fast", "With several files, stop at the first file that fails; by default the remaining files are processed and errors summarized at the end"),
+    ("seedless", "Guarantee byte-identical output and reports across platforms and environments: paths in reports use /, timings are recorded as 0, and messages are in English unless --lang is given"),
+    ("follow_symlinks", "Follow symlinks inside directory inputs, skipped by default"),
+    ("hidden", "Include hidden files and directories (starting with .) inside directory inputs, skipped by default"),
+    ("max_depth", "Maximum traversal depth for directory inputs, files directly inside the directory being at depth 1; unlimited by default"),
+    ("max_file_size", "Skip files larger than this size with a warning (e.g. 500K, 2M); unlimited by default"),
+    ("since", "Only process files modified after this point: the path of an existing file (its modification time), Unix seconds, a UTC time (such as 2024-05-01 or 2024-05-01T08:30:00), or a git ref (its commit time)"),
+    ("stamp", "Only process files modified after this file's modification time (all files when it doesn't exist), and update it to the start time of the run once the run finishes without errors; with --check only when every file is already formatted"),
+    ("report_eol", "Don't format; only check and list each file's mixed line endings, indentation mixing tabs and spaces, and trailing whitespace; with --write fix them in place (line endings and indentation follow the style most of the file uses)"),
+    ("stdin_batch", "Read JSON requests {\"path\", \"content\", \"options\"} from stdin, one per line, and write one JSON result line per request to stdout, so build tools can format many in-memory buffers through one process"),
+    ("lenient", "On malformed input (stray }, unclosed tags, truncated files...) format the rest as well as possible with warnings instead of failing"),
+    ("max_nesting", "Limit on the nesting depth of brackets and HTML block elements, failing instead of emitting excessive indentation, default 256"),
+    ("max_line_tokens", "Limit on the tokens of one JS/TS/CSS statement (between two of ; { }), failing when exceeded, default 100000"),
+    ("verify_stable", "Format the result a second time and fail with a diff when the two results differ (guarantees formatting is idempotent)"),
+    ("fail_on", "Which diagnostics cause a non-zero exit code: warnings/errors/never"),
+    ("quiet", "Only print errors"),
+    ("verbose", "-v prints per-file details, -vv additionally traces the formatters' tokens and state"),
+    ("preset", "Style preset: default/compact/readable/2-space, or one defined under [presets.<name>] in the config file; options given on the command line take precedence"),
+    ("lang", "Interface language: zh/en, defaults from the LC_ALL/LC_MESSAGES/LANG environment variables"),
+];
+
+/// validate 子命令简介
+/// Description of the validate subcommand
+const VALIDATE_ABOUT: [&str; 2] = [
+    "校验语法结构：报告不平衡的括号、未闭合的字符串/注释与不配对的 HTML 标签，不修改文件",
+    "Validate the syntax structure: report unbalanced brackets, unclosed strings/comments and unpaired HTML tags without modifying files",
+];
+
+/// minify 子命令参数帮助的英文目录
+/// English catalog of the minify subcommand's argument help
+const MINIFY_HELP_EN: &[(&str, &str)] = &[
+    ("input", "Input file path, - reads from stdin"),
+    ("output", "Output file path of the minified code, - writes to stdout"),
+    ("file_type", "Code type: html/css/js/ts/json (or a type provided by a plugin), or a file name to infer the type from"),
+];
+
+/// validate 子命令参数帮助的英文目录
+/// English catalog of the validate subcommand's argument help
+const VALIDATE_HELP_EN: &[(&str, &str)] = &[
+    ("input", "Paths of the files to validate; several may be given, - reads from stdin"),
+    ("file_type", "Code type: html/css/js/ts/json (or a type provided by a plugin), or a file name to infer the type from"),
+];
+
+/// debug-tokens 子命令参数帮助的英文目录
+/// English catalog of the debug-tokens subcommand's argument help
+const DEBUG_TOKENS_HELP_EN: &[(&str, &str)] = &[
+    ("input", "Input file path, - reads from stdin"),
+    ("file_type", "Code type: html/css/js/ts/json (or a type provided by a plugin), or a file name to infer the type from"),
+];
+
+/// 按界面语言生成命令行定义：简介只保留一种语言，英文界面替换参数帮助
+/// Build the command line definition for a language: descriptions keep a single language, English replaces the argument help
+pub(crate) fn localized_command(lang: Lang) -> clap::Command {
+    let index = lang as usize;
+    let english = |mut command: clap::Command, catalog: &[(&'static str, &'static str)]| {
+        if lang == Lang::En {
+            for (id, help) in catalog {
+                command = command.mut_arg(*id, |arg| arg.help(*help));
+            }
+        }
+        command
+    };
+    let command = english(Cli::command().about(ABOUT[index]), HELP_EN);
+    command
+        .mut_subcommand("minify", |minify| english(minify.about(MINIFY_ABOUT[index]), MINIFY_HELP_EN))
+        .mut_subcommand("validate", |validate| english(validate.about(VALIDATE_ABOUT[index]), VALIDATE_HELP_EN))
+        .mut_subcommand("debug-tokens", |debug| english(debug.about(DEBUG_TOKENS_ABOUT[index]), DEBUG_TOKENS_HELP_EN))
+}
+
+/// UTF-8 字节顺序标记
+/// UTF-8 byte order mark
+pub(crate) const UTF8_BOM: char = '\u{FEFF}';
+
+/// 去掉开头的 BOM，返回是否存在 BOM 及其余内容
+/// Strip a leading BOM, returning whether one was present and the remaining content
+pub(crate) fn strip_bom(content: &str) -> (bool, &str) {
+    match content.strip_prefix(UTF8_BOM) {
+        Some(rest) => (true, rest),
+        None => (false, content),
+    }
+}
@@ -0,0 +1,1263 @@
+//! JS/TS 格式化（完整缩进 + 最后一行处理）
+//! JS/TS Formatting (full indentation + last line handling)
+
+use anyhow::Result;
+use crate::*;
+
+/// JS/TS 词法单元
+/// JS/TS lexical token
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsToken {
+    /// 连续空白（`newline` 表示其中是否含换行）
+    /// A run of whitespace (`newline` tells whether it contains a line break)
+    Whitespace { newline: bool },
+    /// `// ...`（不含行尾换行）
+    /// `// ...` (without the trailing line break)
+    LineComment(String),
+    /// `/* ... */`
+    BlockComment(String),
+    /// 引号字符串（含引号，原样保留）
+    /// Quoted string (quotes included, kept verbatim)
+    Str(String),
+    /// 模板字符串（含反引号，原样保留）
+    /// Template literal (backticks included, kept verbatim)
+    Template(String),
+    /// 正则表达式字面量（含标志）
+    /// Regular expression literal (flags included)
+    Regex(String),
+    /// 标识符、关键字、数字
+    /// Identifier, keyword, number
+    Word(String),
+    /// 运算符与标点
+    /// Operator or punctuation
+    Punct(String),
+}
+
+/// 多字符运算符（按长度从长到短匹配）
+/// Multi-character operators (matched longest first)
+const JS_OPERATORS: &[&str] = &[
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "**", "<<", ">>",
+];
+
+/// 其后的 `/` 开始正则表达式而不是除号的关键字
+/// Keywords after which a `/` starts a regular expression rather than a division
+const JS_REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "case", "do", "else", "in", "of", "new", "delete", "void", "throw",
+    "instanceof", "yield", "await",
+];
+
+/// 后跟括号条件的控制流关键字，与 `(` 之间加空格
+/// Control-flow keywords followed by a parenthesized header, separated from the `(` by a space
+const JS_CONTROL_KEYWORDS: &[&str] = &["if", "for", "while", "switch", "catch", "with"];
+
+/// 可以跟在 `}` 之后、与前一个块相连的关键字
+/// Keywords that may follow a `}` and continue the preceding block
+const JS_CONTINUATION_KEYWORDS: &[&str] = &["else", "catch", "finally"];
+
+/// 可以跟在表达式的 `}` 之后、延续同一个语句的关键字（`} from "m"`、`} as const`）
+/// Keywords that may follow the `}` of an expression and continue the same statement (`} from "m"`, `} as const`)
+const JS_EXPRESSION_CONTINUATION_KEYWORDS: &[&str] = &["from", "as", "satisfies", "in", "of", "instanceof"];
+
+/// 是否为标识符字符
+/// Whether a character can be part of an identifier
+pub(crate) fn is_js_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 词法单元的种类，与 [`JsToken`] 一一对应但不带原文
+/// Kind of a lexical token, matching [`JsToken`] one to one but without the source text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JsKind {
+    Whitespace { newline: bool },
+    LineComment,
+    BlockComment,
+    Str,
+    Template,
+    Regex,
+    Word,
+    Punct,
+}
+
+impl JsKind {
+    /// 带上原文成为词法单元
+    /// Turn into a token with its source text
+    fn token(self, text: &str) -> JsToken {
+        let text = text.to_string();
+        match self {
+            JsKind::Whitespace { newline } => JsToken::Whitespace { newline },
+            JsKind::LineComment => JsToken::LineComment(text),
+            JsKind::BlockComment => JsToken::BlockComment(text),
+            JsKind::Str => JsToken::Str(text),
+            JsKind::Template => JsToken::Template(text),
+            JsKind::Regex => JsToken::Regex(text),
+            JsKind::Word => JsToken::Word(text),
+            JsKind::Punct => JsToken::Punct(text),
+        }
+    }
+}
+
+/// JS/TS 词法扫描器：按顺序给出每个词法单元的种类与字节范围，不复制原文；字符串、模板字符串、正则与注释整体作为一个词法单元
+/// JS/TS scanner: yields the kind and byte range of every token in order without copying any text; strings,
+/// templates, regexes and comments are one token each
+pub(crate) struct JsScanner<'a> {
+    content: &'a str,
+    position: usize,
+    /// 上一个有效词法单元（空白与注释除外），决定 `/` 是否开始正则表达式
+    /// The previous significant token (whitespace and comments aside), deciding whether a `/` starts a regex
+    previous: Option<(JsKind, std::ops::Range<usize>)>,
+}
+
+/// 从头扫描 JS/TS 源码
+/// Scan JS/TS source from the start
+pub(crate) fn scan_js(content: &str) -> JsScanner<'_> {
+    JsScanner { content, position: 0, previous: None }
+}
+
+impl Iterator for JsScanner<'_> {
+    type Item = (JsKind, std::ops::Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let content = self.content;
+        let bytes = content.as_bytes();
+        let char_at = |i: usize| content[i..].chars().next();
+        // 跳过 `i` 处的一个字符（可能是多字节字符）
+        // Step over the one character at `i` (possibly a multi-byte one)
+        let next_char = |i: usize| i + char_at(i).map_or(1, char::len_utf8);
+        let start = self.position;
+        let c = char_at(start)?;
+        let mut i = start;
+        let kind = if c.is_whitespace() {
+            let mut newline = false;
+            while let Some(c) = char_at(i).filter(|c| c.is_whitespace()) {
+                newline |= c == '\n';
+                i += c.len_utf8();
+            }
+            JsKind::Whitespace { newline }
+        } else if content[i..].starts_with("//") || (i == 0 && content.starts_with("#!")) {
+            // 文件开头的 `#!` 行与 `//` 注释一样原样保留到行尾
+            // A `#!` line at the start of the file is kept verbatim up to the line end like a `//` comment
+            i = content[i..].find('\n').map_or(content.len(), |end| i + end);
+            JsKind::LineComment
+        } else if content[i..].starts_with("/*") {
+            i = content[i + 2..].find("*/").map_or(content.len(), |end| i + 2 + end + 2);
+            JsKind::BlockComment
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != c as u8 && bytes[i] != b'\n' {
+                i = if bytes[i] == b'\\' && i + 1 < bytes.len() { next_char(i + 1) } else { next_char(i) };
+            }
+            i = (i + 1).min(bytes.len());
+            JsKind::Str
+        } else if c == '`' {
+            // 模板字符串：跟踪 ${ } 嵌套，表达式内的反引号不结束模板
+            // Template literal: track ${ } nesting, backticks inside expressions don't end the template
+            i += 1;
+            let mut depth = 0usize;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' if i + 1 < bytes.len() => i = next_char(i + 1),
+                    b'`' if depth == 0 => break,
+                    b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                        depth += 1;
+                        i += 2;
+                    }
+                    b'}' if depth > 0 => {
+                        depth -= 1;
+                        i += 1;
+                    }
+                    _ => i = next_char(i),
+                }
+            }
+            i = (i + 1).min(bytes.len());
+            JsKind::Template
+        } else if c == '/' && js_regex_allowed(self.previous.clone().map(|(kind, range)| (kind, &content[range]))) {
+            i += 1;
+            let mut in_class = false;
+            while i < bytes.len() && bytes[i] != b'\n' {
+                match bytes[i] {
+                    b'\\' if i + 1 < bytes.len() => {
+                        i = next_char(i + 1);
+                        continue;
+                    }
+                    b'[' => in_class = true,
+                    b']' => in_class = false,
+                    b'/' if !in_class => break,
+                    _ => {}
+                }
+                i = next_char(i);
+            }
+            i = (i + 1).min(bytes.len());
+            while i < bytes.len() && bytes[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            JsKind::Regex
+        } else if is_js_word_char(c) || (c == '.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) {
+            // 标识符或数字（数字可含小数点与指数符号）
+            // Identifier or number (numbers may contain a decimal point and exponent sign)
+            let is_number = c.is_ascii_digit() || c == '.';
+            i += c.len_utf8();
+            while let Some(n) = char_at(i) {
+                let exponent_sign = is_number
+                    && (n == '+' || n == '-')
+                    && matches!(bytes[i - 1], b'e' | b'E')
+                    && !content[start..i].bytes().any(|b| b.is_ascii_alphabetic() && !matches!(b, b'e' | b'E'));
+                if is_js_word_char(n) || (is_number && n == '.') || exponent_sign {
+                    i += n.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            JsKind::Word
+        } else {
+            // 多字符运算符的第二个字符都在这些字符之中，其他情况直接是单字符
+            // The second character of every multi-character operator is one of these, anything else is a single character
+            let multi = bytes.get(i + 1).is_some_and(|n| b"=.&|?+-*<>".contains(n));
+            i += JS_OPERATORS
+                .iter()
+                .filter(|_| multi)
+                .find(|op| content[i..].starts_with(**op))
+                .map_or(c.len_utf8(), |op| op.len());
+            JsKind::Punct
+        };
+        if !matches!(kind, JsKind::Whitespace { .. } | JsKind::LineComment | JsKind::BlockComment) {
+            self.previous = Some((kind, start..i));
+        }
+        self.position = i;
+        Some((kind, start..i))
+    }
+}
+
+/// 将 JS/TS 源码拆分为词法单元，同时返回每个词法单元的起始字节偏移；字符串、模板字符串、正则与注释整体保留
+/// Split JS/TS source into tokens along with the starting byte offset of each; strings, templates, regexes and comments stay whole
+pub(crate) fn tokenize_js_with_offsets(content: &str) -> (Vec<JsToken>, Vec<usize>) {
+    scan_js(content).map(|(kind, range)| (kind.token(&content[range.clone()]), range.start)).unzip()
+}
+
+/// 将 JS/TS 源码拆分为词法单元
+/// Split JS/TS source into tokens
+pub(crate) fn tokenize_js(content: &str) -> Vec<JsToken> {
+    tokenize_js_with_offsets(content).0
+}
+
+/// 根据前一个有效词法单元（种类与原文）判断 `/` 是否开始正则表达式
+/// Decide from the previous significant token (its kind and text) whether a `/` starts a regular expression
+fn js_regex_allowed(previous: Option<(JsKind, &str)>) -> bool {
+    match previous {
+        None => true,
+        Some((JsKind::Punct, p)) => !matches!(p, ")" | "]" | "}" | "++" | "--"),
+        Some((JsKind::Word, w)) => JS_REGEX_KEYWORDS.contains(&w),
+        _ => false,
+    }
+}
+
+/// 词法单元的原文（空白返回空串）
+/// Source text of a token (empty for whitespace)
+pub(crate) fn js_token_text(token: &JsToken) -> &str {
+    match token {
+        JsToken::Whitespace { .. } => "",
+        JsToken::LineComment(text)
+        | JsToken::BlockComment(text)
+        | JsToken::Str(text)
+        | JsToken::Template(text)
+        | JsToken::Regex(text)
+        | JsToken::Word(text)
+        | JsToken::Punct(text) => text,
+    }
+}
+
+/// 是否为一元前缀运算符（`+`、`-`、`++`、`--`、`!` 由前面的词法单元决定，`before` 为 `previous` 之前的有效词法单元）
+/// 生成器方法的 `*`（`*gen() {}`、`static *gen() {}`、`async *gen() {}`）同样紧贴其后的名字
+/// Whether the token is a prefix unary operator (`+`, `-`, `++`, `--` and `!` depend on the tokens before it, `before`
+/// being the significant token preceding `previous`)
+/// The `*` of a generator method (`*gen() {}`, `static *gen() {}`, `async *gen() {}`) sticks to the following name as well
+fn js_prefix_unary(before: Option<&JsToken>, previous: Option<&JsToken>, token: &JsToken) -> bool {
+    let JsToken::Punct(op) = token else {
+        return false;
+    };
+    match op.as_str() {
+        "!" => !js_non_null_assertion(previous),
+        "~" | "..." => true,
+        "*" => match previous {
+            None => true,
+            Some(JsToken::Punct(p)) => p == ",",
+            Some(JsToken::Word(w)) => w == "async" || w == "static",
+            Some(_) => false,
+        },
+        "+" | "-" | "++" | "--" => match previous {
+            None => true,
+            Some(JsToken::Punct(p)) if p == "!" => !js_non_null_assertion(before),
+            Some(JsToken::Punct(p)) => !matches!(p.as_str(), ")" | "]" | "}" | "++" | "--"),
+            Some(JsToken::Word(w)) => JS_REGEX_KEYWORDS.contains(&w.as_str()),
+            Some(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// `previous` 之后的 `!` 是否为 TypeScript 的非空断言（`x!`、`f()!`、`a[0]!`）：它跟在操作数之后，而不是开始一个操作数
+/// Whether a `!` after `previous` is a TypeScript non-null assertion (`x!`, `f()!`, `a[0]!`): it follows an operand
+/// instead of starting one
+fn js_non_null_assertion(previous: Option<&JsToken>) -> bool {
+    match previous {
+        Some(JsToken::Punct(p)) => matches!(p.as_str(), ")" | "]"),
+        Some(JsToken::Word(w)) => !JS_REGEX_KEYWORDS.contains(&w.as_str()),
+        _ => false,
+    }
+}
+
+/// 词法单元是否为关键字（不是 `.` 之后的属性名，如 `promise.catch`）
+/// Whether the token is the given kind of keyword (not a property name after `.`, as in `promise.catch`)
+fn js_is_keyword(before: Option<&JsToken>, token: &JsToken, keywords: &[&str]) -> bool {
+    matches!(token, JsToken::Word(w) if keywords.contains(&w.as_str()))
+        && !matches!(before, Some(JsToken::Punct(p)) if p == "." || p == "?.")
+}
+
+/// 两个相邻有效词法单元之间是否加空格；`had_space` 表示源码中二者之间是否有空白，`before` 为 `previous` 之前的有效词法单元
+/// 三元运算符的 `?` 与 `:` 由调用方处理；其余的 `:`（对象键、类型注解、标签）与 TypeScript 可选标记 `?` 前不加空格，`:` 后加空格
+/// Whether to put a space between two adjacent significant tokens; `had_space` tells whether the source had whitespace between
+/// them, `before` is the significant token preceding `previous`
+/// The `?` and `:` of conditional expressions are handled by the caller; any other `:` (object key, type annotation, label)
+/// and the TypeScript optional marker `?` get no space before, and `:` gets one after
+fn js_space_between(before: Option<&JsToken>, previous: &JsToken, previous_unary: bool, token: &JsToken, had_space: bool) -> bool {
+    if previous_unary {
+        return false;
+    }
+    let previous_punct = match previous {
+        JsToken::Punct(p) => Some(p.as_str()),
+        _ => None,
+    };
+    if matches!(previous_punct, Some("." | "?." | "(" | "[" | "@" | "#")) {
+        return false;
+    }
+    match token {
+        JsToken::Whitespace { .. } => false,
+        JsToken::LineComment(_) | JsToken::BlockComment(_) => true,
+        JsToken::Punct(op) => match op.as_str() {
+            "," | ";" | ")" | "]" | "." | "?." => false,
+            "}" => previous_punct != Some("{"),
+            "{" => true,
+            // `function*` 与 `yield*` 的 `*` 紧贴关键字
+            // The `*` of `function*` and `yield*` sticks to the keyword
+            "*" if js_is_keyword(before, previous, &["function", "yield"]) => false,
+            // `async () =>` 与 `async function` 一样，async 与其后内容之间留一个空格；解构声明 `let [a, b]` 同样留空格
+            // As with `async function`, `async () =>` keeps one space after async; so does a destructuring `let [a, b]`
+            "(" | "[" => match previous {
+                JsToken::Word(_) => {
+                    js_is_keyword(before, previous, JS_REGEX_KEYWORDS)
+                        || (op == "(" && js_is_keyword(before, previous, JS_CONTROL_KEYWORDS))
+                        || (op == "(" && js_is_keyword(before, previous, &["async"]))
+                        || (op == "[" && js_is_keyword(before, previous, &["let", "const", "var"]))
+                }
+                // 三元运算符的 `?` 由调用方处理，其余的 `?` 是可选方法的标记（`m?()`）
+                // Conditional `?` are handled by the caller, any other `?` marks an optional method (`m?()`)
+                JsToken::Punct(p) => !matches!(p.as_str(), ")" | "]" | "}" | "?"),
+                JsToken::LineComment(_) | JsToken::BlockComment(_) => true,
+                _ => false,
+            },
+            "?" | ":" => false,
+            "++" | "--" | "!" if !js_prefix_unary(before, Some(previous), token) => false,
+            _ => true,
+        },
+        JsToken::Str(_) | JsToken::Template(_) | JsToken::Regex(_) | JsToken::Word(_) => match previous {
+            JsToken::Word(_) if matches!(token, JsToken::Word(_)) => true,
+            JsToken::Punct(_) | JsToken::LineComment(_) | JsToken::BlockComment(_) => true,
+            _ => had_space,
+        },
+    }
+}
+
+/// 行内可以换行的位置的种类，按运算符优先级从低到高排列（优先在优先级低的位置换行）
+/// Kind of a position where a line may be broken, ordered from lowest to highest operator precedence (lower ones are broken first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum JsBreakKind {
+    /// 逗号之后
+    /// After a comma
+    Comma,
+    /// 三元运算符的 `?` 与 `:` 之前
+    /// Before the `?` and `:` of a conditional expression
+    Ternary,
+    /// `||` 与 `??` 之前
+    /// Before `||` and `??`
+    Or,
+    /// `&&` 之前
+    /// Before `&&`
+    And,
+}
+
+/// 行内可以换行的位置：字节偏移、所在的括号嵌套深度与种类
+/// A position where a line may be broken: byte offset, bracket nesting depth and kind
+#[derive(Debug, Clone, Copy)]
+struct JsBreak {
+    position: usize,
+    depth: usize,
+    kind: JsBreakKind,
+}
+
+/// 找出一行词法单元中属于三元运算符的 `?` 与 `:`：`?` 与其后同一括号深度的第一个未配对的 `:` 配对
+/// 分支可以是跨行的对象字面量或函数（`a ? {` …… `} : b`），因此未配对的 `?` 记在 `pending` 中（绝对括号深度）留给后面的行；
+/// `end_depth` 为这一行之后的绝对括号深度
+/// TypeScript 的可选标记（`a?: T`、`(a?)`、`m?(): T`）不是三元运算符
+/// Find the `?` and `:` of conditional expressions in a line of tokens: a `?` pairs with the first unpaired `:` after it at
+/// the same bracket depth
+/// A branch may be an object literal or function spanning lines (`a ? {` ... `} : b`), so unpaired `?` are kept in `pending`
+/// (by absolute bracket depth) for the lines that follow; `end_depth` is the absolute bracket depth after this line
+/// TypeScript optional markers (`a?: T`, `(a?)`, `m?(): T`) are not conditional operators
+fn js_ternary_operators(tokens: &[JsToken], end_depth: usize, pending: &mut Vec<usize>) -> Vec<bool> {
+    let mut ternary = vec![false; tokens.len()];
+    let net: isize = tokens
+        .iter()
+        .map(|token| match token {
+            JsToken::Punct(p) if matches!(p.as_str(), "(" | "[" | "{") => 1,
+            JsToken::Punct(p) if matches!(p.as_str(), ")" | "]" | "}") => -1,
+            _ => 0,
+        })
+        .sum();
+    let mut depth = (end_depth as isize - net).max(0) as usize;
+    for (index, token) in tokens.iter().enumerate() {
+        let JsToken::Punct(op) = token else {
+            continue;
+        };
+        match op.as_str() {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => {
+                depth = depth.saturating_sub(1);
+                pending.retain(|&d| d <= depth);
+            }
+            ";" => pending.retain(|&d| d < depth),
+            "?" => {
+                let next = tokens[index + 1..].iter().find(|t| !matches!(t, JsToken::Whitespace { .. }));
+                // `m?()` 紧贴名字与括号的 `?` 是可选方法
+                // A `?` right between a name and a parenthesis (`m?()`) marks an optional method
+                let optional_method = index > 0
+                    && matches!(tokens[index - 1], JsToken::Word(_))
+                    && matches!(tokens.get(index + 1), Some(JsToken::Punct(p)) if p == "(");
+                if !optional_method && !matches!(next, Some(JsToken::Punct(p)) if matches!(p.as_str(), ":" | "," | ")" | "=")) {
+                    ternary[index] = true;
+                    pending.push(depth);
+                }
+            }
+            ":" => {
+                if let Some(position) = pending.iter().rposition(|&d| d == depth) {
+                    pending.remove(position);
+                    ternary[index] = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    ternary
+}
+
+/// 类型参数的内容中可以出现的符号
+/// Punctuation that may appear inside type arguments
+const TS_TYPE_ARGUMENT_PUNCTS: &[&str] = &[",", ".", "[", "]", "|", "&", "?", ":", "=>", "=", "(", ")"];
+
+/// 类型参数之后可以紧接的符号（如 `Map<K, V>(`、`Array<T>[]`、`Promise<T> {`）
+/// Punctuation that may directly follow type arguments (as in `Map<K, V>(`, `Array<T>[]`, `Promise<T> {`)
+const TS_AFTER_TYPE_ARGUMENT_PUNCTS: &[&str] = &["(", ")", ",", ";", "=", "{", "[", "]", ".", "|", "&", "=>", "?", ":", "}"];
+
+/// 找出一行词法单元中 TypeScript 类型参数的尖括号（`Map<string, T>`、`function f<T extends U>(`、`<T,>(x: T) =>`）：
+/// 开头的 `<` 紧跟在名字之后（或在 `=`、`(`、`,` 之后且整体后接 `(`），其间只有类型中会出现的词法单元，
+/// 闭合的 `>`（`>>`、`>>>` 一次闭合多层）之后是类型参数之后才会出现的内容；比较运算不满足这些条件
+/// Find the angle brackets of TypeScript type arguments in a line of tokens (`Map<string, T>`, `function f<T extends U>(`,
+/// `<T,>(x: T) =>`): the opening `<` follows a name (or `=`, `(`, `,` when the whole is followed by `(`), only tokens that
+/// occur in types sit between them, and the closing `>` (`>>` and `>>>` close several levels at once) is followed by
+/// something that only follows type arguments; comparisons don't meet these conditions
+fn ts_type_brackets(tokens: &[JsToken]) -> Vec<bool> {
+    let mut marks = vec![false; tokens.len()];
+    let significant: Vec<usize> = (0..tokens.len()).filter(|&i| !matches!(tokens[i], JsToken::Whitespace { .. })).collect();
+    let punct = |k: usize| match significant.get(k).map(|&i| &tokens[i]) {
+        Some(JsToken::Punct(p)) => Some(p.as_str()),
+        _ => None,
+    };
+    let mut k = 0;
+    while k < significant.len() {
+        if punct(k) != Some("<") || marks[significant[k]] {
+            k += 1;
+            continue;
+        }
+        // 找到闭合的 `>`，记下其间所有的尖括号
+        // Find the closing `>`, noting every angle bracket in between
+        let (mut angle, mut parens) = (1usize, 0usize);
+        let mut brackets = vec![significant[k]];
+        let mut end = None;
+        for m in k + 1..significant.len() {
+            let token = &tokens[significant[m]];
+            match token {
+                JsToken::Word(_) | JsToken::Str(_) => continue,
+                JsToken::Punct(p) if p == "<" => angle += 1,
+                JsToken::Punct(p) if !p.is_empty() && p.chars().all(|c| c == '>') => {
+                    if p.len() > angle {
+                        break;
+                    }
+                    angle -= p.len();
+                }
+                JsToken::Punct(p) if p == "(" => parens += 1,
+                JsToken::Punct(p) if p == ")" && parens > 0 => parens -= 1,
+                JsToken::Punct(p) if p != ")" && TS_TYPE_ARGUMENT_PUNCTS.contains(&p.as_str()) => continue,
+                _ => break,
+            }
+            brackets.push(significant[m]);
+            if angle == 0 {
+                end = Some(m);
+                break;
+            }
+        }
+        let Some(end) = end.filter(|_| parens == 0) else {
+            k += 1;
+            continue;
+        };
+        let before = k.checked_sub(1).map(|b| &tokens[significant[b]]);
+        let after = significant.get(end + 1).map(|&i| &tokens[i]);
+        let follows_name = matches!(before, Some(JsToken::Word(w)) if !JS_REGEX_KEYWORDS.contains(&w.as_str()));
+        let arrow_parameters = matches!(before, None | Some(JsToken::Punct(_))) && punct(end + 1) == Some("(")
+            && before.is_none_or(|b| matches!(b, JsToken::Punct(p) if matches!(p.as_str(), "=" | "(" | ",")));
+        let followed = match after {
+            None => true,
+            Some(JsToken::Punct(p)) => TS_AFTER_TYPE_ARGUMENT_PUNCTS.contains(&p.as_str()),
+            Some(JsToken::Word(w)) => matches!(w.as_str(), "extends" | "implements" | "as" | "satisfies"),
+            Some(_) => false,
+        };
+        if (follows_name && followed) || arrow_parameters {
+            for index in brackets {
+                if matches!(&tokens[index], JsToken::Punct(p) if p.starts_with(['<', '>'])) {
+                    marks[index] = true;
+                }
+            }
+            k = end + 1;
+        } else {
+            k += 1;
+        }
+    }
+    marks
+}
+
+/// 按运算符规则拼接一行内的词法单元：二元运算符（含三元运算符）两侧加空格，逗号后加空格，一元运算符、成员访问与括号内侧不加空格，
+/// TypeScript 类型参数的尖括号两侧不加空格
+/// 字符串、模板字符串、正则与注释原样输出，内容绝不改动
+/// 同时返回可以换行的位置（逗号之后，三元与逻辑运算符之前；类型参数内部不换行）；
+/// `end_depth` 与 `pending` 见 [`js_ternary_operators`]
+/// Join the tokens of one line by the operator rules: spaces around binary operators (conditional ones included) and after
+/// commas, none after unary operators, around member access, inside brackets or around the angle brackets of TypeScript
+/// type arguments
+/// Strings, templates, regexes and comments are emitted verbatim, their contents are never modified
+/// Also returns the positions where the line may be broken (after commas, before conditional and logical operators; never
+/// inside type arguments); see [`js_ternary_operators`] for `end_depth` and `pending`
+fn add_operator_spaces(tokens: &[JsToken], end_depth: usize, pending: &mut Vec<usize>) -> (String, Vec<JsBreak>) {
+    let ternary = js_ternary_operators(tokens, end_depth, pending);
+    let type_brackets = ts_type_brackets(tokens);
+    // 类型参数的 `>` 之后按名字之后处理（`Map<K, V>(`、`Promise<T> {`）
+    // After the `>` of type arguments spacing is as after a name (`Map<K, V>(`, `Promise<T> {`)
+    let name = JsToken::Word(String::new());
+    let mut text = String::new();
+    let mut breaks = Vec::new();
+    let mut before: Option<&JsToken> = None;
+    let mut previous: Option<&JsToken> = None;
+    let mut previous_unary = false;
+    let mut previous_ternary = false;
+    let mut previous_bracket: Option<bool> = None;
+    let mut had_space = false;
+    let mut depth = 0usize;
+    let mut angle = 0usize;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if let JsToken::Whitespace { .. } = token {
+            had_space = true;
+            continue;
+        }
+        let op = match token {
+            JsToken::Punct(p) => p.as_str(),
+            _ => "",
+        };
+        let kind = match op {
+            "?" | ":" if ternary[index] => Some(JsBreakKind::Ternary),
+            "||" | "??" => Some(JsBreakKind::Or),
+            "&&" => Some(JsBreakKind::And),
+            _ => None,
+        };
+        if let Some(kind) = kind.filter(|_| previous.is_some() && angle == 0) {
+            breaks.push(JsBreak { position: text.len(), depth, kind });
+        }
+        let space = match (type_brackets[index], previous_bracket) {
+            // `<` 紧贴前面的名字，`>` 紧贴前面的内容，`<` 之后不加空格
+            // `<` sticks to the name before it, `>` to whatever precedes it, and nothing is added after `<`
+            (true, _) if op.starts_with('>') => false,
+            (_, Some(true)) => false,
+            (true, _) => !matches!(previous, Some(JsToken::Word(_))) && previous.is_some_and(|p| js_space_between(before, p, previous_unary, token, had_space)),
+            (false, Some(false)) => ternary[index] || js_space_between(before, &name, false, token, had_space),
+            (false, None) => {
+                ternary[index] || previous_ternary || previous.is_some_and(|p| js_space_between(before, p, previous_unary, token, had_space))
+            }
+        };
+        if previous.is_some() && space {
+            text.push(' ');
+        }
+        text.push_str(js_token_text(token));
+        match op {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => depth = depth.saturating_sub(1),
+            "," if angle == 0 => breaks.push(JsBreak { position: text.len(), depth, kind: JsBreakKind::Comma }),
+            _ if type_brackets[index] && op.starts_with('<') => angle += 1,
+            _ if type_brackets[index] => angle = angle.saturating_sub(op.len()),
+            _ => {}
+        }
+        previous_unary = js_prefix_unary(before, previous, token);
+        previous_ternary = ternary[index];
+        // Some(true)：类型参数的 `<`；Some(false)：类型参数的 `>`
+        // Some(true): the `<` of type arguments; Some(false): their `>`
+        previous_bracket = type_brackets[index].then(|| op.starts_with('<'));
+        before = previous;
+        previous = Some(token);
+        had_space = false;
+    }
+    (text, breaks)
+}
+
+/// 把超过行宽的一行拆成多段：在嵌套最浅、优先级最低的一类位置换行，逗号尽量填满每行，三元与逻辑运算符则每个都换行；
+/// 拆分后仍过长的段继续在更深的位置拆分
+/// `first_width` 为第一段前缩进的宽度，`continuation_width` 为续行缩进的宽度
+/// Split a line that exceeds the line length: break at the shallowest, lowest-precedence kind of position, filling lines
+/// greedily at commas and breaking before every conditional or logical operator; segments that are still too long are split
+/// further at deeper positions
+/// `first_width` is the width of the first segment's indentation, `continuation_width` that of continuation lines
+fn split_js_line(text: &str, breaks: &[JsBreak], first_width: usize, continuation_width: usize, max_width: usize) -> Vec<String> {
+    let Some(depth) = breaks.iter().map(|b| b.depth).min() else {
+        return vec![text.trim().to_string()];
+    };
+    if first_width + display_width(text.trim()) <= max_width {
+        return vec![text.trim().to_string()];
+    }
+    let kind = breaks.iter().filter(|b| b.depth == depth).map(|b| b.kind).min().unwrap_or(JsBreakKind::Comma);
+    let candidates: Vec<usize> = breaks.iter().filter(|b| b.depth == depth && b.kind == kind).map(|b| b.position).collect();
+
+    let mut cuts = Vec::new();
+    if kind == JsBreakKind::Comma {
+        let mut start = 0;
+        let mut width = first_width;
+        let mut last = None;
+        for &position in candidates.iter().chain(std::iter::once(&text.len())) {
+            if width + display_width(text[start..position].trim()) > max_width {
+                if let Some(cut) = last.filter(|&cut| cut > start) {
+                    cuts.push(cut);
+                    start = cut;
+                    width = continuation_width;
+                }
+            }
+            last = Some(position);
+        }
+    } else {
+        cuts = candidates;
+    }
+    cuts.retain(|&cut| cut > 0 && cut < text.len());
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for end in cuts.into_iter().chain(std::iter::once(text.len())) {
+        let inner: Vec<JsBreak> = breaks
+            .iter()
+            .filter(|b| b.position > start && b.position < end)
+            .map(|b| JsBreak { position: b.position - start, ..*b })
+            .collect();
+        let width = if start == 0 { first_width } else { continuation_width };
+        lines.extend(split_js_line(&text[start..end], &inner, width, continuation_width, max_width));
+        start = end;
+    }
+    lines
+}
+
+/// JS/TS 格式化状态：逐行收集词法单元，遇到语句或块边界时按当前缩进输出
+/// JS/TS formatting state: collects the tokens of a line and emits it at the current indentation on statement or block boundaries
+struct JsFormatter<'a> {
+    options: &'a FormatOptions,
+    indent_unit: String,
+    /// 已完成的输出行（每行以换行结尾）
+    /// Finished output lines (each ending with a newline)
+    output: String,
+    indent_level: usize,
+    line: Vec<JsToken>,
+    /// 尚未闭合的括号
+    /// Brackets that are still open
+    brackets: Vec<char>,
+    /// 尚未闭合的块：开头的关键字（如 `do`、`switch`，没有关键字时为空串）与块内增加的缩进层数
+    /// Blocks that are still open: their leading keyword (such as `do` or `switch`, empty when there is none) and the
+    /// number of indentation levels they add
+    blocks: Vec<(String, usize)>,
+    /// 当前行使用的缩进层级（未指定时为 `indent_level`）
+    /// Indentation level of the current line (`indent_level` when unset)
+    line_level: Option<usize>,
+    /// 当前行是 `case`/`default` 标签时，标签内尚未匹配 `:` 的 `?` 个数
+    /// When the current line is a `case`/`default` label, the number of `?` in it still waiting for their `:`
+    case_label: Option<usize>,
+    /// 已输出的行中尚未配对 `:` 的三元运算符 `?`（绝对括号深度）
+    /// Conditional `?` on lines already emitted that still wait for their `:` (absolute bracket depth)
+    ternaries: Vec<usize>,
+    /// 尚未结束的无花括号语句体（`if (a)` 换行之后的单条语句）：所在块的层数与头部关键字，每个多缩进一级
+    /// Brace-less bodies still open (the single statement after `if (a)` and a line break): the number of enclosing
+    /// blocks and the header keyword, each indented one more level
+    bodies: Vec<(usize, &'static str)>,
+    /// 刚结束的是 `do` 的语句体，下一行的 `while (…)` 结束 do-while 而不是开始新的语句体
+    /// The body of a `do` just ended, so the `while (...)` on the next line ends the do-while instead of opening a body
+    do_while: bool,
+}
+
+impl<'a> JsFormatter<'a> {
+    fn new(options: &'a FormatOptions, capacity: usize) -> Self {
+        Self {
+            options,
+            indent_unit: options.indent_unit(),
+            output: String::with_capacity(capacity),
+            indent_level: 0,
+            line: Vec::new(),
+            brackets: Vec::new(),
+            blocks: Vec::new(),
+            line_level: None,
+            case_label: None,
+            ternaries: Vec::new(),
+            bodies: Vec::new(),
+            do_while: false,
+        }
+    }
+
+    /// 把缩进、内容与换行直接写入输出
+    /// Write the indentation, the content and a newline straight into the output
+    fn write_line(&mut self, level: usize, text: &str) {
+        if !text.is_empty() {
+            for _ in 0..level {
+                self.output.push_str(&self.indent_unit);
+            }
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    /// 当前行是否还没有有效内容
+    /// Whether the current line has no content yet
+    fn line_is_empty(&self) -> bool {
+        self.line.iter().all(|t| matches!(t, JsToken::Whitespace { .. }))
+    }
+
+    /// 是否处于语句层（不在圆括号或方括号内）
+    /// Whether we are at statement level (not inside parentheses or square brackets)
+    fn at_statement_level(&self) -> bool {
+        matches!(self.brackets.last(), None | Some('{'))
+    }
+
+    /// 输出当前行；超过行宽时在逗号后或三元、逻辑运算符前换行，续行多缩进一级
+    /// Emit the current line; past the line length it is broken after commas or before conditional and logical operators,
+    /// continuation lines indented one more level
+    fn flush_line(&mut self) {
+        let level = self.line_level.take().unwrap_or(self.indent_level);
+        self.case_label = None;
+        if self.line_is_empty() {
+            self.line.clear();
+            return;
+        }
+        let (text, breaks) = add_operator_spaces(&self.line, self.brackets.len(), &mut self.ternaries);
+        self.line.clear();
+        let width = self.options.indent_width();
+        let segments = split_js_line(&text, &breaks, width * level, width * (level + 1), self.options.line_length);
+        for (index, segment) in segments.iter().enumerate() {
+            self.write_line(if index == 0 { level } else { level + 1 }, segment);
+        }
+    }
+
+    /// 在语句层结束当前行，`next` 为其后的有效词法单元：以无花括号语句体的头部（`if (a)`、`else`、`for (…)`……）结束的行
+    /// 之后语句体多缩进一级，后面是 `{` 时由块负责缩进；其余的行结束语句，同时结束它所在的语句体
+    /// End the current line at statement level, `next` being the significant token after it: after a line ending in the
+    /// header of a brace-less body (`if (a)`, `else`, `for (...)`...) the body is indented one more level, unless a `{`
+    /// follows and the block takes care of it; any other line ends a statement and the bodies it completes
+    fn end_line(&mut self, next: Option<&JsToken>) {
+        let header = js_body_header(&self.line).filter(|_| self.at_statement_level());
+        let do_statement = self.line_head() == "do";
+        let do_while = std::mem::take(&mut self.do_while);
+        self.flush_line();
+        match header {
+            Some(_) if matches!(next, Some(JsToken::Punct(p)) if p == "{") => {}
+            Some(kind) if !(do_while && kind == "while") => {
+                self.bodies.push((self.blocks.len(), kind));
+                self.indent_level += 1;
+            }
+            _ => {
+                self.end_bodies(next);
+                self.do_while |= do_statement && matches!(next, Some(JsToken::Word(w)) if w == "while");
+            }
+        }
+    }
+
+    /// 语句已结束，结束当前块内的无花括号语句体；其后的 `else` 属于刚结束的 `if`、`while` 属于刚结束的 `do`，在那里停下
+    /// A statement has ended, end the brace-less bodies it completes in the current block; an `else` after it belongs to
+    /// the `if` just ended and a `while` to the `do` just ended, so stop there
+    fn end_bodies(&mut self, next: Option<&JsToken>) {
+        let follows = |keyword: &str| matches!(next, Some(JsToken::Word(w)) if w == keyword);
+        while let Some(&(_, kind)) = self.bodies.last().filter(|&&(depth, _)| depth == self.blocks.len()) {
+            self.bodies.pop();
+            self.indent_level = self.indent_level.saturating_sub(1);
+            if kind == "if" && follows("else") {
+                break;
+            }
+            if kind == "do" && follows("while") {
+                self.do_while = true;
+                break;
+            }
+        }
+    }
+
+    /// 单独成行的块注释：逐行重新缩进，以 `*` 开头的行对齐到开头的 `/*`；开启 --wrap-comments 时超长注释重新折行
+    /// A block comment on its own lines: re-indented line by line, lines starting with `*` aligned under the opening `/*`;
+    /// with --wrap-comments an overlong comment is reflowed
+    fn push_block_comment(&mut self, comment: &str) {
+        self.flush_line();
+        let level = self.indent_level;
+        let columns = self.options.indent_width() * level;
+        if let Some(lines) = reflow_block_comment(comment, columns, self.options.line_length).filter(|_| self.options.wrap_comments) {
+            for line in lines {
+                self.write_line(level, &line);
+            }
+            return;
+        }
+        for (index, line) in comment.lines().enumerate() {
+            let line = line.trim();
+            if index > 0 && line.starts_with('*') {
+                self.write_line(level, &format!(" {}", line));
+            } else {
+                self.write_line(level, line);
+            }
+        }
+    }
+
+    /// 单独成行的一段 `//` 注释：超出行宽时整段重新折行，否则逐行原样输出
+    /// A run of own-line `//` comments: reflowed as a whole when it exceeds the line length, otherwise written line by line
+    fn push_line_comments(&mut self, comments: &[&str]) {
+        let level = self.indent_level;
+        let columns = self.options.indent_width() * level;
+        let lines = reflow_line_comments(comments, columns, self.options.line_length)
+            .unwrap_or_else(|| comments.iter().map(|comment| comment.trim_end().to_string()).collect());
+        for line in lines {
+            self.write_line(level, &line);
+        }
+    }
+
+    fn open_block(&mut self) {
+        if log_enabled(LogLevel::Trace) {
+            let (text, _) = add_operator_spaces(&self.line, self.brackets.len(), &mut self.ternaries.clone());
+            log(LogLevel::Trace, tr!("js: 块开始 {:?}（缩进层级 {} → {}）", "js: block start {:?} (indent level {} → {})", text, self.indent_level, self.indent_level + 1));
+        }
+        let head = self.line_head();
+        // switch 的标签比块内语句少缩进一级，--indent-case-labels 时标签本身再缩进一级；
+        // `case 1: {` 的块与标签下的语句同级，不再增加缩进
+        // Labels of a switch sit one level left of its statements, and are themselves indented one level with
+        // --indent-case-labels; the block of `case 1: {` is at the level of the label's statements and adds none
+        let step = match head.as_str() {
+            "switch" if self.options.indent_case_labels => 2,
+            "case" | "default" if self.case_label.is_some() => 0,
+            _ => 1,
+        };
+        self.blocks.push((head, step));
+        self.line.push(JsToken::Punct("{".to_string()));
+        self.brackets.push('{');
+        self.flush_line();
+        self.indent_level += step;
+    }
+
+    /// 当前行开头的关键字，作为其后块的类型：含 `class` 关键字的行（如 `export class X extends Y`）为 `class`
+    /// The leading keyword of the current line, used as the kind of the block it opens: a line containing the `class`
+    /// keyword (such as `export class X extends Y`) gives `class`
+    fn line_head(&self) -> String {
+        let significant: Vec<&JsToken> = self.line.iter().filter(|t| !matches!(t, JsToken::Whitespace { .. })).collect();
+        let is_class = significant
+            .iter()
+            .enumerate()
+            .any(|(index, token)| js_is_keyword(index.checked_sub(1).map(|i| significant[i]), token, &["class"]));
+        if is_class {
+            return "class".to_string();
+        }
+        let head = significant.iter().find_map(|t| match t {
+            JsToken::Word(w) => Some(w.clone()),
+            JsToken::Punct(_) => None,
+            _ => Some(String::new()),
+        });
+        head.unwrap_or_default()
+    }
+
+    /// 当前块是否为类的块体
+    /// Whether the current block is the body of a class
+    fn in_class_body(&self) -> bool {
+        self.brackets.last() == Some(&'{') && self.blocks.last().is_some_and(|(head, _)| head == "class")
+    }
+
+    /// 块的 `}` 已放入当前行，决定其后内容的去向：`})`、`},`、`};`、`}.` 等留在同一行；`} else`、`} catch`、`} finally`
+    /// 按 --else-placement 放置，`do { } while` 始终同行，此时返回续接的关键字的下标；其余情况结束当前行，类的成员之间空一行
+    /// The `}` of a block is on the current line, decide where what follows goes: `})`, `},`, `};`, `}.` and the like stay on
+    /// the same line; `} else`, `} catch` and `} finally` follow --else-placement and `do { } while` always stays on one line,
+    /// returning the index of the continuing keyword; otherwise the line ends, with a blank line between class members
+    fn after_block(&mut self, tokens: &[JsToken], close: usize, head: &str) -> Option<usize> {
+        let continuation = (close + 1..tokens.len())
+            .find(|&n| !matches!(tokens[n], JsToken::Whitespace { .. }))
+            .filter(|&n| match &tokens[n] {
+                JsToken::Word(w) if w == "while" => head == "do",
+                JsToken::Word(w) => JS_CONTINUATION_KEYWORDS.contains(&w.as_str()) && self.options.else_placement == ElsePlacement::SameLine,
+                _ => false,
+            })
+            .filter(|_| js_statement_block(tokens, close));
+        if continuation.is_some() {
+            self.line.push(JsToken::Whitespace { newline: false });
+            return continuation;
+        }
+        let next = js_next_significant(tokens, close + 1);
+        let joins = next.is_some_and(|n| match &tokens[n] {
+            JsToken::Punct(q) if matches!(q.as_str(), ")" | "]" | "," | ";" | "." | "?." | ":") => true,
+            // 对象字面量、解构模式与 import/export 列表之后的运算符和关键字延续同一个语句：`} = y`、`} from "m"`
+            // An operator or keyword after an object literal, destructuring pattern or import/export list continues the
+            // same statement: `} = y`, `} from "m"`
+            JsToken::Punct(q) => !matches!(q.as_str(), "{" | "(" | "[" | "}" | "!" | "~" | "..." | "++" | "--" | "@" | "#") && !js_statement_block(tokens, close),
+            JsToken::Word(w) => JS_EXPRESSION_CONTINUATION_KEYWORDS.contains(&w.as_str()) && !js_statement_block(tokens, close),
+            _ => false,
+        });
+        if !joins {
+            self.end_line(next.map(|n| &tokens[n]));
+            let last_member = next.is_none_or(|n| matches!(&tokens[n], JsToken::Punct(q) if q == "}"));
+            if self.in_class_body() && !last_member {
+                self.write_line(0, "");
+            }
+        }
+        None
+    }
+
+    /// 当前块是否为 switch 的块体
+    /// Whether the current block is the body of a switch
+    fn in_switch_body(&self) -> bool {
+        self.brackets.last() == Some(&'{') && self.blocks.last().is_some_and(|(head, _)| head == "switch")
+    }
+
+    /// 开始一个 `case`/`default` 标签行，标签比语句少缩进一级
+    /// Start a `case`/`default` label line, one level left of the statements
+    fn start_case_label(&mut self) {
+        self.flush_line();
+        self.line_level = Some(self.indent_level.saturating_sub(1));
+        self.case_label = Some(0);
+    }
+
+    /// 关闭当前块，返回块开头的关键字
+    /// Close the current block, returning its leading keyword
+    fn close_block(&mut self) -> String {
+        self.flush_line();
+        if log_enabled(LogLevel::Trace) {
+            log(LogLevel::Trace, tr!("js: 块结束（缩进层级 {} → {}）", "js: block end (indent level {} → {})", self.indent_level, self.indent_level.saturating_sub(1)));
+        }
+        // 块内未结束的语句体随块一起结束
+        // Bodies still open inside the block end with it
+        while self.bodies.last().is_some_and(|&(depth, _)| depth >= self.blocks.len()) {
+            self.bodies.pop();
+            self.indent_level = self.indent_level.saturating_sub(1);
+        }
+        if self.brackets.last() == Some(&'{') {
+            self.brackets.pop();
+        }
+        let (head, step) = self.blocks.pop().unwrap_or_else(|| (String::new(), 1));
+        self.indent_level = self.indent_level.saturating_sub(step);
+        if step == 0 {
+            self.line_level = Some(self.indent_level.saturating_sub(1));
+        }
+        self.line.push(JsToken::Punct("}".to_string()));
+        head
+    }
+
+    /// 结束当前语句；类的字段结束后若下一个成员（从 `next` 开始）是方法，与之空一行
+    /// （方法之后的空行由 [`JsFormatter::after_block`] 负责，单独成行的装饰器与其成员之间不空行）
+    /// End the current statement; after a class field, leave a blank line when the next member (starting at `next`) is a
+    /// method (blank lines after methods are left by [`JsFormatter::after_block`], and decorators on their own line stay
+    /// next to their member)
+    fn end_statement(&mut self, tokens: &[JsToken], next: usize) {
+        if log_enabled(LogLevel::Trace) {
+            let (text, _) = add_operator_spaces(&self.line, self.brackets.len(), &mut self.ternaries.clone());
+            log(LogLevel::Trace, tr!("js: 语句 {:?}（缩进层级 {}）", "js: statement {:?} (indent level {})", text, self.indent_level));
+        }
+        let decorators_only = js_only_decorators(&self.line);
+        self.end_line(js_next_significant(tokens, next).map(|n| &tokens[n]));
+        if self.in_class_body() && !decorators_only && js_class_member_is_method(tokens, next) {
+            self.write_line(0, "");
+        }
+    }
+}
+
+/// 在 `close` 处闭合的 `{` 是否为语句的块体（`)`、`else`、`try`、`catch`、`finally` 或 `do` 之后）；
+/// 对象字面量与箭头函数体之后的 `else` 只能由换行前的自动分号插入结束语句，不能接在 `}` 之后
+/// Whether the `{` closed at `close` opens the body of a statement (after `)`, `else`, `try`, `catch`, `finally` or `do`);
+/// after an object literal or an arrow function body only automatic semicolon insertion at a line break ends the statement,
+/// so an `else` can't follow the `}` on the same line
+fn js_statement_block(tokens: &[JsToken], close: usize) -> bool {
+    let mut depth = 0usize;
+    for open in (0..close).rev() {
+        match &tokens[open] {
+            JsToken::Punct(p) if p == "}" => depth += 1,
+            JsToken::Punct(p) if p == "{" && depth > 0 => depth -= 1,
+            JsToken::Punct(p) if p == "{" => {
+                return (0..open).rev().find(|&i| !matches!(tokens[i], JsToken::Whitespace { .. } | JsToken::LineComment(_) | JsToken::BlockComment(_))).is_some_and(
+                    |i| match &tokens[i] {
+                        JsToken::Punct(p) => p == ")",
+                        JsToken::Word(w) => ["else", "try", "catch", "finally", "do"].contains(&w.as_str()),
+                        _ => false,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// 以无花括号语句体的头部结束的一行（`if (a)`、`} else if (a)`、`for await (…)`、`while (a)`、`else`、`do`）的头部关键字；
+/// `do { } while (a)` 的 `while` 结束语句，不算头部
+/// The header keyword of a line ending in the header of a brace-less body (`if (a)`, `} else if (a)`, `for await (...)`,
+/// `while (a)`, `else`, `do`); the `while` of `do { } while (a)` ends the statement and is no header
+fn js_body_header(line: &[JsToken]) -> Option<&'static str> {
+    let significant: Vec<&JsToken> = line.iter().filter(|t| !matches!(t, JsToken::Whitespace { .. } | JsToken::LineComment(_) | JsToken::BlockComment(_))).collect();
+    let keyword = |index: usize, keywords: &[&'static str]| {
+        keywords.iter().copied().find(|&keyword| js_is_keyword(index.checked_sub(1).map(|i| significant[i]), significant[index], &[keyword]))
+    };
+    let last = significant.len().checked_sub(1)?;
+    if let Some(keyword) = keyword(last, &["else", "do"]) {
+        return Some(keyword);
+    }
+    if !matches!(significant[last], JsToken::Punct(p) if p == ")") {
+        return None;
+    }
+    let mut depth = 0usize;
+    let open = (0..=last).rev().find(|&i| {
+        match significant[i] {
+            JsToken::Punct(p) if p == ")" => depth += 1,
+            JsToken::Punct(p) if p == "(" => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    })?;
+    let mut head = open.checked_sub(1)?;
+    if keyword(head, &["await"]).is_some() {
+        head = head.checked_sub(1)?;
+    }
+    let header = keyword(head, &["if", "for", "while", "with"])?;
+    let do_while = header == "while" && matches!(significant[0], JsToken::Punct(p) if p == "}");
+    (!do_while).then_some(header)
+}
+
+/// 一行是否只有装饰器（`@name`、`@a.b(...)`，可有多个）
+/// Whether a line holds nothing but decorators (`@name`, `@a.b(...)`, possibly several)
+fn js_only_decorators(line: &[JsToken]) -> bool {
+    let significant: Vec<&JsToken> = line.iter().filter(|t| !matches!(t, JsToken::Whitespace { .. })).collect();
+    let mut i = 0;
+    while i < significant.len() {
+        if !matches!(significant[i], JsToken::Punct(p) if p == "@") || !matches!(significant.get(i + 1), Some(JsToken::Word(_))) {
+            return false;
+        }
+        i += 2;
+        while matches!(significant.get(i), Some(JsToken::Punct(p)) if p == ".") && matches!(significant.get(i + 1), Some(JsToken::Word(_))) {
+            i += 2;
+        }
+        if matches!(significant.get(i), Some(JsToken::Punct(p)) if p == "(") {
+            let mut depth = 0;
+            while i < significant.len() {
+                match significant[i] {
+                    JsToken::Punct(p) if matches!(p.as_str(), "(" | "[" | "{") => depth += 1,
+                    JsToken::Punct(p) if matches!(p.as_str(), ")" | "]" | "}") => depth -= 1,
+                    _ => {}
+                }
+                i += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+    }
+    !significant.is_empty()
+}
+
+/// 从 `from` 开始的类成员是否为方法：括号外先出现 `(` 的是方法，先出现 `=`、`;` 或 `}` 的是字段（或块体结束）；
+/// 装饰器（`@name`、`@a.b(...)`）不计
+/// Whether the class member starting at `from` is a method: a `(` outside brackets coming first makes a method, while
+/// `=`, `;` or `}` coming first make a field (or end the body); decorators (`@name`, `@a.b(...)`) are skipped
+fn js_class_member_is_method(tokens: &[JsToken], from: usize) -> bool {
+    let mut depth = 0usize;
+    let mut decorator = false;
+    for token in &tokens[from..] {
+        // 装饰器名之后紧接的 `(` 是装饰器的调用，按普通括号计入深度
+        // A `(` right after a decorator name calls the decorator and only counts towards the depth
+        match token {
+            JsToken::Punct(p) if p == "@" && depth == 0 => decorator = true,
+            JsToken::Punct(p) if p == "." && decorator => {}
+            JsToken::Punct(p) if p == "(" && decorator => {
+                decorator = false;
+                depth += 1;
+                continue;
+            }
+            JsToken::Word(_) if decorator => {}
+            _ => decorator = false,
+        }
+        if decorator || matches!(token, JsToken::Punct(p) if p == "@") {
+            continue;
+        }
+        let JsToken::Punct(p) = token else { continue };
+        match p.as_str() {
+            "(" if depth == 0 => return true,
+            "[" | "(" | "{" => depth += 1,
+            "]" | ")" => depth = depth.saturating_sub(1),
+            "}" if depth == 0 => return false,
+            "}" => depth -= 1,
+            "=" | ";" if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// 下一个有效词法单元（跳过空白与注释）的下标
+/// Index of the next significant token (skipping whitespace and comments)
+fn js_next_significant(tokens: &[JsToken], from: usize) -> Option<usize> {
+    (from..tokens.len()).find(|&i| !matches!(tokens[i], JsToken::Whitespace { .. } | JsToken::LineComment(_) | JsToken::BlockComment(_)))
+}
+
+/// 按词法单元格式化 JS/TS：字符串、模板字符串、正则与注释作为整体处理，其中的引号、转义与括号不影响结构
+/// Format JS/TS token by token: strings, templates, regexes and comments are handled whole, so quotes, escapes and
+/// brackets inside them never affect the structure
+pub(crate) fn format_js_ts(content: &str, options: &FormatOptions) -> Result<String> {
+    let (tokens, offsets) = tokenize_js_with_offsets(content);
+    let mut formatter = JsFormatter::new(options, content.len() * 2);
+    let mut previous: Option<usize> = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match token {
+            JsToken::Whitespace { newline } => {
+                // 换行可能结束语句（自动分号插入），此时保留换行
+                // A line break may end a statement (automatic semicolon insertion), keep it in that case
+                let next = js_next_significant(&tokens, i + 1);
+                let ends_statement = *newline
+                    && formatter.at_statement_level()
+                    && !formatter.line_is_empty()
+                    && match (previous, next) {
+                        (Some(p), Some(n)) => js_newline_required(&tokens[p], &tokens[n], formatter.in_class_body()),
+                        _ => false,
+                    };
+                if ends_statement {
+                    formatter.end_statement(&tokens, i + 1);
+                } else if !formatter.line_is_empty() {
+                    formatter.line.push(token.clone());
+                }
+            }
+            JsToken::LineComment(_) if options.wrap_comments && formatter.line_is_empty() => {
+                // 收集紧邻的单独成行的 `//` 注释（中间没有空行），整段重新折行
+                // Collect adjacent own-line `//` comments (no blank line between them) and reflow them as one run
+                let mut run = vec![i];
+                while let Some(next) = run.last().map(|&last| last + 2).filter(|&next| next < tokens.len()) {
+                    let single_break = content[offsets[next - 1]..offsets[next]].matches('\n').count() == 1;
+                    if !single_break || !matches!(tokens[next], JsToken::LineComment(_)) {
+                        break;
+                    }
+                    run.push(next);
+                }
+                let comments: Vec<&str> = run.iter().filter_map(|&n| match &tokens[n] {
+                    JsToken::LineComment(text) => Some(text.as_str()),
+                    _ => None,
+                }).collect();
+                formatter.push_line_comments(&comments);
+                i = run[run.len() - 1] + 1;
+                continue;
+            }
+            JsToken::LineComment(_) => {
+                // 行尾注释之后的换行同样可能结束语句
+                // The line break after a trailing comment may end a statement just the same
+                let next = js_next_significant(&tokens, i + 1);
+                let ends_statement = !formatter.line_is_empty()
+                    && formatter.at_statement_level()
+                    && match (previous, next) {
+                        (Some(p), Some(n)) => js_newline_required(&tokens[p], &tokens[n], formatter.in_class_body()),
+                        _ => false,
+                    };
+                formatter.line.push(token.clone());
+                if ends_statement {
+                    formatter.end_line(next.map(|n| &tokens[n]));
+                } else {
+                    formatter.flush_line();
+                }
+            }
+            JsToken::BlockComment(comment) => {
+                let own_line = formatter.line_is_empty() || comment.contains('\n');
+                if own_line && formatter.at_statement_level() {
+                    formatter.push_block_comment(comment);
+                } else {
+                    formatter.line.push(token.clone());
+                }
+            }
+            JsToken::Punct(p) if p == "{" => {
+                let close = (i + 1..tokens.len()).find(|&n| !matches!(tokens[n], JsToken::Whitespace { .. }));
+                if let Some(close) = close.filter(|&n| matches!(&tokens[n], JsToken::Punct(q) if q == "}")) {
+                    // 空块保持为 `{}`
+                    // An empty block stays `{}`
+                    let head = formatter.line_head();
+                    formatter.line.push(token.clone());
+                    formatter.line.push(tokens[close].clone());
+                    previous = Some(close);
+                    i = formatter.after_block(&tokens, close, &head).unwrap_or(close + 1);
+                    continue;
+                }
+                formatter.open_block();
+            }
+            JsToken::Punct(p) if p == "}" => {
+                let head = formatter.close_block();
+                if let Some(n) = formatter.after_block(&tokens, i, &head) {
+                    previous = Some(i);
+                    i = n;
+                    continue;
+                }
+            }
+            JsToken::Punct(p) if p == "(" || p == "[" => {
+                formatter.line.push(token.clone());
+                formatter.brackets.push(if p == "(" { '(' } else { '[' });
+            }
+            JsToken::Punct(p) if p == ")" || p == "]" => {
+                formatter.line.push(token.clone());
+                if matches!(formatter.brackets.last(), Some('(' | '[')) {
+                    formatter.brackets.pop();
+                }
+            }
+            JsToken::Word(w) if (w == "case" || w == "default") && formatter.line_is_empty() && formatter.in_switch_body() => {
+                formatter.start_case_label();
+                formatter.line.push(token.clone());
+            }
+            JsToken::Punct(p) if p == "?" && formatter.case_label.is_some() => {
+                formatter.case_label = formatter.case_label.map(|pending| pending + 1);
+                formatter.line.push(token.clone());
+            }
+            JsToken::Punct(p) if p == ":" && formatter.case_label.is_some() && formatter.at_statement_level() => {
+                formatter.line.push(token.clone());
+                match formatter.case_label {
+                    Some(0) => {
+                        // 标签结束；`case 1: {` 的块留在标签行
+                        // End of the label; the block of `case 1: {` stays on the label line
+                        let next = js_next_significant(&tokens, i + 1);
+                        if !next.is_some_and(|n| matches!(&tokens[n], JsToken::Punct(q) if q == "{")) {
+                            formatter.flush_line();
+                        }
+                    }
+                    pending => formatter.case_label = pending.map(|pending| pending - 1),
+                }
+            }
+            JsToken::Punct(p) if p == ";" => {
+                formatter.line.push(token.clone());
+                if formatter.at_statement_level() {
+                    formatter.end_statement(&tokens, i + 1);
+                }
+            }
+            _ => formatter.line.push(token.clone()),
+        }
+        if !matches!(token, JsToken::Whitespace { .. } | JsToken::LineComment(_) | JsToken::BlockComment(_)) {
+            previous = Some(i);
+        }
+        i += 1;
+    }
+    formatter.flush_line();
+
+    Ok(formatter.output)
+}
@@ -0,0 +1,398 @@
+//! JSON 格式化（解析为值树后重新输出，可排序键）
+//! JSON Formatting (parsed into a value tree and printed again, keys optionally sorted)
+
+use anyhow::Result;
+use crate::*;
+
+/// package.json 顶层字段的惯用顺序（--sort-keys package-json）
+/// Conventional order of the top-level package.json fields (--sort-keys package-json)
+const PACKAGE_JSON_ORDER: &[&str] = &[
+    "$schema", "name", "displayName", "version", "private", "description", "categories", "keywords", "homepage", "bugs",
+    "repository", "funding", "license", "author", "maintainers", "contributors", "publisher", "type", "exports", "main",
+    "module", "browser", "types", "typings", "bin", "man", "directories", "files", "workspaces", "scripts", "config",
+    "dependencies", "devDependencies", "peerDependencies", "peerDependenciesMeta", "optionalDependencies",
+    "bundledDependencies", "bundleDependencies", "overrides", "resolutions", "packageManager", "engines", "os", "cpu",
+    "publishConfig",
+];
+
+/// package.json 中按字母序排列的依赖表
+/// Dependency maps in package.json that are sorted alphabetically
+const PACKAGE_JSON_DEPENDENCIES: &[&str] =
+    &["dependencies", "devDependencies", "peerDependencies", "peerDependenciesMeta", "optionalDependencies", "resolutions"];
+
+/// JSON 值；字符串、数字与字面量保留原文
+/// A JSON value; strings, numbers and literals keep their source text
+#[derive(Debug, Clone)]
+pub(crate) enum JsonValue {
+    /// 对象成员（键的原文含引号）
+    /// Object members (the key's source text includes the quotes)
+    Object(Vec<(String, JsonValue)>),
+    Array(Vec<JsonValue>),
+    Scalar(String),
+}
+
+/// JSON 解析器：用显式栈代替递归，深层嵌套的输入不会耗尽调用栈
+/// JSON parser: an explicit stack replaces recursion, so deeply nested input can't exhaust the call stack
+struct JsonParser<'a> {
+    source: &'a str,
+    position: usize,
+    /// 是否构建值树；只检查语法时不构建，丢弃深层嵌套的值树同样是递归的
+    /// Whether to build the value tree; not built when only checking the syntax, since dropping a deeply nested tree
+    /// recurses as well
+    build: bool,
+}
+
+/// 解析中尚未闭合的对象或数组
+/// An object or array not yet closed while parsing
+enum JsonContainer {
+    /// 已解析的成员与下一个值所属的键
+    /// The members parsed so far and the key the next value belongs to
+    Object(Vec<(String, JsonValue)>, String),
+    Array(Vec<JsonValue>),
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    /// 当前位置的语法错误（带出错位置，可显示源码与脱字符）
+    /// Syntax error at the current position (with its location, so the source and a caret can be shown)
+    fn error(&self, expected: &str) -> anyhow::Error {
+        let found = match self.peek() {
+            None => tr!("文件结尾", "end of file"),
+            Some(c) if c < ' ' => format!("U+{:04X}", c as u32),
+            Some(c) => format!("`{}`", c),
+        };
+        let len = self.peek().map_or(0, char::len_utf8);
+        FormatError::InvalidJson { expected: expected.to_string(), found, span: SourceSpan::new(self.source, self.position, len) }.into()
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("`{}`", c)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.position;
+        let mut escaped = false;
+        for (offset, c) in self.source[start + 1..].char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    self.position = start + 1 + offset + 1;
+                    return Ok(self.source[start..self.position].to_string());
+                }
+                '\n' => break,
+                // U+0000 到 U+001F 的控制字符只能以转义形式出现在字符串中
+                // Control characters U+0000 to U+001F may only appear escaped in a string
+                c if c < ' ' => {
+                    self.position = start + 1 + offset;
+                    return Err(self.error(&tr!("转义的控制字符", "an escaped control character")));
+                }
+                _ => {}
+            }
+        }
+        Err(self.error(&tr!("字符串结束引号", "a closing quote")))
+    }
+
+    /// 对象的键及其后的 `:`
+    /// An object key and the `:` after it
+    fn key(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        if self.peek() != Some('"') {
+            return Err(self.error(&tr!("字符串键", "a string key")));
+        }
+        let key = self.string()?;
+        self.expect(':')?;
+        Ok(key)
+    }
+
+    /// 数字或 `true`、`false`、`null`
+    /// A number or `true`, `false`, `null`
+    fn scalar(&mut self) -> Result<JsonValue> {
+        let rest = &self.source[self.position..];
+        let length = rest.find(|c: char| c.is_whitespace() || ",:[]{}\"".contains(c)).unwrap_or(rest.len());
+        let text = &rest[..length];
+        if text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            if let Err((offset, expected)) = scan_json_number(text) {
+                self.position += offset;
+                return Err(self.error(&expected));
+            }
+        } else if !matches!(text, "true" | "false" | "null") {
+            return Err(self.error(&tr!("值", "a value")));
+        }
+        self.position += length;
+        Ok(JsonValue::Scalar(text.to_string()))
+    }
+
+    fn value(&mut self) -> Result<JsonValue> {
+        let mut open: Vec<JsonContainer> = Vec::new();
+        loop {
+            // 读取一个值；对象与数组非空时入栈，先读取其第一个成员
+            // Read one value; a non-empty object or array is pushed and its first member read first
+            self.skip_whitespace();
+            let mut value = match self.peek() {
+                Some(c @ ('{' | '[')) => {
+                    self.position += 1;
+                    self.skip_whitespace();
+                    let close = if c == '{' { '}' } else { ']' };
+                    if self.peek() == Some(close) {
+                        self.position += 1;
+                        if c == '{' { JsonValue::Object(Vec::new()) } else { JsonValue::Array(Vec::new()) }
+                    } else {
+                        open.push(if c == '{' { JsonContainer::Object(Vec::new(), self.key()?) } else { JsonContainer::Array(Vec::new()) });
+                        continue;
+                    }
+                }
+                Some('"') => JsonValue::Scalar(self.string()?),
+                _ => self.scalar()?,
+            };
+            // 把值放入所在的对象或数组；遇到闭括号时该对象或数组本身成为值，继续向外
+            // Put the value into its object or array; at a closing bracket that object or array becomes the value
+            // itself and the loop moves outwards
+            loop {
+                let Some(mut container) = open.pop() else {
+                    return Ok(value);
+                };
+                self.skip_whitespace();
+                let next = self.peek();
+                let closed = match &mut container {
+                    JsonContainer::Object(members, key) => {
+                        if self.build {
+                            members.push((std::mem::take(key), value));
+                        }
+                        match next {
+                            Some(',') => false,
+                            Some('}') => true,
+                            _ => return Err(self.error("`,` / `}`")),
+                        }
+                    }
+                    JsonContainer::Array(items) => {
+                        if self.build {
+                            items.push(value);
+                        }
+                        match next {
+                            Some(',') => false,
+                            Some(']') => true,
+                            _ => return Err(self.error("`,` / `]`")),
+                        }
+                    }
+                };
+                self.position += 1;
+                if !closed {
+                    if let JsonContainer::Object(_, key) = &mut container {
+                        *key = self.key()?;
+                    }
+                    open.push(container);
+                    break;
+                }
+                value = match container {
+                    JsonContainer::Object(members, _) => JsonValue::Object(members),
+                    JsonContainer::Array(items) => JsonValue::Array(items),
+                };
+            }
+        }
+    }
+}
+
+/// 按 JSON 语法 `-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?` 检查数字，出错时返回出错的偏移与期望的内容
+/// Check a number against the JSON grammar `-?(0|[1-9]\d*)(\.\d+)?([eE][+-]?\d+)?`, returning the offset of the
+/// error and what was expected there
+fn scan_json_number(text: &str) -> Result<(), (usize, String)> {
+    let bytes = text.as_bytes();
+    let digits_from = |start: usize| start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut index = usize::from(bytes.first() == Some(&b'-'));
+    match bytes.get(index) {
+        Some(b'0') => index += 1,
+        Some(b'1'..=b'9') => index = digits_from(index),
+        _ => return Err((index, tr!("数字", "a digit"))),
+    }
+    if bytes.get(index) == Some(&b'.') {
+        let end = digits_from(index + 1);
+        if end == index + 1 {
+            return Err((end, tr!("数字", "a digit")));
+        }
+        index = end;
+    }
+    if matches!(bytes.get(index), Some(b'e' | b'E')) {
+        index += 1;
+        if matches!(bytes.get(index), Some(b'+' | b'-')) {
+            index += 1;
+        }
+        let end = digits_from(index);
+        if end == index {
+            return Err((end, tr!("数字", "a digit")));
+        }
+        index = end;
+    }
+    if index < bytes.len() {
+        return Err((index, tr!("数字结尾", "the end of the number")));
+    }
+    Ok(())
+}
+
+/// 解析完整的 JSON 文本（值之后只允许空白）
+/// Parse a complete JSON text (only whitespace may follow the value)
+pub(crate) fn parse_json(source: &str) -> Result<JsonValue> {
+    parse_json_with(source, true)
+}
+
+/// 解析完整的 JSON 文本，`build` 为 false 时只检查语法
+/// Parse a complete JSON text, only checking the syntax when `build` is false
+fn parse_json_with(source: &str, build: bool) -> Result<JsonValue> {
+    let mut parser = JsonParser { source, position: 0, build };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position < source.len() {
+        return Err(parser.error(&tr!("文件结尾", "end of file")));
+    }
+    Ok(value)
+}
+
+/// JSON 的语法错误：由解析器检查，尾随逗号、注释等 JS 允许而 JSON 不允许的写法同样报错
+/// Syntax errors in JSON: checked by the parser, so trailing commas, comments and other things JS accepts but JSON
+/// doesn't are reported too
+pub(crate) fn json_syntax_errors(source: &str) -> Vec<FormatError> {
+    parse_json_with(source, false).err().and_then(|err| err.downcast::<FormatError>().ok()).into_iter().collect()
+}
+
+/// 按 --sort-keys 排序对象的键；package-json 模式只调整顶层字段与依赖表的顺序
+/// Sort object keys per --sort-keys; the package-json mode only reorders the top-level fields and the dependency maps
+fn sort_json_keys(value: &mut JsonValue, order: SortKeys, top_level: bool) {
+    let key_name = |key: &str| key[1..key.len() - 1].to_string();
+    match value {
+        JsonValue::Object(members) => {
+            match order {
+                SortKeys::Alphabetical => members.sort_by_key(|(key, _)| key_name(key)),
+                SortKeys::PackageJson if top_level => {
+                    // 未知字段排在已知字段之后，保持原有顺序
+                    // Unknown fields follow the known ones in their original order
+                    members.sort_by_key(|(key, _)| {
+                        PACKAGE_JSON_ORDER.iter().position(|field| *field == key_name(key)).unwrap_or(PACKAGE_JSON_ORDER.len())
+                    });
+                    for (key, member) in members.iter_mut() {
+                        if let JsonValue::Object(dependencies) = member {
+                            if PACKAGE_JSON_DEPENDENCIES.contains(&key_name(key).as_str()) {
+                                dependencies.sort_by_key(|(name, _)| key_name(name));
+                            }
+                        }
+                    }
+                    return;
+                }
+                _ => {}
+            }
+            for (_, member) in members.iter_mut() {
+                sort_json_keys(member, order, false);
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items.iter_mut() {
+                sort_json_keys(item, order, false);
+            }
+        }
+        JsonValue::Scalar(_) => {}
+    }
+}
+
+/// 输出 JSON 值：非空的对象与数组每个成员一行，`indent_unit` 为 None 时输出紧凑形式
+/// Print a JSON value: non-empty objects and arrays get one member per line, compact output when `indent_unit` is None
+fn write_json(value: &JsonValue, indent_unit: Option<&str>, level: usize, out: &mut String) {
+    let (open, close, count) = match value {
+        JsonValue::Scalar(text) => {
+            out.push_str(text);
+            return;
+        }
+        JsonValue::Object(members) => ('{', '}', members.len()),
+        JsonValue::Array(items) => ('[', ']', items.len()),
+    };
+    out.push(open);
+    for index in 0..count {
+        if index > 0 {
+            out.push(',');
+        }
+        if let Some(unit) = indent_unit {
+            out.push('\n');
+            out.push_str(&unit.repeat(level + 1));
+        }
+        match value {
+            JsonValue::Object(members) => {
+                out.push_str(&members[index].0);
+                out.push_str(if indent_unit.is_some() { ": " } else { ":" });
+                write_json(&members[index].1, indent_unit, level + 1, out);
+            }
+            JsonValue::Array(items) => write_json(&items[index], indent_unit, level + 1, out),
+            JsonValue::Scalar(_) => {}
+        }
+    }
+    if let (Some(unit), true) = (indent_unit, count > 0) {
+        out.push('\n');
+        out.push_str(&unit.repeat(level));
+    }
+    out.push(close);
+}
+
+pub(crate) fn format_json(content: &str, options: &FormatOptions) -> Result<String> {
+    let mut value = parse_json(content)?;
+    sort_json_keys(&mut value, options.sort_keys, true);
+    let mut formatted = String::new();
+    write_json(&value, Some(&options.indent_unit()), 0, &mut formatted);
+    formatted.push('\n');
+    Ok(formatted)
+}
+
+/// 压缩 JSON：去掉值之间的全部空白
+/// Minify JSON: drop all whitespace between values
+pub(crate) fn minify_json(content: &str) -> Result<String> {
+    let mut minified = String::new();
+    write_json(&parse_json(content)?, None, 0, &mut minified);
+    Ok(minified)
+}
+
+/// 解码 JSON 字符串字面量（含引号的原文），不是合法字符串时返回 None
+/// Decode a JSON string literal (source text with the quotes), None when it isn't a valid string
+pub(crate) fn json_unescape(literal: &str) -> Option<String> {
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    let hex = |chars: &mut std::str::Chars| -> Option<u32> { u32::from_str_radix(&chars.by_ref().take(4).collect::<String>(), 16).ok() };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let decoded = match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'u' => {
+                let high = hex(&mut chars)?;
+                // 代理对由两个连续的 \u 转义组成
+                // A surrogate pair is made of two consecutive \u escapes
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    let low = chars.as_str().strip_prefix("\\u").and_then(|rest| u32::from_str_radix(rest.get(..4)?, 16).ok())?;
+                    chars.nth(5);
+                    0x10000 + ((high - 0xD800) << 10) + (low.checked_sub(0xDC00)? & 0x3FF)
+                } else {
+                    high
+                };
+                char::from_u32(code)?
+            }
+            other => other,
+        };
+        result.push(decoded);
+    }
+    Some(result)
+}
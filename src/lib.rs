@@ -0,0 +1,673 @@
+//! 压缩代码格式化工具：符合行业规范的 HTML/CSS/JS/TS/JSON 格式化（高可读性）
+//! Code formatter for compressed code: Industry-standard HTML/CSS/JS/TS/JSON formatting (high readability)
+//!
+//! 命令行程序见 [`cli_main`]；编辑器插件等可调用 [`format`] 与 [`format_with_diff`]
+//! The command line program is [`cli_main`]; editor plugins and other tools can call [`format`] and [`format_with_diff`]
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+#[macro_use]
+mod i18n;
+mod batch;
+mod cli;
+mod config;
+mod css;
+mod diff;
+mod dump;
+mod encoding;
+mod eol;
+mod external;
+mod html;
+mod js;
+mod json;
+mod limits;
+mod logging;
+mod minify;
+mod options;
+#[cfg(feature = "plugins")]
+mod plugins;
+mod report;
+mod results;
+mod settings;
+mod since;
+mod syntax;
+mod util;
+mod walk;
+
+pub use cli::cli_main;
+pub use diff::{FormatOutcome, Span};
+pub use options::FormatOptions;
+use i18n::*;
+use batch::*;
+use cli::*;
+use config::*;
+use css::*;
+use diff::*;
+use dump::*;
+use encoding::*;
+use eol::*;
+use external::*;
+use html::*;
+use js::*;
+use json::*;
+use limits::*;
+use logging::*;
+use minify::*;
+use options::*;
+#[cfg(feature = "plugins")]
+use plugins::*;
+use report::*;
+use results::*;
+use settings::*;
+use since::*;
+use syntax::*;
+use util::*;
+use walk::*;
+
+// ============================================================================
+// 语言注册表
+// Language registry
+// ============================================================================
+
+/// 一种代码类型的格式化器：类型名与别名、扩展名，以及格式化、压缩与结构检查的实现
+/// 新增语言只需实现这些函数并在 [`LANGUAGES`] 中登记，或者编译为 WASM 插件放入插件目录（见 [`WasmPlugin`]）；
+/// 命令行、配置文件、校验与报告流程自动支持
+/// The formatter of one code type: type name and aliases, extensions, and the formatting, minification and structural
+/// check implementations
+/// A new language only needs these functions and an entry in [`LANGUAGES`], or a WASM plugin in the plugins directory
+/// (see [`WasmPlugin`]); the CLI, config file, validation and reporting pipeline pick it up automatically
+struct Language {
+    /// 代码类型名（`--type`、配置文件与报告中使用）
+    /// Code type name (used by `--type`, the config file and reports)
+    name: &'static str,
+    /// 类型名的别名
+    /// Aliases of the type name
+    aliases: &'static [&'static str],
+    /// 内置扩展名（小写，不含点）
+    /// Built-in extensions (lowercase, without dot)
+    extensions: &'static [&'static str],
+    backend: Backend,
+}
+
+/// 语言的实现：编译进程序的函数，或从插件目录加载的 WASM 插件
+/// The implementation of a language: functions compiled into the program, or a WASM plugin from the plugins directory
+enum Backend {
+    Builtin(Builtin),
+    #[cfg(feature = "plugins")]
+    Wasm(Box<WasmPlugin>),
+}
+
+/// 编译进程序的语言实现
+/// A language implementation compiled into the program
+struct Builtin {
+    /// 格式化去掉首尾空白的源码
+    /// Format the source without its surrounding whitespace
+    format: fn(&str, &FormatOptions) -> Result<String>,
+    /// 压缩（不支持压缩时为 None）
+    /// Minify (None when minification is not supported)
+    minify: Option<fn(&str) -> Result<String>>,
+    /// 格式化前的结构检查
+    /// Structural checks before formatting
+    syntax_errors: fn(&str) -> Vec<FormatError>,
+    /// validate 子命令的结构检查（可比格式化前的检查更严格）
+    /// Structural checks of the validate subcommand (may be stricter than the pre-formatting checks)
+    validation_errors: fn(&str) -> Vec<FormatError>,
+    /// 格式化前检查 --max-nesting / --max-line-tokens 等上限
+    /// Checks of limits such as --max-nesting / --max-line-tokens before formatting
+    limit_error: fn(&str, &FormatOptions) -> Option<FormatError>,
+    /// 块注释的起止标记，用于识别开头需原样保留的许可证横幅
+    /// Start and end markers of block comments, used to find the license banner kept verbatim at the start
+    comment: (&'static str, &'static str),
+    /// debug-tokens 子命令输出的词法单元或语法树（含小标题）
+    /// The tokens or syntax tree printed by the debug-tokens subcommand (with its heading)
+    dump: fn(&str, &mut String),
+}
+
+/// 编译进程序的语言
+/// Languages compiled into the program
+static LANGUAGES: &[Language] = &[
+    Language {
+        name: "html",
+        aliases: &["htm"],
+        extensions: &["html", "htm"],
+        backend: Backend::Builtin(Builtin {
+            format: format_html,
+            minify: Some(minify_html),
+            syntax_errors: |source| html_syntax_errors(source, false),
+            validation_errors: |source| html_syntax_errors(source, true),
+            limit_error: |source, options| html_nesting_error(source, options.max_nesting),
+            comment: ("<!--", "-->"),
+            dump: dump_html_tokens,
+        }),
+    },
+    Language {
+        name: "css",
+        aliases: &[],
+        extensions: &["css"],
+        backend: Backend::Builtin(Builtin {
+            format: format_css,
+            minify: Some(minify_css),
+            syntax_errors: css_syntax_errors,
+            validation_errors: css_syntax_errors,
+            limit_error: |source, options| token_limit_error(source, options, true),
+            comment: ("/*", "*/"),
+            dump: dump_css_tree,
+        }),
+    },
+    Language {
+        name: "js",
+        aliases: &["javascript"],
+        extensions: &["js", "mjs", "cjs"],
+        backend: Backend::Builtin(Builtin {
+            format: format_js_ts,
+            minify: Some(minify_js_ts),
+            syntax_errors: js_syntax_errors,
+            validation_errors: js_syntax_errors,
+            limit_error: |source, options| token_limit_error(source, options, true),
+            comment: ("/*", "*/"),
+            dump: dump_js_tokens,
+        }),
+    },
+    Language {
+        name: "ts",
+        aliases: &["typescript"],
+        extensions: &["ts", "mts", "cts"],
+        backend: Backend::Builtin(Builtin {
+            format: format_js_ts,
+            minify: Some(minify_js_ts),
+            syntax_errors: js_syntax_errors,
+            validation_errors: js_syntax_errors,
+            limit_error: |source, options| token_limit_error(source, options, true),
+            comment: ("/*", "*/"),
+            dump: dump_js_tokens,
+        }),
+    },
+    Language {
+        name: "json",
+        aliases: &[],
+        extensions: &["json"],
+        backend: Backend::Builtin(Builtin {
+            format: format_json,
+            minify: Some(minify_json),
+            syntax_errors: json_syntax_errors,
+            validation_errors: json_syntax_errors,
+            limit_error: |source, options| token_limit_error(source, options, false),
+            comment: ("/*", "*/"),
+            dump: dump_js_tokens,
+        }),
+    },
+];
+
+/// 从插件目录加载的语言（未指定插件目录时为空）
+/// Languages loaded from the plugins directory (empty without a plugins directory)
+static PLUGINS: std::sync::OnceLock<Vec<Language>> = std::sync::OnceLock::new();
+
+/// 注册表中的全部语言：编译进程序的语言在前，插件在后
+/// Every language of the registry: the ones compiled into the program first, then the plugins
+fn languages() -> impl Iterator<Item = &'static Language> {
+    LANGUAGES.iter().chain(PLUGINS.get().into_iter().flatten())
+}
+
+impl Language {
+    /// 格式化去掉首尾空白的源码
+    /// Format the source without its surrounding whitespace
+    fn format(&self, source: &str, options: &FormatOptions) -> Result<String> {
+        match &self.backend {
+            Backend::Builtin(builtin) => (builtin.format)(source, options),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(plugin) => plugin.format(source, options),
+        }
+    }
+
+    /// 压缩；不支持压缩的类型报告配置错误
+    /// Minify; types without minification report a configuration error
+    fn minify(&self, source: &str) -> Result<String> {
+        match &self.backend {
+            Backend::Builtin(Builtin { minify: Some(minify), .. }) => minify(source),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(plugin) if plugin.can_minify() => plugin.minify(source),
+            _ => Err(ConfigError(tr!("不支持压缩的文件类型：{}", "Minification is not supported for file type: {}", self.name)).into()),
+        }
+    }
+
+    /// 格式化前的结构检查；插件自行检查输入，在格式化失败时报告
+    /// Structural checks before formatting; plugins check their input themselves and report it when formatting fails
+    fn syntax_errors(&self, source: &str) -> Vec<FormatError> {
+        match &self.backend {
+            Backend::Builtin(builtin) => (builtin.syntax_errors)(source),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(_) => Vec::new(),
+        }
+    }
+
+    /// validate 子命令的结构检查
+    /// Structural checks of the validate subcommand
+    fn validation_errors(&self, source: &str) -> Vec<FormatError> {
+        match &self.backend {
+            Backend::Builtin(builtin) => (builtin.validation_errors)(source),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(_) => Vec::new(),
+        }
+    }
+
+    /// 格式化前的上限检查；插件在自己的沙箱内存中运行，不做检查
+    /// Limit checks before formatting; plugins run in their own sandboxed memory and aren't checked
+    fn limit_error(&self, source: &str, options: &FormatOptions) -> Option<FormatError> {
+        match &self.backend {
+            Backend::Builtin(builtin) => (builtin.limit_error)(source, options),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(_) => None,
+        }
+    }
+
+    /// 块注释的起止标记；插件语言没有需保留的横幅
+    /// Start and end markers of block comments; plugin languages have no banner to keep
+    fn comment(&self) -> Option<(&'static str, &'static str)> {
+        match &self.backend {
+            Backend::Builtin(builtin) => Some(builtin.comment),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(_) => None,
+        }
+    }
+
+    /// debug-tokens 子命令的词法单元或语法树
+    /// The tokens or syntax tree of the debug-tokens subcommand
+    fn dump(&self, source: &str, out: &mut String) {
+        match &self.backend {
+            Backend::Builtin(builtin) => (builtin.dump)(source, out),
+            #[cfg(feature = "plugins")]
+            Backend::Wasm(plugin) => {
+                out.push_str(&tr!("## 词法单元\n", "## Tokens\n"));
+                out.push_str(&tr!("（WASM 插件 {} 不提供词法单元）\n", "(WASM plugin {} provides no tokens)\n", plugin.path));
+            }
+        }
+    }
+}
+
+/// 按代码类型名查找语言
+/// Look up a language by its code type name
+fn language(file_type: &str) -> Option<&'static Language> {
+    languages().find(|language| language.name == file_type)
+}
+
+/// 支持的代码类型列表，用于错误信息（如 `html/css/js/ts/json`）
+/// The supported code types for error messages (such as `html/css/js/ts/json`)
+fn supported_types() -> String {
+    languages().map(|language| language.name).collect::<Vec<_>>().join("/")
+}
+
+/// 不支持的代码类型对应的配置错误
+/// Configuration error for an unsupported code type
+fn unsupported_type(file_type: &str) -> anyhow::Error {
+    ConfigError(tr!("不支持的文件类型：{}，仅支持 {}", "Unsupported file type: {}, only {} are supported", file_type, supported_types())).into()
+}
+
+/// 代码类型名称（含别名）→ 代码类型
+/// Code type name (including aliases) → code type
+fn file_type_from_name(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    languages()
+        .find(|language| language.name == name || language.aliases.contains(&name.as_str()))
+        .map(|language| language.name)
+}
+
+/// 根据文件扩展名判断代码类型；配置文件中的自定义扩展名优先于内置扩展名
+/// Determine code type based on file extension; custom extensions from the config file win over built-in ones
+fn get_file_type(file_path: &str, extensions: &[(String, &'static str)]) -> Result<&'static str> {
+    let ext = Path::new(file_path)
+        .extension()
+        .ok_or_else(|| ConfigError(tr!("文件无扩展名，无法识别代码类型：{}（可用 --type 指定）", "File has no extension, cannot determine the code type: {} (use --type)", file_path)))?
+        .to_str()
+        .with_context(|| tr!("扩展名编码无效", "Invalid extension encoding"))?;
+
+    let ext_lower = ext.to_lowercase();
+    if let Some((_, file_type)) = extensions.iter().find(|(custom, _)| *custom == ext_lower) {
+        return Ok(file_type);
+    }
+    languages()
+        .find(|language| language.extensions.contains(&ext_lower.as_str()))
+        .map(|language| language.name)
+        .ok_or_else(|| unsupported_type(ext))
+}
+
+/// 确定代码类型：优先使用 `--type`（类型名或用于推断的文件名），其次按输入文件扩展名判断，
+/// 扩展名缺失或无法识别（包括标准输入）时根据内容判断
+/// Resolve the code type: `--type` (a type name or a file name to infer from) wins, then the input extension;
+/// when the extension is missing or unknown (including stdin) the content decides
+fn resolve_file_type(
+    file_path: &str,
+    type_hint: Option<&str>,
+    extensions: &[(String, &'static str)],
+    content: &str,
+) -> Result<&'static str> {
+    if let Some(hint) = type_hint {
+        return match file_type_from_name(hint) {
+            Some(file_type) => Ok(file_type),
+            None => get_file_type(hint, extensions),
+        };
+    }
+    let by_extension = if file_path == "-" {
+        Err(ConfigError(tr!("标准输入没有扩展名", "Standard input has no extension")).into())
+    } else {
+        get_file_type(file_path, extensions)
+    };
+    by_extension.or_else(|err| {
+        sniff_file_type(content).ok_or_else(|| {
+            ConfigError(tr!("{:#}；根据内容也无法判断代码类型，请用 --type 指定", "{:#}; the code type cannot be determined from the content either, use --type", err)).into()
+        })
+    })
+}
+
+/// 内容判断所需的最低得分；得分最高的类型还必须超过第二名的两倍
+/// Minimum score for content sniffing; the best type must also score more than twice the runner-up
+const SNIFF_MIN_SCORE: usize = 3;
+
+/// 根据内容特征判断代码类型，无法确定时返回 None
+/// Guess the code type from content features, None when undecidable
+fn sniff_file_type(content: &str) -> Option<&'static str> {
+    let text: String = content.trim_start().chars().take(8192).collect();
+    let lower = text.to_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return Some("html");
+    }
+
+    let lines: Vec<&str> = lower.lines().map(str::trim).collect();
+    let count = |pattern: &str| lower.matches(pattern).count();
+    let lines_starting = |prefixes: &[&str]| lines.iter().filter(|l| prefixes.iter().any(|p| l.starts_with(p))).count();
+
+    let mut html = 0;
+    if lower.starts_with('<') {
+        html += 2;
+    }
+    html += count("</").min(3);
+    if ["<div", "<span", "<body", "<head", "<script", "<meta", "<link", "<p>", "<a "].iter().any(|tag| lower.contains(tag)) {
+        html += 2;
+    }
+
+    // CSS：最内层 `{}` 中 `属性: 值` 形式的声明、at 规则与 `.`/`#` 开头的选择器
+    // CSS: `property: value` declarations inside innermost `{}`, at-rules and selectors opening with `.`/`#`
+    let mut declarations = 0;
+    let mut block_start = None;
+    for (i, c) in lower.char_indices() {
+        match c {
+            '{' => block_start = Some(i + 1),
+            '}' => {
+                if let Some(start) = block_start.take() {
+                    declarations += lower[start..i]
+                        .split(';')
+                        .filter(|part| {
+                            part.split_once(':').is_some_and(|(property, value)| {
+                                let property = property.trim();
+                                !property.is_empty()
+                                    && property.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+                                    && !value.trim().is_empty()
+                                    && !value.contains('=')
+                            })
+                        })
+                        .count();
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut css = declarations.min(4);
+    if ["@media", "@import", "@charset", "@font-face", "@keyframes", ":root"].iter().any(|p| lower.contains(p)) {
+        css += 3;
+    }
+    if lines_starting(&[".", "#"]) > 0 {
+        css += 1;
+    }
+
+    let mut js = 2 * lines_starting(&["function ", "const ", "let ", "var ", "import ", "export ", "async function"]).min(2);
+    js += 2 * count("=>").min(1);
+    js += ["require(", "console.", "document.", "window.", "return "].iter().filter(|p| lower.contains(*p)).count();
+    let ts = 2 * lines_starting(&["interface ", "type ", "enum ", "export interface ", "export type "]).min(1)
+        + [": string", ": number", ": boolean", ": any", ": void", "readonly ", "implements "]
+            .iter()
+            .filter(|p| lower.contains(*p))
+            .count();
+    js += ts;
+
+    let mut scores = [("html", html), ("css", css), ("js", js)];
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    let (best, score) = scores[0];
+    if score < SNIFF_MIN_SCORE || score <= scores[1].1 * 2 {
+        return None;
+    }
+    Some(if best == "js" && ts >= 2 { "ts" } else { best })
+}
+
+// ============================================================================
+// 格式化流程（公开接口，以及共用的结构检查与收尾处理）
+// Formatting pipeline (public API plus the shared structural checks and finishing)
+// ============================================================================
+
+/// 格式化一段源码，结果与命令行写入文件的内容相同（去掉 `\r`，按 BOM 与末尾换行策略处理）
+/// `file_type` 为代码类型名或别名（如 `html`、`css`、`js`、`ts`、`json`）
+/// Format source code; the result is what the command line would write to the file (`\r` removed, the BOM and final
+/// newline policies applied)
+/// `file_type` is a code type name or alias (such as `html`, `css`, `js`, `ts`, `json`)
+pub fn format(source: &str, file_type: &str, options: &FormatOptions) -> Result<String> {
+    let file_type = file_type_from_name(file_type).ok_or_else(|| unsupported_type(file_type))?;
+    let (has_bom, text) = strip_bom(source);
+    let content = text.replace('\r', "");
+    let formatted = format_code(&content, file_type, &options.for_type(file_type))?;
+    Ok(apply_bom_policy(formatted, has_bom, options.bom, TextEncoding::Utf8))
+}
+
+/// 格式化一段源码并给出相对原文的改动区间；偏移基于传入的 `source`（含 BOM 与 `\r`），
+/// 按顺序应用这些改动即可把编辑器中的缓冲区变为 `formatted`
+/// Format source code and report the ranges that changed relative to it; offsets refer to `source` as passed (BOM and
+/// `\r` included), so applying the changes in order turns the editor's buffer into `formatted`
+pub fn format_with_diff(source: &str, file_type: &str, options: &FormatOptions) -> Result<FormatOutcome> {
+    let formatted = format(source, file_type, options)?;
+    let changed_ranges = changed_ranges(source, &formatted);
+    Ok(FormatOutcome { formatted, changed_ranges })
+}
+
+/// 统一格式化入口：格式化去掉首尾空白的内容，再统一应用行尾空白与末尾换行策略
+/// Unified formatting entry point: formats the content without surrounding whitespace, then applies the
+/// trailing-whitespace and final-newline policies uniformly
+fn format_code(content: &str, file_type: &str, options: &FormatOptions) -> Result<String> {
+    // 结构有误的输入无法可靠地格式化，报告第一处错误
+    // Structurally broken input can't be formatted reliably, report the first error
+    if let Some(error) = syntax_errors(content, file_type).into_iter().next() {
+        return Err(error.into());
+    }
+    if let Some(error) = limit_error(content, file_type, options) {
+        return Err(error.into());
+    }
+    let formatted = format_source(content.trim(), file_type, options)?;
+    Ok(finish_output(formatted, content, license_header(content.trim(), file_type), options))
+}
+
+/// 在语言注册表中查找代码类型的格式化器并调用（不做结构检查与收尾处理）
+/// Look up the formatter of the code type in the language registry and run it (without structural checks or finishing)
+fn format_source(source: &str, file_type: &str, options: &FormatOptions) -> Result<String> {
+    if log_enabled(LogLevel::Trace) {
+        log(LogLevel::Trace, tr!("format_code: 类型 {}，{} 字节", "format_code: type {}, {} bytes", file_type, source.len()));
+    }
+    let language = language(file_type).ok_or_else(|| unsupported_type(file_type))?;
+    // 开头的许可证/版权横幅原样放在输出最前面，只格式化其后的代码
+    // A leading license/banner header goes verbatim to the very top of the output, only the code after it is formatted
+    let header = license_header(source, file_type);
+    if header.is_empty() {
+        return language.format(source, options);
+    }
+    let body = source[header.len()..].trim_start();
+    let formatted = if body.is_empty() { String::new() } else { language.format(body, options)? };
+    // CSS 的 `@charset` 必须是文件的第一条内容，横幅放在它之后
+    // CSS requires `@charset` to be the very first thing in the file, so the banner goes after it
+    if let Some((charset, rest)) = css_leading_charset(&formatted, file_type) {
+        return Ok(format!("{}\n{}\n{}", charset, header, rest.trim_start()));
+    }
+    Ok(format!("{}\n{}", header, formatted))
+}
+
+/// CSS 输出开头的 `@charset` 规则所在行及其后的内容；其他类型或没有 `@charset` 时为 None
+/// The line with the `@charset` rule at the start of CSS output and what follows it; None for other types or without
+/// `@charset`
+fn css_leading_charset<'a>(output: &'a str, file_type: &str) -> Option<(&'a str, &'a str)> {
+    if file_type != "css" || !output.get(..8).is_some_and(|start| start.eq_ignore_ascii_case("@charset")) {
+        return None;
+    }
+    Some(output.split_once('\n').unwrap_or((output, "")))
+}
+
+/// 源码开头连续的需保留注释（`/*!`、`@license`、`@preserve`，HTML 为 `<!--!` 或含这些标记的注释）及其间的空白，原样返回
+/// The run of comments at the start of the source that must be kept (`/*!`, `@license`, `@preserve`; in HTML `<!--!` or
+/// comments with those markers) together with the whitespace between them, returned verbatim
+fn license_header<'a>(source: &'a str, file_type: &str) -> &'a str {
+    let Some((open, close)) = language(file_type).and_then(Language::comment) else {
+        return "";
+    };
+    let mut end = 0;
+    loop {
+        let rest = &source[end..];
+        let start = end + rest.len() - rest.trim_start().len();
+        let Some(length) = source[start..].strip_prefix(open).and_then(|body| body.find(close)).map(|n| open.len() + n + close.len()) else {
+            break;
+        };
+        let comment = &source[start..start + length];
+        let preserved = comment[open.len()..].starts_with('!') || comment.contains("@license") || comment.contains("@preserve");
+        if !preserved {
+            break;
+        }
+        end = start + length;
+    }
+    &source[..end]
+}
+
+/// 宽松模式的格式化：多余的闭括号处把输入分段分别格式化，被截断的结尾临时补上闭括号，
+/// 第一处无法越过的错误（未闭合的字符串/注释/标签、不匹配的括号）之后的内容原样输出；返回结果与遇到的错误
+/// Lenient formatting: the input is split at stray closing brackets and the pieces formatted separately, a truncated end
+/// gets temporary closing brackets, and everything after the first error that can't be skipped (unclosed strings/comments/tags,
+/// mismatched brackets) is emitted verbatim; returns the result and the errors encountered
+fn format_lenient(content: &str, file_type: &str, options: &FormatOptions) -> Result<(String, Vec<FormatError>)> {
+    // 超出上限的输入无法安全地格式化，宽松模式也不例外
+    // Input beyond the limits can't be formatted safely, lenient mode included
+    if let Some(error) = limit_error(content, file_type, options) {
+        return Err(error.into());
+    }
+    let mut errors = syntax_errors(content, file_type);
+    if errors.is_empty() {
+        return Ok((format_code(content, file_type, options)?, errors));
+    }
+    errors.sort_by_key(|error| error.span().map_or(0, |span| span.offset));
+    let hard_error = errors
+        .iter()
+        .filter(|error| !matches!(error, FormatError::UnexpectedBracket { .. } | FormatError::UnclosedBracket { .. }))
+        .filter_map(FormatError::span)
+        .map(|span| span.offset)
+        .min();
+    let cut = hard_error.map_or(content.len(), |offset| statement_boundary(content, offset, file_type));
+    let mut breaks: Vec<usize> = errors
+        .iter()
+        .filter_map(|error| match error {
+            FormatError::UnexpectedBracket { span, .. } if span.offset < cut => Some(span.offset),
+            _ => None,
+        })
+        .collect();
+    breaks.push(cut);
+
+    let mut result = String::new();
+    let mut start = 0;
+    for end in breaks {
+        let fragment = content[start..end].trim();
+        if !fragment.is_empty() {
+            match format_fragment(fragment, file_type, options) {
+                Some(formatted) => result.push_str(&formatted),
+                None => result.push_str(fragment),
+            }
+            result.push('\n');
+        }
+        // 多余的闭括号单独成行原样保留
+        // A stray closing bracket is kept verbatim on its own line
+        if end < cut {
+            result.push_str(&content[end..end + 1]);
+            result.push('\n');
+            start = end + 1;
+        }
+    }
+    result.push_str(content[cut..].trim_start_matches('\n'));
+    Ok((finish_output(result, content, license_header(content.trim(), file_type), options), errors))
+}
+
+/// 格式化一个可能缺少闭括号的片段：临时补上闭括号，格式化后再从结果末尾去掉；片段有其他错误时返回 None
+/// Format a fragment that may lack closing brackets: they are appended temporarily and removed from the end of the result;
+/// returns None when the fragment has other errors
+fn format_fragment(fragment: &str, file_type: &str, options: &FormatOptions) -> Option<String> {
+    let mut closers = String::new();
+    for error in syntax_errors(fragment, file_type) {
+        match error {
+            FormatError::UnclosedBracket { open, .. } => closers.insert(0, closing_bracket(open)),
+            _ => return None,
+        }
+    }
+    let mut formatted = format_source(&format!("{}{}", fragment, closers), file_type, options).ok()?;
+    for closer in closers.chars().rev() {
+        let position = formatted.rfind(closer)?;
+        formatted.truncate(position);
+    }
+    Some(formatted.trim_end().to_string())
+}
+
+/// 出错位置之前最近的语句边界（`;`、`{` 或 `}` 之后）；HTML 在出错位置处即可切分
+/// The closest statement boundary before an error (after `;`, `{` or `}`); HTML can be cut at the error itself
+fn statement_boundary(content: &str, offset: usize, file_type: &str) -> usize {
+    if file_type == "html" {
+        return offset;
+    }
+    // CSS 的字符串与注释写法与 JS 相同，这里同样适用
+    // CSS strings and comments are written like JS ones, so the JS tokenizer works here as well
+    let (tokens, offsets) = tokenize_js_with_offsets(&content[..offset]);
+    tokens
+        .iter()
+        .zip(offsets)
+        .rev()
+        .find(|(token, _)| matches!(token, JsToken::Punct(p) if matches!(p.as_str(), ";" | "{" | "}")))
+        .map_or(0, |(_, position)| position + 1)
+}
+
+/// 输出的收尾处理：去掉末尾空行，按选项去除行尾空白并决定是否以换行结尾；输出开头的许可证横幅 `header` 不做改动
+/// 直接在格式化结果的缓冲区上修改，只有确实存在行尾空白时才重新复制一遍
+/// Finish the output: drop trailing blank lines, strip trailing whitespace and end with a newline per the options;
+/// the license header `header` at the start of the output is left untouched
+/// The formatter's buffer is modified in place and only copied again when some line really has trailing whitespace
+fn finish_output(formatted: String, original: &str, header: &str, options: &FormatOptions) -> String {
+    let mut output = formatted;
+    output.truncate(output.trim_end().len());
+    if options.trim_trailing_whitespace {
+        // 横幅原样保留；CSS 的横幅位于开头的 `@charset` 行之后
+        // The banner is kept verbatim; in CSS it follows the leading `@charset` line
+        let charset = match output.split_once('\n') {
+            Some((line, rest)) if !output.starts_with(header) && line.starts_with("@charset") && rest.starts_with(header) => line.len() + 1,
+            _ => 0,
+        };
+        let kept = if output[charset..].starts_with(header) { charset + header.len() } else { 0 };
+        // 格式化器本身不产生行尾空白，只有原样保留的内容（注释、模板字符串等）可能带有
+        // The formatters never produce trailing whitespace themselves, only verbatim content (comments, template
+        // literals and such) may carry it
+        if output[kept..].lines().any(|line| line.ends_with(char::is_whitespace)) {
+            let mut trimmed = String::with_capacity(output.len() + 1);
+            trimmed.push_str(&output[..kept]);
+            for (index, line) in output[kept..].lines().enumerate() {
+                if index > 0 {
+                    trimmed.push('\n');
+                }
+                trimmed.push_str(line.trim_end());
+            }
+            output = trimmed;
+        }
+    }
+    let final_newline = match options.final_newline {
+        FinalNewline::Always => true,
+        FinalNewline::Never => false,
+        FinalNewline::Preserve => original.ends_with('\n'),
+    };
+    if final_newline && !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
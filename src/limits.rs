@@ -0,0 +1,121 @@
+//! 病态输入保护（嵌套层数与单行词法单元数上限）
+//! Pathological input safeguards (limits on nesting depth and tokens per line)
+
+use crate::*;
+
+/// 格式化前检查输入是否超出 --max-nesting / --max-line-tokens，避免输出巨量缩进或在深层递归中耗尽栈；返回第一处超限
+/// Check before formatting whether the input exceeds --max-nesting / --max-line-tokens, so it neither produces huge
+/// amounts of indentation nor exhausts the stack in deep recursion; returns the first violation
+pub(crate) fn limit_error(source: &str, file_type: &str, options: &FormatOptions) -> Option<FormatError> {
+    language(file_type).and_then(|language| language.limit_error(source, options))
+}
+
+/// 按词法单元检查嵌套层数与（`limit_lines` 时）每条语句的词法单元数
+/// Check the nesting depth by tokens and, with `limit_lines`, the number of tokens per statement
+pub(crate) fn token_limit_error(source: &str, options: &FormatOptions, limit_lines: bool) -> Option<FormatError> {
+    // CSS 的字符串与注释写法与 JS 相同，JSON 是 JS 的子集，都可以用 JS 的分词器
+    // CSS strings and comments are written like JS ones and JSON is a subset of JS, so the JS tokenizer works for all
+    let mut depth = 0usize;
+    let mut line_tokens = 0usize;
+    let mut line_start = 0;
+    for (kind, range) in scan_js(source) {
+        let offset = range.start;
+        let punct = match kind {
+            JsKind::Whitespace { .. } | JsKind::LineComment | JsKind::BlockComment => continue,
+            JsKind::Punct => &source[range],
+            _ => "",
+        };
+        match punct {
+            "(" | "[" | "{" => {
+                depth += 1;
+                if depth > options.max_nesting {
+                    return Some(FormatError::NestingTooDeep { limit: options.max_nesting, span: SourceSpan::new(source, offset, 1) });
+                }
+            }
+            ")" | "]" | "}" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        // 语句在 `;`、`{`、`}` 处换行；JSON 每个成员各占一行，不限制
+        // Statements break at `;`, `{` and `}`; JSON puts every member on its own line and isn't limited
+        if !limit_lines || matches!(punct, ";" | "{" | "}") {
+            line_tokens = 0;
+            continue;
+        }
+        if line_tokens == 0 {
+            line_start = offset;
+        }
+        line_tokens += 1;
+        if line_tokens > options.max_line_tokens {
+            return Some(FormatError::TooManyLineTokens { limit: options.max_line_tokens, span: SourceSpan::new(source, line_start, 1) });
+        }
+    }
+    None
+}
+
+/// HTML 块级元素的嵌套超过上限时返回错误（行内元素不增加缩进，原样输出元素的内容不计）
+/// Error when HTML block elements nest deeper than the limit (inline elements add no indentation, the content of raw
+/// text elements isn't counted)
+pub(crate) fn html_nesting_error(source: &str, limit: usize) -> Option<FormatError> {
+    let mut depth = 0usize;
+    let mut position = 0;
+    while let Some(found) = source[position..].find('<') {
+        let offset = position + found;
+        let after = &source[offset + 1..];
+        position = offset + 1;
+        if after.starts_with("!--") {
+            position = after.find("-->").map_or(source.len(), |end| offset + 1 + end + 3);
+            continue;
+        }
+        let name = after
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let mut quote = None;
+        let end = after.char_indices().find(|&(_, c)| match quote {
+            Some(q) if c == q => {
+                quote = None;
+                false
+            }
+            Some(_) => false,
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                false
+            }
+            None => c == '>',
+        });
+        let Some((end, _)) = end else {
+            break;
+        };
+        position = offset + 1 + end + 1;
+        if after.starts_with('/') {
+            if !INLINE_ELEMENTS.contains(&name.as_str()) {
+                depth = depth.saturating_sub(1);
+            }
+            continue;
+        }
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            // 跳到对应的结束标签（不区分大小写）
+            // Skip to the matching end tag (case-insensitively)
+            let close = format!("</{}", name);
+            position = (position..source.len())
+                .filter(|&i| source.as_bytes()[i] == b'<')
+                .find(|&i| source.get(i..i + close.len()).is_some_and(|tag| tag.eq_ignore_ascii_case(&close)))
+                .unwrap_or(source.len());
+            continue;
+        }
+        let self_closing = after[..end].trim_end().ends_with('/');
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) || INLINE_ELEMENTS.contains(&name.as_str()) {
+            continue;
+        }
+        depth += 1;
+        if depth > limit {
+            return Some(FormatError::NestingTooDeep { limit, span: SourceSpan::new(source, offset, 1) });
+        }
+    }
+    None
+}
@@ -0,0 +1,130 @@
+//! 日志
+//! Logging
+
+use std::io::IsTerminal;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use crate::*;
+
+/// 日志级别：每一级包含之前各级的输出
+/// Log level: each level includes the output of the levels before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LogLevel {
+    /// 只输出错误（-q）
+    /// Errors only (-q)
+    Error,
+    /// 默认：警告与每个文件的结果
+    /// Default: warnings and the result of every file
+    Normal,
+    /// 每个文件的详细信息（-v）
+    /// Per-file details (-v)
+    Verbose,
+    /// 格式化器的 token 与状态跟踪（-vv）
+    /// Token and state tracing of the formatters (-vv)
+    Trace,
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Normal as u8);
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+pub(crate) static TRACE_TO_STDOUT: AtomicBool = AtomicBool::new(false);
+
+/// 设置日志级别；`stdout_reserved` 为 true 时标准输出用于报告或格式化结果，日志改写到标准错误
+/// Set the log level; when `stdout_reserved` is set stdout carries a report or the formatted result, so logs go to stderr
+pub(crate) fn init_logging(level: LogLevel, stdout_reserved: bool) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+    LOG_TO_STDERR.store(stdout_reserved, Ordering::Relaxed);
+}
+
+/// 某个级别的日志是否输出（用于跳过代价较高的跟踪信息）
+/// Whether messages of a level are printed (used to skip building expensive trace output)
+pub(crate) fn log_enabled(level: LogLevel) -> bool {
+    level as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// 按级别输出一行日志；跟踪信息写到标准错误（debug-tokens 子命令时写到标准输出）
+/// Print one log line at a level; trace output goes to stderr (to stdout for the debug-tokens subcommand)
+pub(crate) fn log(level: LogLevel, message: impl fmt::Display) {
+    if !log_enabled(level) {
+        return;
+    }
+    if level == LogLevel::Trace && TRACE_TO_STDOUT.load(Ordering::Relaxed) {
+        println!("[TRACE] {}", message);
+    } else if level == LogLevel::Trace {
+        eprintln!("[TRACE] {}", message);
+    } else if LOG_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// 标准错误是否使用颜色：是终端且未设置 NO_COLOR
+/// Whether stderr uses colors: it is a terminal and NO_COLOR is not set
+fn stderr_color() -> bool {
+    static COLOR: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *COLOR.get_or_init(|| std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none())
+}
+
+/// 按 ANSI 样式（如 "1;31"）着色，不使用颜色时原样返回
+/// Color text with an ANSI style (e.g. "1;31"), returned unchanged when colors are off
+fn paint(text: &str, style: &str) -> String {
+    if stderr_color() {
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 以源码摘录的形式渲染一条诊断：消息、位置、出错行、指向出错处的插入符与修复建议
+/// Render a diagnostic with a source excerpt: the message, location, offending line, carets under the problem and a hint
+pub(crate) fn render_diagnostic(severity: Severity, message: &str, path: &str, span: &SourceSpan, hint: &str) -> String {
+    let (label, style) = match severity {
+        Severity::Error => (tr!("错误：", "error: "), "1;31"),
+        Severity::Warning => (tr!("警告：", "warning: "), "1;33"),
+    };
+    let excerpt: Vec<char> = span.excerpt.chars().map(|c| if c == '\t' { ' ' } else { c }).collect();
+    let prefix: String = excerpt[..span.excerpt_column].iter().collect();
+    let rest: String = excerpt[span.excerpt_column..].iter().collect();
+    let marked = span.snippet.chars().count().clamp(1, rest.chars().count().max(1));
+    let carets = display_width(&rest.chars().take(marked).collect::<String>()).max(1);
+
+    let line_number = span.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+    let bar = paint("|", "1;34");
+    format!(
+        "{}{}\n{}{} {}:{}:{}\n{} {}\n{} {} {}\n{} {} {}{}\n{} {} {}{}",
+        paint(&label, style),
+        paint(message, "1"),
+        gutter,
+        paint("-->", "1;34"),
+        path,
+        span.line,
+        span.column,
+        gutter,
+        bar,
+        paint(&line_number, "1;34"),
+        bar,
+        excerpt.iter().collect::<String>(),
+        gutter,
+        bar,
+        " ".repeat(display_width(&prefix)),
+        paint(&"^".repeat(carets), style),
+        gutter,
+        paint("=", "1;34"),
+        paint(&tr!("提示：", "help: "), "1"),
+        hint
+    )
+}
+
+/// 渲染处理某个文件时的错误：带位置的格式化错误显示源码摘录，其余错误显示错误链
+/// Render an error raised while processing a file: formatting errors with a location show a source excerpt, others show the error chain
+pub(crate) fn render_error(err: &anyhow::Error, path: &str) -> String {
+    let located = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<FormatError>())
+        .and_then(|error| error.span().map(|span| (error, span)));
+    match located {
+        Some((error, span)) => render_diagnostic(Severity::Error, &format!("{:#}", err), path, span, &error.hint()),
+        None => tr!("错误：{}：{:?}", "Error: {}: {:?}", path, err),
+    }
+}
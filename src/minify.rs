@@ -0,0 +1,420 @@
+//! 压缩（格式化的逆操作）
+//! Minification (the reverse of formatting)
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+use crate::*;
+
+/// 是否为需要保留的注释（`/*!`、`@license`、`@preserve`）
+/// Whether a comment must be kept (`/*!`, `@license`, `@preserve`)
+fn is_preserved_comment(comment: &str) -> bool {
+    comment.starts_with("/*!") || comment.contains("@license") || comment.contains("@preserve")
+}
+
+/// 压缩 CSS 前导：去掉逗号、组合符两侧及 at-rule 括号内 `:` 后的空格
+/// Minify a CSS prelude: drop spaces around commas and combinators and after `:` in at-rule parentheses
+fn minify_css_prelude(prelude: &str) -> String {
+    let formatted = format_css_prelude(prelude);
+    let is_at_rule = formatted.starts_with('@');
+    let mut result = String::new();
+    let mut chars = formatted.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                result.push(c);
+                take_css_string(c, &mut chars, &mut result);
+            }
+            '(' if ends_with_url(&result) => {
+                result.push(c);
+                take_css_url_body(&mut chars, &mut result);
+            }
+            ' ' => {
+                let next = chars.peek().copied();
+                let around_symbol = matches!(result.chars().last(), Some(',' | ':'))
+                    || next == Some(',')
+                    || (!is_at_rule && (matches!(result.chars().last(), Some('>' | '+' | '~')) || matches!(next, Some('>' | '+' | '~'))));
+                if !around_symbol {
+                    result.push(c);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// 去掉属性值中（引号外）的注释
+/// Remove the comments (outside quotes) from a CSS value
+fn strip_css_value_comments(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut quote = None;
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        match quote {
+            None if rest.starts_with("/*") => {
+                rest = rest.find("*/").map_or("", |end| &rest[end + 2..]);
+                if !result.ends_with(' ') {
+                    result.push(' ');
+                }
+                continue;
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            Some(q) if c == q => quote = None,
+            _ => {}
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result.trim().to_string()
+}
+
+/// 压缩 CSS 属性值：逗号两侧不留空格，去掉注释
+/// Minify a CSS value: no spaces around commas, comments removed
+fn minify_css_value(value: &str) -> String {
+    let value = strip_css_value_comments(value);
+    let tokens = normalize_css_value_spacing(tokenize_css_value(&value));
+    let mut result: Vec<CssValueToken> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        if token == CssValueToken::Space && result.last() == Some(&CssValueToken::Comma) {
+            continue;
+        }
+        result.push(token);
+    }
+    join_css_value(&result)
+}
+
+/// 紧凑输出 CSS 节点
+/// Emit CSS nodes compactly
+fn emit_minified_css(nodes: &[CssNode], result: &mut String) {
+    for (i, node) in nodes.iter().enumerate() {
+        match node {
+            CssNode::Comment(comment) | CssNode::TrailingComment(comment) if is_preserved_comment(comment) => result.push_str(comment),
+            CssNode::Comment(_) | CssNode::TrailingComment(_) => {}
+            CssNode::Statement(text) => {
+                result.push_str(&minify_css_prelude(text));
+                result.push(';');
+            }
+            CssNode::Declaration { property, value } => {
+                let (value, important) = if property.starts_with("--") { (value.as_str(), false) } else { split_important(value) };
+                result.push_str(property);
+                result.push(':');
+                result.push_str(&minify_css_value(value));
+                if important {
+                    result.push_str("!important");
+                }
+                // 块内最后一条声明不需要分号
+                // The last declaration in a block needs no semicolon
+                if nodes[i + 1..].iter().any(|n| !matches!(n, CssNode::Comment(_) | CssNode::TrailingComment(_))) {
+                    result.push(';');
+                }
+            }
+            CssNode::Block { prelude, children } => {
+                result.push_str(&minify_css_prelude(prelude));
+                result.push('{');
+                emit_minified_css(children, result);
+                result.push('}');
+            }
+        }
+    }
+}
+
+pub(crate) fn minify_css(content: &str) -> Result<String> {
+    let mut nodes = parse_css_nodes(&mut content.chars().peekable(), 0);
+    // `@charset` 必须是文件的第一条内容，保留的横幅注释移到它之后
+    // `@charset` must be the very first thing in the file, so preserved banner comments move after it
+    if let Some(index) = nodes.iter().position(|node| !matches!(node, CssNode::Comment(_) | CssNode::TrailingComment(_))) {
+        if matches!(&nodes[index], CssNode::Statement(text) if text.get(..8).is_some_and(|start| start.eq_ignore_ascii_case("@charset"))) {
+            let charset = nodes.remove(index);
+            nodes.insert(0, charset);
+        }
+    }
+    let mut result = String::new();
+    emit_minified_css(&nodes, &mut result);
+    Ok(result)
+}
+
+/// 紧凑输出标签：属性之间一个空格，`=` 两侧无空格
+/// Emit a tag compactly: one space between attributes, no spaces around `=`
+fn minify_html_tag(raw: &str) -> String {
+    let inner = raw.trim_start_matches('<').trim_end_matches('>');
+    if let Some(end_name) = inner.strip_prefix('/') {
+        return format!("</{}>", end_name.trim());
+    }
+    let name: String = inner.chars().take_while(|c| !c.is_whitespace() && *c != '/').collect();
+    let mut tag = format!("<{}", name);
+    for attribute in parse_html_attributes(raw) {
+        tag.push(' ');
+        tag.push_str(&attribute.name);
+        if let Some(value) = &attribute.value {
+            tag.push('=');
+            match attribute.quote {
+                Some(q) => {
+                    tag.push(q);
+                    tag.push_str(value);
+                    tag.push(q);
+                }
+                None => tag.push_str(value),
+            }
+        }
+    }
+    if inner.trim_end().ends_with('/') {
+        tag.push('/');
+    }
+    tag.push('>');
+    tag
+}
+
+pub(crate) fn minify_html(content: &str) -> Result<String> {
+    let mut result = String::new();
+    let mut pending_space = false;
+    // 上一个输出的内容是否为块级边界（其后的空白可以省略）
+    // Whether the last emitted item was a block boundary (whitespace after it can be dropped)
+    let mut after_block = true;
+    let mut tokens = tokenize_html(content).into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let (text, is_block) = match token {
+            HtmlToken::Text(text) => {
+                let mut collapsed = String::new();
+                for c in text.chars() {
+                    if c.is_whitespace() {
+                        pending_space = true;
+                    } else {
+                        if pending_space && !after_block {
+                            collapsed.push(' ');
+                        }
+                        pending_space = false;
+                        after_block = false;
+                        collapsed.push(c);
+                    }
+                }
+                result.push_str(&collapsed);
+                continue;
+            }
+            HtmlToken::Comment(comment) => {
+                // 条件注释与保留注释不能删除
+                // Conditional and preserved comments must not be removed
+                let body = comment.trim_start_matches("<!--");
+                if body.starts_with("[if") || body.starts_with("<![endif]") || body.starts_with('!') {
+                    (comment, true)
+                } else {
+                    continue;
+                }
+            }
+            HtmlToken::Declaration(decl) => (decl, true),
+            HtmlToken::StartTag { name, raw, self_closing } => {
+                let tag = minify_html_tag(&raw);
+                if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+                    let content = match tokens.next_if(|t| matches!(t, HtmlToken::RawText(_))) {
+                        Some(HtmlToken::RawText(text)) => text,
+                        _ => String::new(),
+                    };
+                    // JSON 数据脚本可以安全压缩，其他脚本只去掉首尾空白，未知类型原样保留
+                    // JSON payload scripts can be minified safely, other scripts only lose surrounding whitespace, and
+                    // unknown types are kept verbatim
+                    let body = match name.as_str() {
+                        "style" => minify_css(&content)?,
+                        "script" => match script_kind(&raw) {
+                            ScriptKind::Js => content.trim().to_string(),
+                            ScriptKind::Json => minify_json(&content).unwrap_or_else(|_| content.trim().to_string()),
+                            ScriptKind::Verbatim => content,
+                        },
+                        _ => content,
+                    };
+                    (format!("{}{}", tag, body), !INLINE_ELEMENTS.contains(&name.as_str()))
+                } else {
+                    (tag, !INLINE_ELEMENTS.contains(&name.as_str()))
+                }
+            }
+            HtmlToken::EndTag { name, raw } => (minify_html_tag(&raw), !INLINE_ELEMENTS.contains(&name.as_str())),
+            HtmlToken::RawText(text) => (text, false),
+        };
+        if pending_space && !after_block && !is_block {
+            result.push(' ');
+        }
+        pending_space = false;
+        result.push_str(&text);
+        after_block = is_block;
+    }
+    Ok(result)
+}
+
+/// 两个相邻词法单元之间是否必须保留换行（避免改变自动分号插入的结果）；`#`（私有成员）与 `@`（装饰器）不能接在表达式之后，
+/// 总是开始新的语句或成员，类的块体中 `*` 开始生成器方法
+/// Whether a line break between two tokens must be kept (so automatic semicolon insertion is unchanged); `#` (private
+/// members) and `@` (decorators) can't continue an expression and always start a new statement or member, and in a class
+/// body `*` starts a generator method
+pub(crate) fn js_newline_required(previous: &JsToken, next: &JsToken, class_body: bool) -> bool {
+    let restricted = matches!(previous, JsToken::Word(w) if ["return", "break", "continue", "throw", "yield"].contains(&w.as_str()));
+    let next_is_update = matches!(next, JsToken::Punct(p) if p == "++" || p == "--");
+    let previous_can_end = match previous {
+        JsToken::Word(_) | JsToken::Str(_) | JsToken::Template(_) | JsToken::Regex(_) => true,
+        JsToken::Punct(p) => matches!(p.as_str(), ")" | "]" | "}" | "++" | "--"),
+        _ => false,
+    };
+    let next_can_start = match next {
+        JsToken::Word(_) | JsToken::Str(_) | JsToken::Template(_) | JsToken::Regex(_) => true,
+        JsToken::Punct(p) => matches!(p.as_str(), "{" | "!" | "~" | "#" | "@") || (class_body && p == "*"),
+        _ => false,
+    };
+    restricted || next_is_update || (previous_can_end && next_can_start)
+}
+
+/// 两个相邻词法单元之间是否需要空格才能不被粘连
+/// Whether two adjacent tokens need a space to stay separate
+fn js_space_required(previous: &str, next: &str) -> bool {
+    let (Some(last), Some(first)) = (previous.chars().last(), next.chars().next()) else {
+        return false;
+    };
+    (is_js_word_char(last) && is_js_word_char(first))
+        || (last == '+' && first == '+')
+        || (last == '-' && first == '-')
+        || (last == '/' && (first == '/' || first == '*'))
+        || (previous.chars().all(|c| c.is_ascii_digit()) && first == '.')
+}
+
+pub(crate) fn minify_js_ts(content: &str) -> Result<String> {
+    let mut result = String::new();
+    // `#!` 行只能出现在文件开头，原样保留并单独成行
+    // A `#!` line may only appear at the very start of the file, it is kept verbatim on its own line
+    let mut content = content;
+    if content.starts_with("#!") {
+        let end = content.find('\n').unwrap_or(content.len());
+        result.push_str(&content[..end]);
+        result.push('\n');
+        content = &content[end..];
+    }
+    // 上一个有效词法单元（决定换行是否关系到自动分号插入）与上一段输出的文本（决定是否需要空格）；
+    // 保留的注释只改变后者，因此注释前后的换行判断与没有注释时相同
+    // The last significant token (deciding whether a line break matters to automatic semicolon insertion) and the last
+    // text written (deciding whether a space is needed); preserved comments only change the latter, so the line break
+    // decision around them is the same as without them
+    let mut previous: Option<JsToken> = None;
+    let mut previous_text = String::new();
+    let mut saw_newline = false;
+    // 保留的多行注释本身已在输出中带来换行
+    // A preserved multi-line comment already puts a line break into the output
+    let mut newline_written = false;
+
+    for token in tokenize_js(content) {
+        match &token {
+            JsToken::Whitespace { newline } => {
+                saw_newline |= *newline;
+                continue;
+            }
+            JsToken::LineComment(_) => {
+                saw_newline = true;
+                continue;
+            }
+            JsToken::BlockComment(comment) if !is_preserved_comment(comment) => {
+                saw_newline |= comment.contains('\n');
+                continue;
+            }
+            JsToken::BlockComment(comment) => {
+                if js_space_required(&previous_text, comment) {
+                    result.push(' ');
+                }
+                result.push_str(comment);
+                newline_written |= comment.contains('\n');
+                previous_text.clone_from(comment);
+                continue;
+            }
+            _ => {}
+        }
+        let text = js_token_text(&token);
+        let newline = saw_newline && !newline_written && previous.as_ref().is_some_and(|prev| js_newline_required(prev, &token, false));
+        if newline {
+            result.push('\n');
+        } else if js_space_required(&previous_text, text) {
+            result.push(' ');
+        }
+        result.push_str(text);
+        saw_newline = false;
+        newline_written = false;
+        previous_text = text.to_string();
+        previous = Some(token);
+    }
+    Ok(result)
+}
+
+/// 统一压缩入口
+/// Unified minification entry point
+fn minify_code(content: &str, file_type: &str) -> Result<String> {
+    language(file_type).ok_or_else(|| unsupported_type(file_type))?.minify(content)
+}
+
+/// 写入输出：`-` 表示标准输出
+/// Write the output: `-` means stdout
+pub(crate) fn write_output(path: &str, bytes: &[u8]) -> Result<()> {
+    if path == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(bytes).and_then(|_| stdout.flush()).with_context(|| tr!("无法写入标准输出", "Cannot write to stdout"))
+    } else {
+        write_atomic(Path::new(path), bytes).with_context(|| tr!("无法写入输出文件：{}", "Cannot write output file: {}", path))
+    }
+}
+
+/// 原子写入：先写入同目录下的临时文件并落盘，再重命名覆盖目标，保留目标原有的权限；
+/// 目标为符号链接时写入链接指向的文件
+/// Atomic write: write and sync a temporary file in the same directory, then rename it over the target, keeping the
+/// target's permissions; a symlinked target writes through to the file it points at
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let is_symlink = std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink());
+    let target = if is_symlink { std::fs::canonicalize(path)? } else { path.to_path_buf() };
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        if let Ok(meta) = std::fs::metadata(&target) {
+            std::fs::set_permissions(&temp, meta.permissions())?;
+        }
+        std::fs::rename(&temp, &target)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// 覆盖前备份已存在的目标文件，返回备份路径（目标不存在或为标准输出时不备份）
+/// Back up an existing target file before it is overwritten, returning the backup path (nothing for a missing target or stdout)
+pub(crate) fn backup_file(path: &str, suffix: &str) -> Result<Option<String>> {
+    if path == "-" || !Path::new(path).is_file() {
+        return Ok(None);
+    }
+    let backup = format!("{}{}", path, suffix);
+    std::fs::copy(path, &backup).with_context(|| tr!("无法备份文件：{} → {}", "Cannot back up file: {} → {}", path, backup))?;
+    Ok(Some(backup))
+}
+
+/// 压缩子命令
+/// Minify subcommand
+pub(crate) fn run_minify(input: &str, output: &str, type_hint: Option<&str>, config: &Config) -> Result<()> {
+    let (raw_content, encoding) = read_source(input, TextEncoding::Auto)?;
+    // BOM 不参与压缩，输入有 BOM 时原样保留
+    // The BOM is kept out of minification and re-emitted when the input has one
+    let (has_bom, text) = strip_bom(&raw_content);
+    let content = text.replace("\r", "");
+
+    let file_type = resolve_file_type(input, type_hint, &config.extensions, &content)?;
+    log(LogLevel::Verbose, tr!("[INFO] 压缩 {} 文件", "[INFO] Minifying {} file", file_type));
+
+    let mut minified_content = minify_code(content.trim(), file_type)
+        .with_context(|| tr!("代码压缩失败", "Minification failed"))?;
+    if has_bom {
+        minified_content.insert(0, UTF8_BOM);
+    }
+
+    write_output(output, &encode_text(&minified_content, encoding)?)?;
+
+    log(LogLevel::Normal, tr!("[SUCCESS] 压缩完成！输出文件：{}", "[SUCCESS] Minified! Output file: {}", output));
+    Ok(())
+}
@@ -0,0 +1,321 @@
+//! 格式化选项
+//! Formatting options
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use crate::*;
+
+/// 文件末尾换行的处理方式
+/// How the final newline of a file is handled
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FinalNewline {
+    /// 始终以一个换行结尾
+    /// Always end with exactly one newline
+    Always,
+    /// 不以换行结尾
+    /// Never end with a newline
+    Never,
+    /// 与输入保持一致
+    /// Same as the input
+    Preserve,
+}
+
+/// 源文件的文本编码
+/// Text encoding of source files
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextEncoding {
+    /// 自动检测：UTF-8、文件声明的编码、GB18030，最后回退到 Latin-1
+    /// Detect automatically: UTF-8, the declared encoding, GB18030, falling back to Latin-1
+    Auto,
+    #[value(name = "utf-8")]
+    Utf8,
+    Gbk,
+    Gb18030,
+    Latin1,
+}
+
+impl TextEncoding {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Auto => "auto",
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Gbk => "GBK",
+            TextEncoding::Gb18030 => "GB18030",
+            TextEncoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// 输出文件的 UTF-8 BOM 策略
+/// UTF-8 BOM policy of the output file
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BomPolicy {
+    /// 输入有 BOM 时保留
+    /// Keep the BOM when the input has one
+    Keep,
+    /// 总是去掉
+    /// Always strip it
+    Strip,
+    /// 总是添加
+    /// Always add it
+    Add,
+}
+
+/// 缩进使用的字符
+/// Character used for indentation
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IndentStyle {
+    /// 每级缩进一个 tab
+    /// One tab per indentation level
+    Tabs,
+    /// 每级缩进 `--indent` 个空格
+    /// `--indent` spaces per indentation level
+    Spaces,
+}
+
+/// CSS 小数前导零的写法
+/// How CSS decimals write their leading zero
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LeadingZero {
+    /// 补全前导零：`.5em` → `0.5em`
+    /// Add the leading zero: `.5em` → `0.5em`
+    Add,
+    /// 去掉前导零：`0.5em` → `.5em`
+    /// Remove the leading zero: `0.5em` → `.5em`
+    Remove,
+    /// 保持原样
+    /// Keep as written
+    Preserve,
+}
+
+/// CSS 规则何时压缩为单行
+/// When CSS rules are compacted onto a single line
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CssSingleLineRules {
+    /// 每条声明始终独占一行
+    /// Always one declaration per line
+    Never,
+    /// 不超过 3 条声明且不超过行宽时压缩
+    /// Compact when there are at most 3 declarations and the rule fits the line length
+    Auto,
+    /// 只要不超过行宽就压缩
+    /// Compact whenever the rule fits the line length
+    Always,
+}
+
+/// JS/TS 中 else/catch/finally 相对于前一个 `}` 的位置
+/// Placement of else/catch/finally relative to the preceding `}` in JS/TS
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ElsePlacement {
+    /// 与 `}` 同行：`} else {`
+    /// On the same line as `}`: `} else {`
+    SameLine,
+    /// 在 `}` 的下一行
+    /// On the line after `}`
+    NextLine,
+}
+
+/// HTML 属性值的引号
+/// Quotes of HTML attribute values
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttrQuotes {
+    /// 双引号
+    /// Double quotes
+    Double,
+    /// 单引号
+    /// Single quotes
+    Single,
+    /// 保持原样
+    /// Keep as written
+    Preserve,
+}
+
+/// HTML 文本节点的折行方式
+/// How text nodes in HTML are wrapped
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProseWrap {
+    /// 超出行宽时在空白处折行；没有空白的 CJK 文本保持整段，因为其中的换行会被浏览器渲染为空格
+    /// Wrap at whitespace once the line length is exceeded; CJK runs without whitespace stay whole because a line
+    /// break inside them renders as a space in browsers
+    Always,
+    /// 从不折行，文本中的换行折叠为空格
+    /// Never wrap, line breaks in text collapse to spaces
+    Never,
+    /// 保留原文中的换行，不再另外折行
+    /// Keep the author's line breaks and add none
+    Preserve,
+}
+
+/// CSS 声明排序方式
+/// CSS declaration sort order
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortProperties {
+    /// 按属性名字母序
+    /// Alphabetical by property name
+    Alphabetical,
+    /// 由外到内（Concentric CSS）：布局、盒模型、背景、尺寸、文本
+    /// Outside-in (Concentric CSS): layout, box model, background, dimensions, text
+    Concentric,
+    /// 保持原有顺序
+    /// Keep the original order
+    None,
+}
+
+/// JSON 对象键的排序方式
+/// JSON object key order
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortKeys {
+    /// 所有对象的键按字母序
+    /// Keys of every object in alphabetical order
+    Alphabetical,
+    /// package.json 顶层字段按惯用顺序（name、version、scripts、dependencies……），依赖表按字母序
+    /// Top-level package.json fields in the conventional order (name, version, scripts, dependencies...), dependency maps alphabetical
+    PackageJson,
+    /// 保持原有顺序
+    /// Keep the original order
+    None,
+}
+
+/// 格式化选项（由命令行参数生成）
+/// Formatting options (built from command line arguments)
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// 指定的代码类型或用于推断类型的文件名
+    /// Code type override, or a file name to infer the type from
+    pub(crate) file_type: Option<String>,
+    /// 配置文件中的自定义扩展名映射
+    /// Custom extension mapping from the config file
+    pub(crate) extensions: Vec<(String, &'static str)>,
+    pub(crate) indent: u8,
+    pub(crate) indent_style: IndentStyle,
+    pub(crate) final_newline: FinalNewline,
+    pub(crate) trim_trailing_whitespace: bool,
+    pub(crate) encoding: TextEncoding,
+    pub(crate) bom: BomPolicy,
+    /// 覆盖已有文件前备份所用的后缀
+    /// Suffix of the backup taken before overwriting an existing file
+    pub(crate) backup: Option<String>,
+    /// 配置文件中的外部格式化器（文件名模式 → 命令）
+    /// External formatters from the config file (file name pattern → command)
+    pub(crate) external: Vec<(String, String)>,
+    /// 配置文件中按代码类型覆盖的设置（已去掉命令行显式给出的选项）
+    /// Per code type settings from the config file (without the options given explicitly on the command line)
+    pub(crate) overrides: Vec<(&'static str, Vec<Setting>)>,
+    pub(crate) line_length: usize,
+    pub(crate) normalize_doctype: bool,
+    pub(crate) attr_quotes: AttrQuotes,
+    pub(crate) wrap_comments: bool,
+    pub(crate) prose_wrap: ProseWrap,
+    pub(crate) indent_root_elements: bool,
+    pub(crate) format_event_handlers: bool,
+    pub(crate) sort_properties: SortProperties,
+    pub(crate) sort_keys: SortKeys,
+    pub(crate) css_single_line_rules: CssSingleLineRules,
+    pub(crate) normalize_colors: bool,
+    pub(crate) short_hex_colors: bool,
+    pub(crate) leading_zero: LeadingZero,
+    pub(crate) strip_zero_units: bool,
+    pub(crate) preserve_data_uris: bool,
+    pub(crate) split_selectors: bool,
+    pub(crate) else_placement: ElsePlacement,
+    pub(crate) indent_case_labels: bool,
+    /// 输入有结构错误时尽量格式化
+    /// Format malformed input on a best-effort basis
+    pub(crate) lenient: bool,
+    pub(crate) max_nesting: usize,
+    pub(crate) max_line_tokens: usize,
+    /// 再格式化一次以校验结果稳定
+    /// Format once more to verify the result is stable
+    pub(crate) verify_stable: bool,
+}
+
+impl From<&Cli> for FormatOptions {
+    fn from(cli: &Cli) -> Self {
+        FormatOptions {
+            file_type: cli.file_type.clone(),
+            extensions: Vec::new(),
+            external: Vec::new(),
+            overrides: Vec::new(),
+            indent: cli.indent,
+            indent_style: cli.indent_style,
+            final_newline: cli.final_newline,
+            trim_trailing_whitespace: cli.trim_trailing_whitespace,
+            encoding: cli.encoding,
+            bom: cli.bom,
+            backup: cli.backup.clone(),
+            line_length: cli.line_length,
+            normalize_doctype: cli.normalize_doctype,
+            attr_quotes: cli.attr_quotes,
+            wrap_comments: cli.wrap_comments,
+            prose_wrap: cli.prose_wrap,
+            indent_root_elements: cli.indent_root_elements,
+            format_event_handlers: cli.format_event_handlers,
+            sort_properties: cli.sort_properties,
+            sort_keys: cli.sort_keys,
+            css_single_line_rules: cli.css_single_line_rules,
+            normalize_colors: cli.normalize_colors,
+            short_hex_colors: cli.short_hex_colors,
+            leading_zero: cli.leading_zero,
+            strip_zero_units: cli.strip_zero_units,
+            preserve_data_uris: cli.preserve_data_uris,
+            split_selectors: cli.split_selectors,
+            else_placement: cli.else_placement,
+            indent_case_labels: cli.indent_case_labels,
+            lenient: cli.lenient,
+            max_nesting: cli.max_nesting,
+            max_line_tokens: cli.max_line_tokens,
+            verify_stable: cli.verify_stable,
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    /// 命令行的默认选项
+    /// The command line defaults
+    fn default() -> Self {
+        let cli = Cli::try_parse_from(["code_formatter", "-i", "-", "-o", "-"]).expect("default arguments are valid");
+        FormatOptions::from(&cli)
+    }
+}
+
+impl FormatOptions {
+    /// 按配置文件的键名与取值修改一项设置（如 `set("indent", "4")`、`set("indent_style", "tabs")`）；
+    /// 取值按配置文件的写法解析，不是整数、布尔值或带引号的字符串时当作字符串
+    /// Change one setting by its config file key and value (such as `set("indent", "4")` or `set("indent_style", "tabs")`);
+    /// the value is read like in the config file, anything that is not an integer, boolean or quoted string counts as a string
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = match parse_config_value(value.trim()) {
+            Some((value, rest)) if rest.trim().is_empty() => value,
+            _ => ConfigValue::Str(value.trim().to_string()),
+        };
+        Setting::parse(key, &value).map_err(ConfigError)?.apply(self);
+        Ok(())
+    }
+
+    /// 一级缩进的文本
+    /// Text of one indentation level
+    pub(crate) fn indent_unit(&self) -> String {
+        match self.indent_style {
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Spaces => " ".repeat(self.indent as usize),
+        }
+    }
+
+    /// 某种代码类型实际使用的选项：应用配置文件中该类型的覆盖设置
+    /// The options actually used for a code type: the config file's overrides for that type applied
+    pub(crate) fn for_type(&self, file_type: &str) -> FormatOptions {
+        let mut options = self.clone();
+        for (_, settings) in self.overrides.iter().filter(|(overridden, _)| *overridden == file_type) {
+            for setting in settings {
+                setting.apply(&mut options);
+            }
+        }
+        options
+    }
+
+    /// 一级缩进占用的列数（计算行长时使用）
+    /// Columns taken by one indentation level (used when measuring line length)
+    pub(crate) fn indent_width(&self) -> usize {
+        self.indent as usize
+    }
+}
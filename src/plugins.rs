@@ -0,0 +1,151 @@
+//! WASM 插件
+//! WASM plugins
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use crate::*;
+
+/// 从插件目录加载的 WASM 格式化器（需启用 `plugins` 特性）。插件模块需导出：
+/// - `memory`：线性内存
+/// - `alloc(len: i32) -> i32`：分配 `len` 字节，宿主把输入写入其中
+/// - `language() -> i64`：语言描述，内容为以空白分隔的类型名与扩展名（如 `toml toml`）
+/// - `format(ptr: i32, len: i32, indent: i32, use_tabs: i32, line_length: i32) -> i32`：格式化，返回 0 表示成功
+/// - `output() -> i64`：上一次调用的结果，成功时为格式化结果，失败时为错误信息
+/// - 可选的 `minify(ptr: i32, len: i32) -> i32`：压缩，约定与 `format` 相同
+///
+/// 返回文本的函数把位置打包为 `(指针 << 32) | 长度`，文本均为 UTF-8；宿主从不释放插件的内存，插件可在两次调用之间复用缓冲区
+/// A WASM formatter loaded from the plugins directory (requires the `plugins` feature). The plugin module must export:
+/// - `memory`: its linear memory
+/// - `alloc(len: i32) -> i32`: reserve `len` bytes for the host to write the input into
+/// - `language() -> i64`: the language description, the type name and extensions separated by whitespace (such as
+///   `toml toml`)
+/// - `format(ptr: i32, len: i32, indent: i32, use_tabs: i32, line_length: i32) -> i32`: format, 0 means success
+/// - `output() -> i64`: the result of the previous call, the formatted text on success and the error message on failure
+/// - optionally `minify(ptr: i32, len: i32) -> i32`: minify, with the same conventions as `format`
+///
+/// Functions returning text pack its location as `(pointer << 32) | length`, all text is UTF-8; the host never frees
+/// plugin memory, a plugin may reuse its buffers between calls
+pub(crate) struct WasmPlugin {
+    /// 插件文件路径
+    /// Path of the plugin file
+    pub(crate) path: String,
+    /// 插件实例的状态；格式化调用依次进行
+    /// State of the plugin instance; formatting calls take turns
+    store: std::sync::Mutex<wasmi::Store<()>>,
+    memory: wasmi::Memory,
+    alloc: wasmi::TypedFunc<i32, i32>,
+    format: wasmi::TypedFunc<(i32, i32, i32, i32, i32), i32>,
+    minify: Option<wasmi::TypedFunc<(i32, i32), i32>>,
+    output: wasmi::TypedFunc<(), i64>,
+}
+
+impl WasmPlugin {
+    /// 加载并实例化插件，返回插件与它描述的类型名、扩展名
+    /// Load and instantiate a plugin, returning it with the type name and extensions it describes
+    fn load(path: &Path) -> Result<(WasmPlugin, String, Vec<String>)> {
+        let invalid = |message: String| ConfigError(tr!("无效的插件 {}：{}", "Invalid plugin {}: {}", path.display(), message));
+        let bytes = std::fs::read(path).with_context(|| tr!("无法读取插件：{}", "Cannot read plugin: {}", path.display()))?;
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, &bytes).map_err(|err| invalid(err.to_string()))?;
+        let mut store = wasmi::Store::new(&engine, ());
+        let instance = wasmi::Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|err| invalid(err.to_string()))?;
+        let missing = |name: &str| invalid(tr!("缺少导出 `{}` 或其签名不符", "export `{}` is missing or has the wrong signature", name));
+        let memory = instance.get_memory(&store, "memory").ok_or_else(|| missing("memory"))?;
+        let language = instance.get_typed_func::<(), i64>(&store, "language").map_err(|_| missing("language"))?;
+        let plugin = WasmPlugin {
+            path: path.display().to_string(),
+            memory,
+            alloc: instance.get_typed_func(&store, "alloc").map_err(|_| missing("alloc"))?,
+            format: instance.get_typed_func(&store, "format").map_err(|_| missing("format"))?,
+            minify: instance.get_typed_func(&store, "minify").ok(),
+            output: instance.get_typed_func(&store, "output").map_err(|_| missing("output"))?,
+            store: std::sync::Mutex::new(store),
+        };
+        let description = {
+            let mut store = plugin.store.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            let packed = language.call(&mut *store, ()).map_err(|err| invalid(err.to_string()))?;
+            plugin.read_text(&store, packed).ok_or_else(|| missing("language"))?
+        };
+        let mut words = description.split_whitespace();
+        let name = words.next().ok_or_else(|| invalid(tr!("语言描述中没有类型名", "the language description has no type name")))?.to_lowercase();
+        let extensions = words.map(|extension| extension.trim_start_matches('.').to_lowercase()).collect();
+        Ok((plugin, name, extensions))
+    }
+
+    /// 插件是否支持压缩
+    /// Whether the plugin supports minification
+    pub(crate) fn can_minify(&self) -> bool {
+        self.minify.is_some()
+    }
+
+    pub(crate) fn format(&self, source: &str, options: &FormatOptions) -> Result<String> {
+        let indent = i32::from(options.indent);
+        let use_tabs = i32::from(options.indent_style == IndentStyle::Tabs);
+        let line_length = i32::try_from(options.line_length).unwrap_or(i32::MAX);
+        self.call(source, |store, ptr, len| self.format.call(store, (ptr, len, indent, use_tabs, line_length)))
+    }
+
+    pub(crate) fn minify(&self, source: &str) -> Result<String> {
+        let minify = self.minify.as_ref().with_context(|| tr!("插件 {} 不支持压缩", "Plugin {} doesn't support minification", self.path))?;
+        self.call(source, |store, ptr, len| minify.call(store, (ptr, len)))
+    }
+
+    /// 把源码写入插件内存后调用 `function`，再读取 `output()` 的结果；返回值非 0 时结果为错误信息
+    /// Write the source into plugin memory, call `function` and read the result from `output()`; with a non-zero
+    /// return value the result is the error message
+    fn call(&self, source: &str, function: impl FnOnce(&mut wasmi::Store<()>, i32, i32) -> std::result::Result<i32, wasmi::Error>) -> Result<String> {
+        let failed = |message: String| anyhow::anyhow!(tr!("插件 {} 运行失败：{}", "Plugin {} failed: {}", self.path, message));
+        let mut store = self.store.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let len = i32::try_from(source.len()).map_err(|_| failed(tr!("输入过大", "the input is too large")))?;
+        let ptr = self.alloc.call(&mut *store, len).map_err(|err| failed(err.to_string()))?;
+        self.memory.write(&mut *store, ptr as u32 as usize, source.as_bytes()).map_err(|err| failed(err.to_string()))?;
+        let status = function(&mut store, ptr, len).map_err(|err| failed(err.to_string()))?;
+        let packed = self.output.call(&mut *store, ()).map_err(|err| failed(err.to_string()))?;
+        let text = self.read_text(&store, packed).ok_or_else(|| failed(tr!("输出位置无效或不是 UTF-8", "the output location is invalid or not UTF-8")))?;
+        if status != 0 {
+            anyhow::bail!(tr!("插件 {} 格式化失败：{}", "Plugin {} failed to format: {}", self.path, text));
+        }
+        Ok(text)
+    }
+
+    /// 读取插件内存中打包位置 `(指针 << 32) | 长度` 处的 UTF-8 文本
+    /// Read the UTF-8 text at the packed location `(pointer << 32) | length` of plugin memory
+    fn read_text(&self, store: &wasmi::Store<()>, packed: i64) -> Option<String> {
+        let (ptr, len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xFFFF_FFFF) as usize);
+        let bytes = self.memory.data(store).get(ptr..ptr.checked_add(len)?)?;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+/// 加载插件目录中的全部 `.wasm` 插件（按文件名顺序）并登记到语言注册表；类型名或扩展名与已有语言冲突时报告配置错误
+/// Load every `.wasm` plugin of the plugins directory (in file name order) and register it in the language registry; a
+/// type name or extension already taken by another language is a configuration error
+pub(crate) fn load_plugins(dir: &str) -> Result<()> {
+    let mut paths: Vec<std::path::PathBuf> = std::fs::read_dir(dir)
+        .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect())
+        .with_context(|| tr!("无法读取插件目录：{}", "Cannot read the plugins directory: {}", dir))?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("wasm")));
+    paths.sort();
+    let mut plugins: Vec<Language> = Vec::new();
+    for path in paths {
+        let (plugin, name, extensions) = WasmPlugin::load(&path)?;
+        let taken = |check: &dyn Fn(&Language) -> bool| LANGUAGES.iter().chain(&plugins).find(|language| check(language)).map(|language| language.name);
+        if let Some(other) = taken(&|language| language.name == name || language.aliases.contains(&name.as_str())) {
+            return Err(ConfigError(tr!("插件 {} 的类型名 {} 已被 {} 使用", "Plugin {} uses the type name {}, which {} already has", path.display(), name, other)).into());
+        }
+        for extension in &extensions {
+            if let Some(other) = taken(&|language| language.extensions.contains(&extension.as_str())) {
+                return Err(ConfigError(tr!("插件 {} 的扩展名 {} 已属于 {}", "Plugin {} claims the extension {}, which belongs to {}", path.display(), extension, other)).into());
+            }
+        }
+        log(LogLevel::Verbose, tr!("[INFO] 已加载插件 {}：类型 {}，扩展名 {}", "[INFO] Loaded plugin {}: type {}, extensions {}", path.display(), name, extensions.join(", ")));
+        // 注册表中的语言在整个运行期间有效
+        // Languages in the registry live for the whole run
+        let extensions: Vec<&'static str> = extensions.into_iter().map(|extension| &*Box::leak(extension.into_boxed_str())).collect();
+        plugins.push(Language { name: Box::leak(name.into_boxed_str()), aliases: &[], extensions: Box::leak(extensions.into_boxed_slice()), backend: Backend::Wasm(Box::new(plugin)) });
+    }
+    PLUGINS.set(plugins).map_err(|_| anyhow::anyhow!(tr!("插件已经加载", "Plugins are already loaded")))
+}
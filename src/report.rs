@@ -0,0 +1,467 @@
+//! 报告输出
+//! Report output
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::*;
+
+/// 报告格式
+/// Report format
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportFormat {
+    /// 结构化 JSON 报告
+    /// Structured JSON report
+    Json,
+    /// SARIF 2.1.0（--check 模式下每处未格式化区域一条结果）
+    /// SARIF 2.1.0 (one result per unformatted region in --check mode)
+    Sarif,
+    /// GitHub Actions 工作流命令（--check 模式下每处未格式化区域一条注解）
+    /// GitHub Actions workflow commands (one annotation per unformatted region in --check mode)
+    Github,
+}
+
+/// 转义 JSON 字符串内容
+/// Escape the content of a JSON string
+pub(crate) fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// 生成 JSON 报告
+/// Build the JSON report
+pub(crate) fn render_json_report(outcomes: &[FileOutcome], summary: &RunSummary, elapsed: Duration) -> String {
+    let files: Vec<String> = outcomes
+        .iter()
+        .map(|outcome| {
+            let diagnostics: Vec<String> = outcome
+                .diagnostics
+                .iter()
+                .map(|d| {
+                    let location = match &d.span {
+                        Some(span) => format!(
+                            ", \"offset\": {}, \"line\": {}, \"column\": {}, \"snippet\": {}",
+                            span.offset,
+                            span.line,
+                            span.column,
+                            json_escape(&span.snippet)
+                        ),
+                        None => String::new(),
+                    };
+                    format!("{{\"severity\": {}, \"message\": {}{}}}", json_escape(d.severity.as_str()), json_escape(&d.message), location)
+                })
+                .collect();
+            format!(
+                "    {{\n      \"path\": {},\n      \"output\": {},\n      \"status\": {},\n      \"input_bytes\": {},\n      \"output_bytes\": {},\n      \"byte_delta\": {},\n      \"input_lines\": {},\n      \"output_lines\": {},\n      \"duration_ms\": {:.3},\n      \"diagnostics\": [{}]\n    }}",
+                json_escape(&outcome.path),
+                json_escape(&outcome.output),
+                json_escape(outcome.status.as_str()),
+                outcome.input_bytes,
+                outcome.output_bytes,
+                outcome.output_bytes as i64 - outcome.input_bytes as i64,
+                outcome.input_lines,
+                outcome.output_lines,
+                outcome.duration.as_secs_f64() * 1000.0,
+                diagnostics.join(", "),
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"summary\": {{\n    \"processed\": {},\n    \"changed\": {},\n    \"skipped\": {},\n    \"errored\": {},\n    \"input_bytes\": {},\n    \"output_bytes\": {},\n    \"input_lines\": {},\n    \"output_lines\": {},\n    \"elapsed_ms\": {:.3}\n  }},\n  \"files\": [\n{}\n  ]\n}}\n",
+        summary.processed,
+        summary.changed,
+        summary.skipped,
+        summary.errored,
+        summary.input_bytes,
+        summary.output_bytes,
+        summary.input_lines,
+        summary.output_lines,
+        elapsed.as_secs_f64() * 1000.0,
+        files.join(",\n"),
+    )
+}
+
+/// 生成 SARIF 2.1.0 报告：每处未格式化区域一条结果，诊断信息作为执行通知
+/// Build a SARIF 2.1.0 report: one result per unformatted region, diagnostics become execution notifications
+pub(crate) fn render_sarif_report(outcomes: &[FileOutcome]) -> String {
+    let uri = |path: &str| json_escape(&path.replace('\\', "/"));
+    let location = |path: &str, start: usize, end: usize| {
+        format!(
+            "{{\"physicalLocation\": {{\"artifactLocation\": {{\"uri\": {}}}, \"region\": {{\"startLine\": {}, \"endLine\": {}}}}}}}",
+            uri(path),
+            start,
+            end
+        )
+    };
+
+    let array = |items: &[String], indent: &str| {
+        if items.is_empty() {
+            "[]".to_string()
+        } else {
+            format!("[\n{}\n{}]", items.join(",\n"), indent)
+        }
+    };
+
+    let mut results = Vec::new();
+    let mut notifications = Vec::new();
+    for outcome in outcomes {
+        for hunk in &outcome.hunks {
+            let (start, end) = hunk.old_line_range();
+            results.push(format!(
+                "        {{\n          \"ruleId\": \"formatting\",\n          \"level\": \"error\",\n          \"message\": {{\"text\": {}}},\n          \"locations\": [{}]\n        }}",
+                json_escape(&tr!("第 {}-{} 行需要格式化", "Lines {}-{} need formatting", start, end)),
+                location(&outcome.path, start, end)
+            ));
+        }
+        for diagnostic in &outcome.diagnostics {
+            let line = diagnostic.span.as_ref().map_or(1, |span| span.line);
+            notifications.push(format!(
+                "            {{\"level\": \"{}\", \"message\": {{\"text\": {}}}, \"locations\": [{}]}}",
+                diagnostic.severity.as_str(),
+                json_escape(&diagnostic.message),
+                location(&outcome.path, line, line)
+            ));
+        }
+    }
+
+    format!(
+        "{{\n  \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\",\n  \"version\": \"2.1.0\",\n  \"runs\": [\n    {{\n      \"tool\": {{\n        \"driver\": {{\n          \"name\": \"code_formatter\",\n          \"version\": {},\n          \"rules\": [{{\"id\": \"formatting\", \"shortDescription\": {{\"text\": \"Code is not formatted\"}}}}]\n        }}\n      }},\n      \"invocations\": [\n        {{\n          \"executionSuccessful\": {},\n          \"toolExecutionNotifications\": {}\n        }}\n      ],\n      \"results\": {}\n    }}\n  ]\n}}\n",
+        json_escape(env!("CARGO_PKG_VERSION")),
+        outcomes.iter().all(|outcome| !outcome.has(Severity::Error)),
+        array(&notifications, "          "),
+        array(&results, "      "),
+    )
+}
+
+/// 转义 GitHub 工作流命令中的文本；`property` 为 true 时额外转义属性分隔符
+/// Escape text for a GitHub workflow command; `property` additionally escapes the property separators
+fn escape_workflow_command(text: &str, property: bool) -> String {
+    let mut escaped = text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+    if property {
+        escaped = escaped.replace(':', "%3A").replace(',', "%2C");
+    }
+    escaped
+}
+
+/// 生成 GitHub Actions 注解：每处未格式化区域一条 `::error`，诊断按级别输出 `::error`/`::warning`
+/// Build GitHub Actions annotations: one `::error` per unformatted region, diagnostics emit `::error`/`::warning` by severity
+pub(crate) fn render_github_report(outcomes: &[FileOutcome]) -> String {
+    let mut report = String::new();
+    for outcome in outcomes {
+        let file = escape_workflow_command(&outcome.path.replace('\\', "/"), true);
+        for hunk in &outcome.hunks {
+            let (start, end) = hunk.old_line_range();
+            report.push_str(&format!(
+                "::error file={},line={},endLine={},title=code_formatter::needs formatting\n",
+                file, start, end
+            ));
+        }
+        for diagnostic in &outcome.diagnostics {
+            let position = match &diagnostic.span {
+                Some(span) => format!(",line={},col={}", span.line, span.column),
+                None => String::new(),
+            };
+            report.push_str(&format!(
+                "::{} file={}{},title=code_formatter::{}\n",
+                diagnostic.severity.as_str(),
+                file,
+                position,
+                escape_workflow_command(&diagnostic.message, false)
+            ));
+        }
+    }
+    report
+}
+
+/// 输出报告：指定文件时写入文件，否则打印到标准输出
+/// Emit the report: write to a file when given, otherwise print to stdout
+pub(crate) fn write_report(report: &str, report_file: Option<&str>) -> Result<()> {
+    match report_file {
+        Some(path) => write_atomic(Path::new(path), report.as_bytes()).with_context(|| tr!("无法写入报告文件：{}", "Cannot write report file: {}", path)),
+        None => {
+            print!("{}", report);
+            Ok(())
+        }
+    }
+}
+
+/// 单个文件的输出方式
+/// How the result of a single file is output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputMode<'a> {
+    /// 写入目标文件（`-` 为标准输出）
+    /// Write to the target file (`-` for stdout)
+    Write(&'a str),
+    /// 演练：报告将写入的内容但不写入，`diff` 为 true 时输出差异
+    /// Dry run: report what would be written without writing, printing the diff when `diff` is set
+    DryRun { target: &'a str, diff: bool },
+    /// 检查是否已格式化，`diff` 为 true 时输出差异
+    /// Check whether the file is formatted, printing the diff when `diff` is set
+    Check { diff: bool },
+}
+
+impl OutputMode<'_> {
+    /// 结果将写入的目标（检查模式下没有）
+    /// Target the result goes to (none in check mode)
+    pub(crate) fn target(&self) -> Option<&str> {
+        match self {
+            OutputMode::Write(target) | OutputMode::DryRun { target, .. } => Some(target),
+            OutputMode::Check { .. } => None,
+        }
+    }
+}
+
+impl Cli {
+    /// 某个输入文件的输出方式
+    /// Output mode of one input file
+    pub(crate) fn output_mode<'a>(&'a self, input: &'a str) -> OutputMode<'a> {
+        let target = if self.write { input } else { self.output.as_deref().unwrap_or_default() };
+        if self.check {
+            OutputMode::Check { diff: self.diff }
+        } else if self.dry_run {
+            OutputMode::DryRun { target, diff: self.diff }
+        } else {
+            OutputMode::Write(target)
+        }
+    }
+
+    /// 命令行参数对应的日志级别
+    /// Log level selected by the command line flags
+    pub(crate) fn log_level(&self) -> LogLevel {
+        match (self.quiet, self.verbose) {
+            (true, _) => LogLevel::Error,
+            (false, 0) => LogLevel::Normal,
+            (false, 1) => LogLevel::Verbose,
+            (false, _) => LogLevel::Trace,
+        }
+    }
+}
+
+/// 稳定性校验：再次格式化的结果必须与第一次相同，否则输出差异并报错
+/// Stability check: formatting again must give the same result, otherwise the diff is printed and an error returned
+pub(crate) fn verify_stable(formatted: &str, second: &str, input: &str) -> Result<()> {
+    if second != formatted {
+        let hunks = diff_lines(formatted, second);
+        if log_enabled(LogLevel::Normal) {
+            eprint!("{}", render_unified_diff(formatted, second, input, &hunks));
+        }
+        let line = hunks.first().map_or(1, |hunk| hunk.old_line_range().0);
+        return Err(FormatError::Unstable { line }.into());
+    }
+    Ok(())
+}
+
+/// 用内置的格式化器格式化文件内容，返回结果与宽松模式下越过的结构错误
+/// Format file content with the built-in formatters, returning the result and the structural errors skipped in lenient mode
+fn format_builtin(input: &str, content: &str, file_type: &str, options: &FormatOptions) -> Result<(String, Vec<FormatError>)> {
+    let indent = match options.indent_style {
+        IndentStyle::Tabs => tr!("tab，宽度 {}", "tabs, width {}", options.indent),
+        IndentStyle::Spaces => tr!("{} 空格", "{} spaces", options.indent),
+    };
+    log(
+        LogLevel::Verbose,
+        tr!(
+            "[INFO] 格式化 {} 文件 {}（缩进：{}，单行长度：{}）",
+            "[INFO] Formatting {} file {} (indent: {}, line length: {})",
+            file_type,
+            input,
+            indent,
+            options.line_length
+        ),
+    );
+
+    let (formatted_content, recovered) = if options.lenient {
+        format_lenient(content, file_type, options)
+    } else {
+        format_code(content, file_type, options).map(|formatted| (formatted, Vec::new()))
+    }
+    .with_context(|| tr!("代码格式化失败", "Formatting failed"))?;
+
+    // 稳定性校验：格式化结果必须是不动点
+    // Stability check: the formatted result must be a fixed point
+    if options.verify_stable {
+        let second = if options.lenient {
+            format_lenient(&formatted_content, file_type, options).map(|(formatted, _)| formatted)
+        } else {
+            format_code(&formatted_content, file_type, options)
+        }
+        .with_context(|| tr!("再次格式化失败", "Formatting the result again failed"))?;
+        verify_stable(&formatted_content, &second, input)?;
+    }
+    Ok((formatted_content, recovered))
+}
+
+/// 格式化单个文件并按 `mode` 输出结果
+/// Format a single file and output the result per `mode`
+pub(crate) fn format_file(input: &str, mode: OutputMode, options: &FormatOptions) -> Result<FileOutcome> {
+    let started = Instant::now();
+    let (raw_content, encoding) = read_source(input, options.encoding)?;
+
+    // 预处理：去掉 BOM（否则会粘在第一个 token 上），保留换行符（避免缩进丢失）
+    // Preprocessing: strip the BOM (it would stick to the first token), preserve line breaks (avoid indentation loss)
+    let (has_bom, text) = strip_bom(&raw_content);
+    let content = text.replace("\r", "");
+
+    if encoding != TextEncoding::Utf8 {
+        log(LogLevel::Verbose, tr!("[INFO] 文件编码：{}，输出使用相同编码", "[INFO] File encoding: {}, the output uses the same encoding", encoding.name()));
+    }
+
+    // 配置文件把该文件交给外部格式化器时，本工具只负责读写、检查、差异与报告
+    // When the config file hands the file to an external formatter, this tool only does I/O, checks, diffs and reports
+    let external = external_command(options.file_type.as_deref().unwrap_or(input), &options.external);
+    let (formatted_content, recovered, line_limit) = match external {
+        Some(command) => {
+            log(LogLevel::Verbose, tr!("[INFO] 使用外部格式化器格式化 {}：{}", "[INFO] Formatting {} with the external formatter: {}", input, command));
+            let formatted = run_external_formatter(command, &content, input)?;
+            if options.verify_stable {
+                let second = run_external_formatter(command, &formatted, input)?;
+                verify_stable(&formatted, &second, input)?;
+            }
+            (formatted, Vec::new(), None)
+        }
+        None => {
+            let file_type = resolve_file_type(input, options.file_type.as_deref(), &options.extensions, &content)?;
+            let options = options.for_type(file_type);
+            let (formatted, recovered) = format_builtin(input, &content, file_type, &options)?;
+            (formatted, recovered, Some(options.line_length))
+        }
+    };
+    let output_content = apply_bom_policy(formatted_content.clone(), has_bom, options.bom, encoding);
+    let changed = output_content != raw_content;
+
+    // 格式化后仍超过最大行长度的行（无法在空白处折行）作为警告
+    // Lines still longer than the limit after formatting (no whitespace to break at) are reported as a warning
+    let mut diagnostics = Vec::new();
+    for error in recovered {
+        let message = tr!("输入有结构错误，已尽量格式化：{}", "malformed input, formatted on a best-effort basis: {}", error);
+        if let (Some(span), true) = (error.span(), log_enabled(LogLevel::Normal)) {
+            eprintln!("{}\n", render_diagnostic(Severity::Warning, &message, input, span, &error.hint()));
+        }
+        diagnostics.push(Diagnostic { severity: Severity::Warning, message, span: error.span().cloned() });
+    }
+    // 外部格式化器有自己的行宽规则，不按本工具的最大行长度检查
+    // External formatters have their own line width rules, the maximum line length of this tool doesn't apply
+    let line_limit = line_limit.unwrap_or(usize::MAX);
+    let long_lines: Vec<usize> = formatted_content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| display_width(line) > line_limit)
+        .map(|(index, _)| index + 1)
+        .collect();
+    if let Some(first) = long_lines.first() {
+        let message = tr!(
+            "{} 行超过最大行长度 {}（首个位于输出第 {} 行）",
+            "{} lines exceed the maximum line length {} (first at output line {})",
+            long_lines.len(),
+            line_limit,
+            first
+        );
+        log(LogLevel::Normal, tr!("[WARN] {}：{}", "[WARN] {}: {}", input, message));
+        diagnostics.push(Diagnostic { severity: Severity::Warning, message, span: None });
+    }
+
+    let encoded = encode_text(&output_content, encoding)?;
+    if let OutputMode::Write(output) = mode {
+        // 目标内容已相同时不写入，保留修改时间（避免破坏增量构建缓存）
+        // Skip the write when the target already has the same content, keeping its mtime (incremental build caches rely on it)
+        let up_to_date = output != "-" && std::fs::read(output).is_ok_and(|existing| existing == encoded);
+        if up_to_date {
+            log(LogLevel::Normal, tr!("[SUCCESS] 内容无变化，未写入：{}", "[SUCCESS] Unchanged, not written: {}", output));
+        } else {
+            if let Some(suffix) = &options.backup {
+                if let Some(backup) = backup_file(output, suffix)? {
+                    log(LogLevel::Verbose, tr!("[INFO] 已备份原文件：{}", "[INFO] Backed up the original file: {}", backup));
+                }
+            }
+            write_output(output, &encoded)?;
+            log(LogLevel::Normal, tr!("[SUCCESS] 格式化完成！输出文件：{}", "[SUCCESS] Formatted! Output file: {}", output));
+        }
+    }
+
+    // 写入模式只在 -v 输出差异数时需要差异
+    // Write mode only needs the differences for the count printed with -v
+    let mut hunks = Vec::new();
+    if changed && (!matches!(mode, OutputMode::Write(_)) || log_enabled(LogLevel::Verbose)) {
+        hunks = diff_lines(&raw_content, &output_content);
+    }
+    match mode {
+        OutputMode::Write(_) => {}
+        OutputMode::DryRun { target, .. } => {
+            let delta = encoded.len() as i64 - raw_content.len() as i64;
+            let unchanged = if changed { String::new() } else { tr!("，内容无变化", ", unchanged") };
+            log(
+                LogLevel::Normal,
+                tr!(
+                    "[DRY-RUN] {}：将写入 {}（{} → {} 字节，{:+}）{}",
+                    "[DRY-RUN] {}: would write {} ({} → {} bytes, {:+}){}",
+                    input,
+                    target,
+                    raw_content.len(),
+                    encoded.len(),
+                    delta,
+                    unchanged
+                ),
+            );
+        }
+        OutputMode::Check { .. } if changed => log(LogLevel::Normal, tr!("[CHECK] 需要格式化：{}", "[CHECK] Needs formatting: {}", input)),
+        // 已格式化的文件只在 -v 时列出，避免多文件检查时刷屏
+        // Clean files are only listed with -v so multi-file checks stay readable
+        OutputMode::Check { .. } => log(LogLevel::Verbose, tr!("[CHECK] 已格式化：{}", "[CHECK] Formatted: {}", input)),
+    }
+    if let OutputMode::DryRun { diff: true, .. } | OutputMode::Check { diff: true } = mode {
+        if changed && log_enabled(LogLevel::Normal) {
+            let diff = render_unified_diff(&raw_content, &output_content, input, &hunks);
+            log(LogLevel::Normal, diff.trim_end_matches('\n'));
+        }
+    }
+    log(
+        LogLevel::Verbose,
+        tr!("[INFO] {}：{} 处差异，耗时 {:.1} ms", "[INFO] {}: {} differences, {:.1} ms", input, hunks.len(), started.elapsed().as_secs_f64() * 1000.0),
+    );
+
+    Ok(FileOutcome {
+        path: input.to_string(),
+        output: mode.target().unwrap_or_default().to_string(),
+        status: if changed { FileStatus::Changed } else { FileStatus::Unchanged },
+        input_bytes: raw_content.len(),
+        output_bytes: output_content.len(),
+        input_lines: raw_content.lines().count(),
+        output_lines: formatted_content.lines().count(),
+        duration: started.elapsed(),
+        diagnostics,
+        hunks,
+        exit_status: if matches!(mode, OutputMode::Check { .. }) && changed { ExitStatus::NeedsFormatting } else { ExitStatus::Clean },
+    })
+}
+
+/// 合并命令行选项与某个文件所用的配置（预设、扩展名映射、外部格式化器、按类型覆盖）；命令行显式给出的选项优先
+/// Combine the command line options with the config that applies to one file (preset, extension mapping, external
+/// formatters, per-type overrides); options given explicitly on the command line take precedence
+pub(crate) fn file_options(cli: &Cli, base_options: &FormatOptions, config: Config) -> Result<FormatOptions> {
+    let mut options = base_options.clone();
+    if let Some(preset) = cli.preset.as_deref().or(config.preset.as_deref()) {
+        apply_preset(&mut options, preset, &config, &cli.explicit)?;
+    }
+    options.extensions = config.extensions;
+    options.external = config.external;
+    options.overrides = config
+        .overrides
+        .into_iter()
+        .map(|(file_type, settings)| (file_type, settings.into_iter().filter(|s| !cli.explicit.iter().any(|key| key == s.key())).collect()))
+        .collect();
+    Ok(options)
+}
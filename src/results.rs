@@ -0,0 +1,186 @@
+//! 运行结果与统计
+//! Run results and statistics
+
+use std::time::Duration;
+use crate::*;
+
+/// 单个文件的处理状态
+/// Processing status of a single file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileStatus {
+    /// 格式化结果与原内容不同
+    /// Formatted output differs from the original content
+    Changed,
+    /// 格式化结果与原内容相同
+    /// Formatted output equals the original content
+    Unchanged,
+    /// 处理失败（错误信息见诊断列表）
+    /// Processing failed (see the diagnostics for the error)
+    Errored,
+}
+
+impl FileStatus {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FileStatus::Changed => "changed",
+            FileStatus::Unchanged => "unchanged",
+            FileStatus::Errored => "errored",
+        }
+    }
+}
+
+/// 诊断级别
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// 处理文件时产生的诊断信息
+/// Diagnostic produced while processing a file
+#[derive(Debug, Clone)]
+pub(crate) struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    /// 出错位置（格式化错误带有位置时）
+    /// Location of the problem (when a formatting error carries one)
+    pub(crate) span: Option<SourceSpan>,
+}
+
+/// 单个文件的处理结果
+/// Processing result of a single file
+#[derive(Debug, Clone)]
+pub(crate) struct FileOutcome {
+    pub(crate) path: String,
+    pub(crate) output: String,
+    pub(crate) status: FileStatus,
+    pub(crate) input_bytes: usize,
+    pub(crate) output_bytes: usize,
+    pub(crate) input_lines: usize,
+    pub(crate) output_lines: usize,
+    pub(crate) duration: Duration,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    /// --check 模式下未格式化的区域
+    /// Unformatted regions in --check mode
+    pub(crate) hunks: Vec<DiffHunk>,
+    /// 该文件对应的退出码
+    /// Exit status contributed by this file
+    pub(crate) exit_status: ExitStatus,
+}
+
+impl FileOutcome {
+    /// 处理失败的文件结果
+    /// Result of a file that failed to process
+    pub(crate) fn failed(path: &str, output: &str, err: &anyhow::Error, duration: Duration) -> Self {
+        FileOutcome {
+            path: path.to_string(),
+            output: output.to_string(),
+            status: FileStatus::Errored,
+            input_bytes: 0,
+            output_bytes: 0,
+            input_lines: 0,
+            output_lines: 0,
+            duration,
+            diagnostics: vec![Diagnostic {
+                severity: Severity::Error,
+                message: format!("{:#}", err),
+                span: err.chain().find_map(|cause| cause.downcast_ref::<FormatError>()).and_then(FormatError::span).cloned(),
+            }],
+            hunks: Vec::new(),
+            exit_status: ExitStatus::of_error(err),
+        }
+    }
+
+    /// 是否有指定级别的诊断
+    /// Whether any diagnostic has the given severity
+    pub(crate) fn has(&self, severity: Severity) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == severity)
+    }
+}
+
+/// 整次运行的统计
+/// Statistics of a whole run
+#[derive(Debug, Default)]
+pub(crate) struct RunSummary {
+    pub(crate) processed: usize,
+    pub(crate) changed: usize,
+    pub(crate) skipped: usize,
+    pub(crate) errored: usize,
+    pub(crate) input_bytes: usize,
+    pub(crate) output_bytes: usize,
+    pub(crate) input_lines: usize,
+    pub(crate) output_lines: usize,
+    /// 出错的文件及错误信息
+    /// Files that failed, with their error messages
+    failures: Vec<(String, String)>,
+}
+
+impl RunSummary {
+    pub(crate) fn record(&mut self, outcome: &FileOutcome) {
+        match outcome.status {
+            FileStatus::Changed => self.changed += 1,
+            FileStatus::Unchanged => {}
+            FileStatus::Errored => {
+                self.errored += 1;
+                for diagnostic in outcome.diagnostics.iter().filter(|d| d.severity == Severity::Error) {
+                    self.failures.push((outcome.path.clone(), diagnostic.message.clone()));
+                }
+                return;
+            }
+        }
+        self.processed += 1;
+        self.input_bytes += outcome.input_bytes;
+        self.output_bytes += outcome.output_bytes;
+        self.input_lines += outcome.input_lines;
+        self.output_lines += outcome.output_lines;
+    }
+
+    pub(crate) fn print(&self, elapsed: Duration) {
+        let delta = |before: usize, after: usize| after as i64 - before as i64;
+        log(
+            LogLevel::Normal,
+            tr!(
+                "[SUMMARY] 文件：处理 {}，变更 {}，跳过 {}，出错 {}",
+                "[SUMMARY] Files: processed {}, changed {}, skipped {}, failed {}",
+                self.processed,
+                self.changed,
+                self.skipped,
+                self.errored
+            ),
+        );
+        log(
+            LogLevel::Normal,
+            tr!(
+                "[SUMMARY] 字节：输入 {} → 输出 {}（{:+}）",
+                "[SUMMARY] Bytes: input {} → output {} ({:+})",
+                self.input_bytes,
+                self.output_bytes,
+                delta(self.input_bytes, self.output_bytes)
+            ),
+        );
+        log(
+            LogLevel::Normal,
+            tr!(
+                "[SUMMARY] 行数：输入 {} → 输出 {}（{:+}）",
+                "[SUMMARY] Lines: input {} → output {} ({:+})",
+                self.input_lines,
+                self.output_lines,
+                delta(self.input_lines, self.output_lines)
+            ),
+        );
+        log(LogLevel::Normal, tr!("[SUMMARY] 耗时：{:.1} ms", "[SUMMARY] Elapsed: {:.1} ms", elapsed.as_secs_f64() * 1000.0));
+        for (path, message) in &self.failures {
+            log(LogLevel::Normal, tr!("[SUMMARY] 出错：{}：{}", "[SUMMARY] Failed: {}: {}", path, message));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! 可配置的格式化设置与预设
+//! Configurable formatting settings and presets
+
+use anyhow::Result;
+use clap::ValueEnum;
+use crate::*;
+
+/// 可由预设或配置文件设置的一项格式化选项，键名与命令行参数的字段名一致（如 `line_length`）
+/// One formatting option that presets or the config file can set, keyed like the command line field (such as `line_length`)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Setting {
+    Indent(u8),
+    IndentStyle(IndentStyle),
+    LineLength(usize),
+    FinalNewline(FinalNewline),
+    TrimTrailingWhitespace(bool),
+    ElsePlacement(ElsePlacement),
+    IndentCaseLabels(bool),
+    CssSingleLineRules(CssSingleLineRules),
+    SortProperties(SortProperties),
+    SplitSelectors(bool),
+    NormalizeColors(bool),
+    LeadingZero(LeadingZero),
+    WrapComments(bool),
+    ProseWrap(ProseWrap),
+    IndentRootElements(bool),
+    FormatEventHandlers(bool),
+    SortKeys(SortKeys),
+    AttrQuotes(AttrQuotes),
+}
+
+/// 可设置的键名
+/// Keys that can be set
+const SETTING_KEYS: &[&str] = &[
+    "indent", "indent_style", "line_length", "final_newline", "trim_trailing_whitespace", "else_placement",
+    "indent_case_labels", "css_single_line_rules", "sort_properties", "split_selectors", "normalize_colors",
+    "leading_zero", "wrap_comments", "prose_wrap",
+    "indent_root_elements", "format_event_handlers", "sort_keys", "attr_quotes",
+];
+
+impl Setting {
+    /// 由键与配置值解析设置，键名中的 `-` 与 `_` 等价；出错时返回错误描述
+    /// Parse a setting from a key and a config value, `-` and `_` being equivalent in keys; returns a description on error
+    pub(crate) fn parse(key: &str, value: &ConfigValue) -> std::result::Result<Setting, String> {
+        fn choice<T: ValueEnum>(key: &str, value: &ConfigValue) -> std::result::Result<T, String> {
+            let ConfigValue::Str(text) = value else {
+                return Err(tr!("{} 应为字符串，实际为{}", "{} must be a string, found {}", key, value.type_name()));
+            };
+            T::from_str(text, true).map_err(|_| {
+                let names: Vec<String> = T::value_variants().iter().filter_map(|v| v.to_possible_value()).map(|v| v.get_name().to_string()).collect();
+                tr!("{} 的取值无效：{}，可选 {}", "invalid value for {}: {}, expected one of {}", key, text, names.join("/"))
+            })
+        }
+        let flag = || match value {
+            ConfigValue::Bool(flag) => Ok(*flag),
+            other => Err(tr!("{} 应为布尔值，实际为{}", "{} must be a boolean, found {}", key, other.type_name())),
+        };
+        let number = |max: i64| match value {
+            ConfigValue::Int(n) if (1..=max).contains(n) => Ok(*n),
+            ConfigValue::Int(n) => Err(tr!("{} 超出范围：{}（1～{}）", "{} out of range: {} (1 to {})", key, n, max)),
+            other => Err(tr!("{} 应为整数，实际为{}", "{} must be an integer, found {}", key, other.type_name())),
+        };
+        let setting = match key.replace('-', "_").as_str() {
+            "indent" => Setting::Indent(number(u8::MAX as i64)? as u8),
+            "indent_style" => Setting::IndentStyle(choice(key, value)?),
+            "line_length" => Setting::LineLength(number(i64::from(u32::MAX))? as usize),
+            "final_newline" => Setting::FinalNewline(choice(key, value)?),
+            "trim_trailing_whitespace" => Setting::TrimTrailingWhitespace(flag()?),
+            "else_placement" => Setting::ElsePlacement(choice(key, value)?),
+            "indent_case_labels" => Setting::IndentCaseLabels(flag()?),
+            "css_single_line_rules" => Setting::CssSingleLineRules(choice(key, value)?),
+            "sort_properties" => Setting::SortProperties(choice(key, value)?),
+            "split_selectors" => Setting::SplitSelectors(flag()?),
+            "normalize_colors" => Setting::NormalizeColors(flag()?),
+            "leading_zero" => Setting::LeadingZero(choice(key, value)?),
+            "wrap_comments" => Setting::WrapComments(flag()?),
+            "prose_wrap" => Setting::ProseWrap(choice(key, value)?),
+            "indent_root_elements" => Setting::IndentRootElements(flag()?),
+            "format_event_handlers" => Setting::FormatEventHandlers(flag()?),
+            "sort_keys" => Setting::SortKeys(choice(key, value)?),
+            "attr_quotes" => Setting::AttrQuotes(choice(key, value)?),
+            _ => return Err(tr!("未知的设置：{}，可设置 {}", "unknown setting: {}, expected one of {}", key, SETTING_KEYS.join("/"))),
+        };
+        Ok(setting)
+    }
+
+    /// 设置的键名（与命令行参数的字段名一致）
+    /// Key of the setting (same as the command line field)
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Setting::Indent(_) => "indent",
+            Setting::IndentStyle(_) => "indent_style",
+            Setting::LineLength(_) => "line_length",
+            Setting::FinalNewline(_) => "final_newline",
+            Setting::TrimTrailingWhitespace(_) => "trim_trailing_whitespace",
+            Setting::ElsePlacement(_) => "else_placement",
+            Setting::IndentCaseLabels(_) => "indent_case_labels",
+            Setting::CssSingleLineRules(_) => "css_single_line_rules",
+            Setting::SortProperties(_) => "sort_properties",
+            Setting::SplitSelectors(_) => "split_selectors",
+            Setting::NormalizeColors(_) => "normalize_colors",
+            Setting::LeadingZero(_) => "leading_zero",
+            Setting::WrapComments(_) => "wrap_comments",
+            Setting::ProseWrap(_) => "prose_wrap",
+            Setting::IndentRootElements(_) => "indent_root_elements",
+            Setting::FormatEventHandlers(_) => "format_event_handlers",
+            Setting::SortKeys(_) => "sort_keys",
+            Setting::AttrQuotes(_) => "attr_quotes",
+        }
+    }
+
+    pub(crate) fn apply(&self, options: &mut FormatOptions) {
+        match *self {
+            Setting::Indent(indent) => options.indent = indent,
+            Setting::IndentStyle(style) => options.indent_style = style,
+            Setting::LineLength(length) => options.line_length = length,
+            Setting::FinalNewline(policy) => options.final_newline = policy,
+            Setting::TrimTrailingWhitespace(flag) => options.trim_trailing_whitespace = flag,
+            Setting::ElsePlacement(placement) => options.else_placement = placement,
+            Setting::IndentCaseLabels(flag) => options.indent_case_labels = flag,
+            Setting::CssSingleLineRules(policy) => options.css_single_line_rules = policy,
+            Setting::SortProperties(order) => options.sort_properties = order,
+            Setting::SplitSelectors(flag) => options.split_selectors = flag,
+            Setting::NormalizeColors(flag) => options.normalize_colors = flag,
+            Setting::LeadingZero(policy) => options.leading_zero = policy,
+            Setting::WrapComments(flag) => options.wrap_comments = flag,
+            Setting::ProseWrap(policy) => options.prose_wrap = policy,
+            Setting::IndentRootElements(flag) => options.indent_root_elements = flag,
+            Setting::FormatEventHandlers(flag) => options.format_event_handlers = flag,
+            Setting::SortKeys(order) => options.sort_keys = order,
+            Setting::AttrQuotes(policy) => options.attr_quotes = policy,
+        }
+    }
+}
+
+/// 内置预设的名称
+/// Names of the built-in presets
+const BUILTIN_PRESETS: &[&str] = &["default", "compact", "readable", "2-space"];
+
+/// 内置预设包含的设置，未知名称返回 None
+/// Settings bundled by a built-in preset, None for an unknown name
+fn builtin_preset(name: &str) -> Option<Vec<Setting>> {
+    let settings = match name {
+        // 与命令行参数的默认值相同
+        // Same as the command line defaults
+        "default" => vec![
+            Setting::Indent(4),
+            Setting::IndentStyle(IndentStyle::Spaces),
+            Setting::LineLength(80),
+            Setting::ElsePlacement(ElsePlacement::SameLine),
+            Setting::CssSingleLineRules(CssSingleLineRules::Auto),
+        ],
+        // 2 空格缩进、120 列，短规则尽量压缩为单行
+        // 2-space indentation, 120 columns, short rules compacted onto one line whenever possible
+        "compact" => vec![
+            Setting::Indent(2),
+            Setting::IndentStyle(IndentStyle::Spaces),
+            Setting::LineLength(120),
+            Setting::ElsePlacement(ElsePlacement::SameLine),
+            Setting::CssSingleLineRules(CssSingleLineRules::Always),
+        ],
+        // 4 空格缩进、80 列，每条声明与每个选择器各占一行，case 标签缩进
+        // 4-space indentation, 80 columns, one declaration and one selector per line, indented case labels
+        "readable" => vec![
+            Setting::Indent(4),
+            Setting::IndentStyle(IndentStyle::Spaces),
+            Setting::LineLength(80),
+            Setting::ElsePlacement(ElsePlacement::SameLine),
+            Setting::IndentCaseLabels(true),
+            Setting::CssSingleLineRules(CssSingleLineRules::Never),
+            Setting::SplitSelectors(true),
+        ],
+        "2-space" => vec![Setting::Indent(2), Setting::IndentStyle(IndentStyle::Spaces)],
+        _ => return None,
+    };
+    Some(settings)
+}
+
+/// 应用预设：配置文件中的同名预设优先于内置预设；命令行显式给出的选项不被覆盖
+/// Apply a preset: a preset of the same name in the config file wins over the built-in one; options given explicitly on
+/// the command line are not overridden
+pub(crate) fn apply_preset(options: &mut FormatOptions, name: &str, config: &Config, explicit: &[String]) -> Result<()> {
+    let settings = match config.presets.iter().find(|(preset, _)| preset == name) {
+        Some((_, settings)) => settings.clone(),
+        None => builtin_preset(name).ok_or_else(|| {
+            let mut names: Vec<&str> = BUILTIN_PRESETS.to_vec();
+            names.extend(config.presets.iter().map(|(preset, _)| preset.as_str()));
+            ConfigError(tr!("未知的预设：{}，可选 {}", "Unknown preset: {}, expected one of {}", name, names.join("/")))
+        })?,
+    };
+    for setting in settings.iter().filter(|setting| !explicit.iter().any(|key| key == setting.key())) {
+        setting.apply(options);
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! 增量运行（--since 与 --stamp）
+//! Incremental runs (--since and --stamp)
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::Duration;
+use crate::*;
+
+/// 解析 UTC 时间 `YYYY-MM-DD[THH:MM[:SS]][Z]`（日期与时间之间也可用空格），返回 Unix 秒数
+/// Parse a UTC time `YYYY-MM-DD[THH:MM[:SS]][Z]` (a space may also separate date and time), returning Unix seconds
+fn parse_utc_time(text: &str) -> Option<i64> {
+    let text = text.strip_suffix(['Z', 'z']).unwrap_or(text);
+    let (date, time) = text.split_once(['T', 't', ' ']).unwrap_or((text, ""));
+    let mut date_parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut time_parts = time.split(':').filter(|part| !part.is_empty()).map(|part| part.parse::<i64>().ok());
+    let hour = time_parts.next().unwrap_or(Some(0))?;
+    let minute = time_parts.next().unwrap_or(Some(0))?;
+    let second = time_parts.next().unwrap_or(Some(0))?;
+    if time_parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    // 公历日期到 1970-01-01 起的天数（Howard Hinnant 的 days_from_civil）
+    // Days since 1970-01-01 for a Gregorian date (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// 把 Unix 秒数写成 UTC 时间 `YYYY-MM-DDTHH:MM:SSZ`（[`parse_utc_time`] 的逆操作）
+/// Write Unix seconds as a UTC time `YYYY-MM-DDTHH:MM:SSZ` (the inverse of [`parse_utc_time`])
+pub(crate) fn format_utc_time(seconds: i64) -> String {
+    let (days, second_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    // 1970-01-01 起的天数到公历日期（Howard Hinnant 的 civil_from_days）
+    // Gregorian date for days since 1970-01-01 (Howard Hinnant's civil_from_days)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, second_of_day / 3600, second_of_day % 3600 / 60, second_of_day % 60)
+}
+
+/// 解析 --since 的取值：已有文件的路径、Unix 秒数、UTC 时间或 git 引用
+/// Resolve the value of --since: the path of an existing file, Unix seconds, a UTC time or a git ref
+pub(crate) fn resolve_since(value: &str) -> Result<std::time::SystemTime> {
+    let from_seconds = |seconds: i64| std::time::UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64);
+    if Path::new(value).is_file() {
+        return std::fs::metadata(value)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| tr!("无法读取文件的修改时间：{}", "Cannot read the modification time of {}", value));
+    }
+    if let Some(seconds) = value.parse::<i64>().ok().or_else(|| parse_utc_time(value)) {
+        return Ok(from_seconds(seconds));
+    }
+    let commit_time = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%ct", value, "--"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse::<i64>().ok());
+    match commit_time {
+        Some(seconds) => Ok(from_seconds(seconds)),
+        None => Err(ConfigError(tr!(
+            "无法识别 --since 的取值：{}（应为已有文件、Unix 秒数、UTC 时间或 git 引用）",
+            "Unrecognized --since value: {} (expected an existing file, Unix seconds, a UTC time or a git ref)",
+            value
+        ))
+        .into()),
+    }
+}
+
+/// 把时间戳文件的修改时间设为给定时刻，文件不存在时创建
+/// Set the modification time of the stamp file to the given moment, creating the file when it doesn't exist
+pub(crate) fn touch_stamp(path: &str, time: std::time::SystemTime) -> Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|file| file.set_modified(time))
+        .with_context(|| tr!("无法更新时间戳文件：{}", "Cannot update the stamp file: {}", path))
+}
@@ -0,0 +1,550 @@
+//! 语法检查（字符串、注释、括号与标签的结构错误）
+//! Syntax checking (structural errors in strings, comments, brackets and tags)
+
+use anyhow::Result;
+use std::fmt;
+use crate::*;
+
+/// 源码中的一段位置：字节偏移与长度、行列号（从 1 开始，列按字符计）以及出错的片段
+/// 偏移基于去掉 BOM 与 `\r` 之后的文本
+/// A location in the source: byte offset and length, line and column (1-based, columns count characters) and the offending snippet
+/// Offsets refer to the text after the BOM and `\r` have been removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceSpan {
+    pub(crate) offset: usize,
+    len: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) snippet: String,
+    /// 出错行在出错位置附近的摘录（过长的行两端以 … 截断），用于展示源码
+    /// Excerpt of the offending line around the error (long lines are cut with … on either side), used to show the source
+    pub(crate) excerpt: String,
+    /// 出错位置在摘录中的字符序号（从 0 开始）
+    /// Character index of the error within the excerpt (0-based)
+    pub(crate) excerpt_column: usize,
+}
+
+/// 片段最多保留的字符数
+/// Maximum number of characters kept in a snippet
+const SNIPPET_MAX_CHARS: usize = 40;
+
+/// 摘录在出错位置之前与之后最多保留的字符数（压缩代码往往整个文件只有一行）
+/// Maximum characters an excerpt keeps before and after the error (minified code is often a single line)
+const EXCERPT_CHARS_BEFORE: usize = 30;
+const EXCERPT_CHARS_AFTER: usize = 50;
+
+impl SourceSpan {
+    pub(crate) fn new(source: &str, offset: usize, len: usize) -> Self {
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+        let text = &source[offset..(offset + len).min(source.len())];
+        let column = source[line_start..offset].chars().count() + 1;
+
+        let before: Vec<char> = source[line_start..offset].chars().collect();
+        let skipped = before.len().saturating_sub(EXCERPT_CHARS_BEFORE);
+        let mut excerpt: String = if skipped > 0 { "…".to_string() } else { String::new() };
+        excerpt.extend(&before[skipped..]);
+        let excerpt_column = excerpt.chars().count();
+        let mut after = source[offset..line_end].chars();
+        excerpt.extend(after.by_ref().take(EXCERPT_CHARS_AFTER));
+        if after.next().is_some() {
+            excerpt.push('…');
+        }
+
+        SourceSpan {
+            offset,
+            len,
+            line: source[..offset].matches('\n').count() + 1,
+            column,
+            snippet: text.lines().next().unwrap_or_default().chars().take(SNIPPET_MAX_CHARS).collect(),
+            excerpt,
+            excerpt_column,
+        }
+    }
+}
+
+/// 格式化错误：输入的结构无法可靠地格式化，或格式化结果违反了保证
+/// Formatting error: the input's structure can't be formatted reliably, or the result breaks a guarantee
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FormatError {
+    /// 字符串或模板字符串没有闭合
+    /// A string or template literal is never closed
+    UnclosedString { span: SourceSpan },
+    /// 块注释没有闭合
+    /// A block comment is never closed
+    UnclosedComment { span: SourceSpan },
+    /// 标签没有闭合（缺少 `>` 或 script/style 等缺少结束标签）
+    /// A tag is never closed (missing `>`, or script/style etc. without an end tag)
+    UnclosedTag { name: String, span: SourceSpan },
+    /// 结束标签与尚未闭合的标签不匹配（仅校验模式）
+    /// An end tag that doesn't match the open element (validation only)
+    MismatchedTag { expected: String, found: String, span: SourceSpan },
+    /// 没有对应开始标签的结束标签（仅校验模式）
+    /// An end tag without a matching start tag (validation only)
+    UnexpectedEndTag { name: String, span: SourceSpan },
+    /// 没有对应开括号的闭括号
+    /// A closing bracket without a matching opening bracket
+    UnexpectedBracket { found: char, span: SourceSpan },
+    /// 闭括号与最近的开括号不匹配
+    /// A closing bracket that doesn't match the innermost opening bracket
+    MismatchedBracket { expected: char, found: char, span: SourceSpan },
+    /// 开括号没有闭合
+    /// An opening bracket that is never closed
+    UnclosedBracket { open: char, span: SourceSpan },
+    /// data URI 在格式化后发生变化（--preserve-data-uris）
+    /// A data URI changed during formatting (--preserve-data-uris)
+    DataUriChanged { uri: String },
+    /// 嵌套超过 --max-nesting 的上限
+    /// Nesting deeper than the --max-nesting limit
+    NestingTooDeep { limit: usize, span: SourceSpan },
+    /// 一条语句的词法单元数超过 --max-line-tokens 的上限，`span` 为语句开头
+    /// A statement with more tokens than the --max-line-tokens limit, `span` is the start of the statement
+    TooManyLineTokens { limit: usize, span: SourceSpan },
+    /// JSON 语法错误：此处应为 `expected`，实际为 `found`
+    /// JSON syntax error: `expected` was expected here but `found` was found
+    InvalidJson { expected: String, found: String, span: SourceSpan },
+    /// 再次格式化的结果与第一次不同（--verify-stable），`line` 为第一次结果中首个差异所在行
+    /// Formatting the result again changed it (--verify-stable), `line` is the first differing line of the first result
+    Unstable { line: usize },
+}
+
+impl FormatError {
+    /// 出错位置（与具体位置无关的错误没有）
+    /// Location of the error (none for errors not tied to a position)
+    pub(crate) fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            FormatError::UnclosedString { span }
+            | FormatError::UnclosedComment { span }
+            | FormatError::UnclosedTag { span, .. }
+            | FormatError::MismatchedTag { span, .. }
+            | FormatError::UnexpectedEndTag { span, .. }
+            | FormatError::UnexpectedBracket { span, .. }
+            | FormatError::MismatchedBracket { span, .. }
+            | FormatError::UnclosedBracket { span, .. }
+            | FormatError::NestingTooDeep { span, .. }
+            | FormatError::TooManyLineTokens { span, .. }
+            | FormatError::InvalidJson { span, .. } => Some(span),
+            FormatError::DataUriChanged { .. } | FormatError::Unstable { .. } => None,
+        }
+    }
+
+    /// 修复建议
+    /// Suggestion on how to fix the problem
+    pub(crate) fn hint(&self) -> String {
+        match self {
+            FormatError::UnclosedString { .. } => {
+                tr!("检查结束引号是否遗漏或被转义", "check whether the closing quote is missing or escaped")
+            }
+            FormatError::UnclosedComment { span } => {
+                let close = if span.snippet.starts_with("<!--") { "-->" } else { "*/" };
+                tr!("在注释末尾补上 `{}`", "add `{}` at the end of the comment", close)
+            }
+            FormatError::UnclosedTag { name, .. } => {
+                tr!("补上标签的 `>` 或结束标签 `</{}>`", "add the tag's `>` or the end tag `</{}>`", name)
+            }
+            FormatError::MismatchedTag { expected, .. } => {
+                tr!("在此之前补上 `</{}>`，或检查标签嵌套", "add `</{}>` before this or check the tag nesting", expected)
+            }
+            FormatError::UnexpectedEndTag { name, .. } => {
+                tr!("删除多余的 `</{}>`，或补上对应的开始标签", "remove the extra `</{}>` or add the matching start tag", name)
+            }
+            FormatError::UnexpectedBracket { found, .. } => {
+                tr!("删除多余的 `{}`，或补上之前缺少的开括号", "remove the extra `{}` or add the missing opening bracket before it", found)
+            }
+            FormatError::MismatchedBracket { expected, .. } => {
+                tr!("此处应为 `{}`，检查其间是否缺少或多出括号", "`{}` was expected here, check for missing or extra brackets in between", expected)
+            }
+            FormatError::UnclosedBracket { open, .. } => {
+                tr!("在适当位置补上 `{}`", "add the matching `{}`", closing_bracket(*open))
+            }
+            FormatError::NestingTooDeep { .. } => {
+                tr!("检查输入是否损坏；确实需要时用 --max-nesting 调高上限", "check whether the input is corrupted; raise the limit with --max-nesting if needed")
+            }
+            FormatError::TooManyLineTokens { .. } => {
+                tr!("检查输入是否损坏；确实需要时用 --max-line-tokens 调高上限", "check whether the input is corrupted; raise the limit with --max-line-tokens if needed")
+            }
+            FormatError::InvalidJson { .. } => {
+                tr!("JSON 不允许尾随逗号、注释、单引号字符串与未加引号的键", "JSON doesn't allow trailing commas, comments, single-quoted strings or unquoted keys")
+            }
+            FormatError::Unstable { .. } => {
+                tr!("请报告此问题并附上差异", "please report this issue along with the diff")
+            }
+            FormatError::DataUriChanged { .. } => {
+                tr!("请报告此问题；关闭 --normalize-colors 等 CSS 值选项可暂时避开", "please report this issue; disabling CSS value options such as --normalize-colors avoids it for now")
+            }
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            FormatError::UnclosedString { span } => {
+                tr!("未闭合的字符串，起始于 {}:{}", "unclosed string starting at {}:{}", span.line, span.column)
+            }
+            FormatError::UnclosedComment { span } => {
+                tr!("未闭合的注释，起始于 {}:{}", "unclosed comment starting at {}:{}", span.line, span.column)
+            }
+            FormatError::UnclosedTag { name, span } => {
+                tr!("未闭合的标签 <{}>，起始于 {}:{}", "unclosed tag <{}> starting at {}:{}", name, span.line, span.column)
+            }
+            FormatError::MismatchedTag { expected, found, span } => tr!(
+                "标签不匹配：应为 </{}>，实际为 </{}>，位于 {}:{}",
+                "mismatched tag: expected </{}>, found </{}> at {}:{}",
+                expected,
+                found,
+                span.line,
+                span.column
+            ),
+            FormatError::UnexpectedEndTag { name, span } => {
+                tr!("多余的结束标签 </{}>，位于 {}:{}", "unexpected end tag </{}> at {}:{}", name, span.line, span.column)
+            }
+            FormatError::UnexpectedBracket { found, span } => {
+                tr!("多余的 `{}`，位于 {}:{}", "unbalanced `{}` at {}:{}", found, span.line, span.column)
+            }
+            FormatError::MismatchedBracket { expected, found, span } => tr!(
+                "括号不匹配：应为 `{}`，实际为 `{}`，位于 {}:{}",
+                "mismatched bracket: expected `{}`, found `{}` at {}:{}",
+                expected,
+                found,
+                span.line,
+                span.column
+            ),
+            FormatError::UnclosedBracket { open, span } => {
+                tr!("未闭合的 `{}`，起始于 {}:{}", "unclosed `{}` starting at {}:{}", open, span.line, span.column)
+            }
+            FormatError::NestingTooDeep { limit, span } => tr!(
+                "嵌套超过 {} 层（--max-nesting），位于 {}:{}",
+                "nesting deeper than {} levels (--max-nesting) at {}:{}",
+                limit,
+                span.line,
+                span.column
+            ),
+            FormatError::TooManyLineTokens { limit, span } => tr!(
+                "语句的词法单元超过 {} 个（--max-line-tokens），起始于 {}:{}",
+                "statement with more than {} tokens (--max-line-tokens) starting at {}:{}",
+                limit,
+                span.line,
+                span.column
+            ),
+            FormatError::InvalidJson { expected, found, span } => tr!(
+                "JSON 语法错误：应为{}，实际为 {}，位于 {}:{}",
+                "JSON syntax error: expected {}, found {} at {}:{}",
+                expected,
+                found,
+                span.line,
+                span.column
+            ),
+            FormatError::DataUriChanged { uri } => {
+                tr!("data URI 在格式化后发生变化：{}", "data URI changed during formatting: {}", uri)
+            }
+            FormatError::Unstable { line } => tr!(
+                "格式化结果不稳定：再次格式化后从输出第 {} 行起发生变化",
+                "formatting is not stable: formatting the output again changes it from output line {}",
+                line
+            ),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// 开括号对应的闭括号
+/// Closing bracket matching an opening bracket
+pub(crate) fn closing_bracket(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+/// 括号栈：记录尚未闭合的开括号及其偏移，遇到不匹配时记录错误
+/// Bracket stack: tracks unclosed opening brackets and their offsets, recording errors on mismatches
+#[derive(Default)]
+struct BracketStack {
+    open: Vec<(char, usize)>,
+}
+
+impl BracketStack {
+    /// 处理一个括号字符；其他字符忽略
+    /// Handle one bracket character; other characters are ignored
+    fn push(&mut self, c: char, offset: usize, source: &str, errors: &mut Vec<FormatError>) {
+        match c {
+            '(' | '[' | '{' => self.open.push((c, offset)),
+            ')' | ']' | '}' => match self.open.last() {
+                Some(&(open, _)) if closing_bracket(open) == c => {
+                    self.open.pop();
+                }
+                // 不匹配时弹出开括号继续检查，避免一个错误引发一连串后续错误
+                // On a mismatch the opening bracket is popped so one error doesn't cascade into many
+                Some(&(open, _)) => {
+                    errors.push(FormatError::MismatchedBracket { expected: closing_bracket(open), found: c, span: SourceSpan::new(source, offset, 1) });
+                    self.open.pop();
+                }
+                None => errors.push(FormatError::UnexpectedBracket { found: c, span: SourceSpan::new(source, offset, 1) }),
+            },
+            _ => {}
+        }
+    }
+
+    /// 输入结束：剩余的开括号都未闭合
+    /// End of input: every remaining opening bracket is unclosed
+    fn finish(self, source: &str, errors: &mut Vec<FormatError>) {
+        for (open, offset) in self.open {
+            errors.push(FormatError::UnclosedBracket { open, span: SourceSpan::new(source, offset, 1) });
+        }
+    }
+}
+
+/// 字符串或模板字符串词法单元是否以未转义的结束引号结尾（模板字符串中 `${ }` 内的反引号不算）
+/// Whether a string or template token ends with an unescaped closing quote (backticks inside a template's `${ }` don't count)
+fn js_string_closed(text: &str) -> bool {
+    let mut chars = text.chars();
+    let Some(quote) = chars.next() else {
+        return false;
+    };
+    let mut depth = 0usize;
+    let mut closed = false;
+    while let Some(c) = chars.next() {
+        closed = false;
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' if quote == '`' && chars.as_str().starts_with('{') => {
+                chars.next();
+                depth += 1;
+            }
+            '}' if depth > 0 => depth -= 1,
+            c if c == quote && depth == 0 => closed = true,
+            _ => {}
+        }
+    }
+    closed
+}
+
+/// 检查 JS/TS 的结构：未闭合的字符串、模板字符串与块注释，以及不平衡的括号
+/// Check JS/TS structure: unclosed strings, templates and block comments, and unbalanced brackets
+pub(crate) fn js_syntax_errors(source: &str) -> Vec<FormatError> {
+    let mut errors = Vec::new();
+    let mut brackets = BracketStack::default();
+    for (kind, range) in scan_js(source) {
+        let (offset, text) = (range.start, &source[range]);
+        match kind {
+            JsKind::Str | JsKind::Template if !js_string_closed(text) => {
+                errors.push(FormatError::UnclosedString { span: SourceSpan::new(source, offset, text.len()) });
+            }
+            JsKind::BlockComment if text.len() < 4 || !text.ends_with("*/") => {
+                errors.push(FormatError::UnclosedComment { span: SourceSpan::new(source, offset, text.len()) });
+            }
+            JsKind::Punct if text.len() == 1 => brackets.push(text.chars().next().unwrap_or_default(), offset, source, &mut errors),
+            _ => {}
+        }
+    }
+    brackets.finish(source, &mut errors);
+    errors
+}
+
+/// 检查 CSS 的结构：未闭合的字符串与注释，以及不平衡的括号（不带引号的 url() 内容整体跳过）
+/// Check CSS structure: unclosed strings and comments, and unbalanced brackets (unquoted url() bodies are skipped whole)
+pub(crate) fn css_syntax_errors(source: &str) -> Vec<FormatError> {
+    let mut errors = Vec::new();
+    let mut brackets = BracketStack::default();
+    let mut chars = source.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '/' if chars.peek().is_some_and(|&(_, next)| next == '*') => {
+                chars.next();
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    if c == '*' && chars.peek().is_some_and(|&(_, next)| next == '/') {
+                        chars.next();
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    errors.push(FormatError::UnclosedComment { span: SourceSpan::new(source, offset, source.len() - offset) });
+                }
+            }
+            // 字符串不能跨行（转义的换行除外）
+            // Strings can't span lines (except for escaped line breaks)
+            '"' | '\'' => {
+                let mut closed = false;
+                while let Some((_, n)) = chars.next_if(|&(_, n)| n != '\n') {
+                    if n == '\\' {
+                        chars.next();
+                    } else if n == c {
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    let end = chars.peek().map_or(source.len(), |&(end, _)| end);
+                    errors.push(FormatError::UnclosedString { span: SourceSpan::new(source, offset, end - offset) });
+                }
+            }
+            '(' if ends_with_url(&source[..offset]) => {
+                brackets.push(c, offset, source, &mut errors);
+                let quoted = source[offset + 1..].trim_start().starts_with(['"', '\'']);
+                if !quoted {
+                    while chars.next_if(|&(_, n)| n != ')').is_some() {}
+                }
+            }
+            _ => brackets.push(c, offset, source, &mut errors),
+        }
+    }
+    brackets.finish(source, &mut errors);
+    errors
+}
+
+/// 结束标签可以省略的元素（HTML 规范中的可选结束标签）
+/// Elements whose end tag may be omitted (optional end tags in the HTML spec)
+const OPTIONAL_END_TAG_ELEMENTS: &[&str] = &[
+    "html", "head", "body", "p", "li", "dt", "dd", "rt", "rp", "optgroup", "option", "colgroup",
+    "caption", "thead", "tbody", "tfoot", "tr", "td", "th",
+];
+
+/// 检查 HTML 的结构：未闭合的注释、缺少 `>` 的标签与缺少结束标签的原样输出元素；
+/// `match_tags` 为 true 时还检查开始与结束标签是否配对（可选结束标签与空元素除外）
+/// Check HTML structure: unclosed comments, tags without `>` and raw text elements without an end tag;
+/// with `match_tags` start and end tags must also pair up (except optional end tags and void elements)
+pub(crate) fn html_syntax_errors(source: &str, match_tags: bool) -> Vec<FormatError> {
+    let mut errors = Vec::new();
+    let mut open: Vec<(String, usize, usize)> = Vec::new();
+    let mut position = 0;
+    while let Some(found) = source[position..].find('<') {
+        let offset = position + found;
+        let after = &source[offset + 1..];
+        if !after.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?') {
+            position = offset + 1;
+            continue;
+        }
+        if let Some(body) = after.strip_prefix("!--") {
+            match body.find("-->") {
+                Some(end) => position = offset + 4 + end + 3,
+                None => {
+                    errors.push(FormatError::UnclosedComment { span: SourceSpan::new(source, offset, source.len() - offset) });
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let name: String = after
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>')
+            .collect::<String>()
+            .to_lowercase();
+        let mut quote = None;
+        let mut end = None;
+        for (i, c) in after.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '>' => {
+                    end = Some(i);
+                    break;
+                }
+                None => {}
+            }
+        }
+        let Some(end) = end else {
+            errors.push(FormatError::UnclosedTag { name, span: SourceSpan::new(source, offset, source.len() - offset) });
+            break;
+        };
+        position = offset + 1 + end + 1;
+        let self_closing = after[..end].trim_end().ends_with('/');
+
+        if match_tags && !name.is_empty() && !after.starts_with(['!', '?']) {
+            if !after.starts_with('/') {
+                if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+                    open.push((name.clone(), offset, end + 2));
+                }
+            } else if let Some(index) = open.iter().rposition(|(open_name, _, _)| *open_name == name) {
+                // 之间尚未闭合的元素被隐式关闭，其中不可省略结束标签的元素报告为不匹配
+                // Elements still open in between are closed implicitly; those whose end tag can't be omitted are mismatches
+                if let Some((expected, _, _)) = open[index + 1..].iter().rev().find(|(n, _, _)| !OPTIONAL_END_TAG_ELEMENTS.contains(&n.as_str())) {
+                    errors.push(FormatError::MismatchedTag { expected: expected.clone(), found: name.clone(), span: SourceSpan::new(source, offset, end + 2) });
+                }
+                open.truncate(index);
+            } else if !VOID_ELEMENTS.contains(&name.as_str()) {
+                errors.push(FormatError::UnexpectedEndTag { name: name.clone(), span: SourceSpan::new(source, offset, end + 2) });
+            }
+        }
+
+        // 原样输出元素必须有结束标签，否则其后的全部内容都会被当作原文
+        // Raw text elements need an end tag, otherwise everything after them is taken verbatim
+        if !after.starts_with('/') && !self_closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let closing = format!("</{}", name);
+            match source[position..].to_ascii_lowercase().find(&closing) {
+                Some(close) => position += close,
+                None => {
+                    open.retain(|(open_name, _, _)| *open_name != name);
+                    errors.push(FormatError::UnclosedTag { name, span: SourceSpan::new(source, offset, end + 2) });
+                    break;
+                }
+            }
+        }
+    }
+    for (name, offset, len) in open {
+        if !OPTIONAL_END_TAG_ELEMENTS.contains(&name.as_str()) {
+            errors.push(FormatError::UnclosedTag { name, span: SourceSpan::new(source, offset, len) });
+        }
+    }
+    errors
+}
+
+/// 按代码类型检查输入的结构错误
+/// Check the input for structural errors according to its code type
+pub(crate) fn syntax_errors(source: &str, file_type: &str) -> Vec<FormatError> {
+    language(file_type).map_or_else(Vec::new, |language| language.syntax_errors(source))
+}
+
+/// 校验模式的检查：在格式化前检查的基础上，HTML 还检查标签配对，结果按位置排序
+/// Checks of the validate mode: on top of the pre-formatting checks, HTML tags must pair up; results are sorted by position
+pub(crate) fn validation_errors(source: &str, file_type: &str) -> Vec<FormatError> {
+    let mut errors = language(file_type).map_or_else(Vec::new, |language| language.validation_errors(source));
+    errors.sort_by_key(|error| error.span().map_or(0, |span| span.offset));
+    errors
+}
+
+/// 校验单个文件，返回发现的结构错误
+/// Validate a single file, returning the structural errors found
+fn validate_file(input: &str, type_hint: Option<&str>, config: Option<&str>) -> Result<Vec<FormatError>> {
+    let config = Config::discover(config, input)?;
+    let (raw_content, _) = read_source(input, TextEncoding::Auto)?;
+    let content = strip_bom(&raw_content).1.replace("\r", "");
+    let file_type = resolve_file_type(input, type_hint, &config.extensions, &content)?;
+    log(LogLevel::Verbose, tr!("[INFO] 校验 {} 文件 {}", "[INFO] Validating {} file {}", file_type, input));
+    Ok(validation_errors(&content, file_type))
+}
+
+/// 校验子命令：逐个文件报告所有结构错误，无法读取的文件记录后继续
+/// Validate subcommand: report every structural error file by file, recording unreadable files and continuing
+pub(crate) fn run_validate(inputs: &[String], type_hint: Option<&str>, config: Option<&str>) -> ExitStatus {
+    let mut status = ExitStatus::Clean;
+    for input in inputs {
+        match validate_file(input, type_hint, config) {
+            Ok(errors) if errors.is_empty() => log(LogLevel::Normal, tr!("[VALIDATE] 结构正确：{}", "[VALIDATE] Valid: {}", input)),
+            Ok(errors) => {
+                for error in &errors {
+                    if let Some(span) = error.span() {
+                        eprintln!("{}\n", render_diagnostic(Severity::Error, &error.to_string(), input, span, &error.hint()));
+                    }
+                }
+                log(LogLevel::Normal, tr!("[VALIDATE] {}：发现 {} 处结构错误", "[VALIDATE] {}: {} structural errors found", input, errors.len()));
+                status = status.max(ExitStatus::FormatError);
+            }
+            Err(err) => {
+                eprintln!("{}", tr!("错误：{:?}", "Error: {:?}", err));
+                status = status.max(ExitStatus::of_error(&err));
+            }
+        }
+    }
+    status
+}
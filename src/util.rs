@@ -0,0 +1,102 @@
+//! 通用工具函数
+//! General Utility Functions
+
+
+/// 宽字符（东亚全角/宽字符与默认以 emoji 显示的字符）的码位区间，显示宽度为 2
+/// Code point ranges of wide characters (East Asian fullwidth/wide and default-emoji characters), display width 2
+const WIDE_CHAR_RANGES: &[(u32, u32)] = &[
+    (0x1100, 0x115F), (0x231A, 0x231B), (0x2329, 0x232A), (0x23E9, 0x23EC), (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3), (0x25FD, 0x25FE), (0x2614, 0x2615), (0x2648, 0x2653), (0x267F, 0x267F),
+    (0x2693, 0x2693), (0x26A1, 0x26A1), (0x26AA, 0x26AB), (0x26BD, 0x26BE), (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE), (0x26D4, 0x26D4), (0x26EA, 0x26EA), (0x26F2, 0x26F3), (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA), (0x26FD, 0x26FD), (0x2705, 0x2705), (0x270A, 0x270B), (0x2728, 0x2728),
+    (0x274C, 0x274C), (0x274E, 0x274E), (0x2753, 0x2755), (0x2757, 0x2757), (0x2795, 0x2797),
+    (0x27B0, 0x27B0), (0x27BF, 0x27BF), (0x2B1B, 0x2B1C), (0x2B50, 0x2B50), (0x2B55, 0x2B55),
+    (0x2E80, 0x303E), (0x3041, 0x33FF), (0x3400, 0x4DBF), (0x4E00, 0x9FFF), (0xA000, 0xA4CF),
+    (0xA960, 0xA97F), (0xAC00, 0xD7A3), (0xF900, 0xFAFF), (0xFE10, 0xFE19), (0xFE30, 0xFE6F),
+    (0xFF00, 0xFF60), (0xFFE0, 0xFFE6), (0x16FE0, 0x16FE4), (0x17000, 0x18AFF), (0x1B000, 0x1B2FF),
+    (0x1F004, 0x1F004), (0x1F0CF, 0x1F0CF), (0x1F18E, 0x1F18E), (0x1F191, 0x1F19A), (0x1F200, 0x1F202),
+    (0x1F210, 0x1F23B), (0x1F240, 0x1F248), (0x1F250, 0x1F251), (0x1F260, 0x1F265), (0x1F300, 0x1F64F),
+    (0x1F680, 0x1F6FF), (0x1F7E0, 0x1F7EB), (0x1F900, 0x1F9FF), (0x1FA70, 0x1FAFF), (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+];
+
+/// 零宽字符（组合符号、零宽空格/连接符、变体选择符、肤色修饰符等）的码位区间
+/// Code point ranges of zero-width characters (combining marks, zero-width spaces/joiners, variation selectors, skin tone modifiers, etc.)
+const ZERO_WIDTH_RANGES: &[(u32, u32)] = &[
+    (0x0300, 0x036F), (0x0483, 0x0489), (0x0591, 0x05BD), (0x05BF, 0x05C7), (0x0610, 0x061A),
+    (0x064B, 0x065F), (0x0670, 0x0670), (0x06D6, 0x06ED), (0x0E31, 0x0E31), (0x0E34, 0x0E3A),
+    (0x0E47, 0x0E4E), (0x1AB0, 0x1AFF), (0x1DC0, 0x1DFF), (0x200B, 0x200F), (0x2060, 0x2064),
+    (0x20D0, 0x20FF), (0x302A, 0x302F), (0x3099, 0x309A), (0xFE00, 0xFE0F), (0xFE20, 0xFE2F),
+    (0xFEFF, 0xFEFF), (0x1F3FB, 0x1F3FF), (0xE0000, 0xE007F), (0xE0100, 0xE01EF),
+];
+
+fn in_ranges(c: char, ranges: &[(u32, u32)]) -> bool {
+    let code_point = c as u32;
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < code_point {
+                std::cmp::Ordering::Less
+            } else if start > code_point {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// 单个字符的显示宽度（0、1 或 2）
+/// Display width of a single character (0, 1 or 2)
+fn char_width(c: char) -> usize {
+    if c == '\t' {
+        1
+    } else if c.is_control() || in_ranges(c, ZERO_WIDTH_RANGES) {
+        0
+    } else if in_ranges(c, WIDE_CHAR_RANGES) {
+        2
+    } else {
+        1
+    }
+}
+
+/// 文本的显示宽度：按字形簇计算，零宽连接符连接的 emoji 序列、国旗与带 VS16 的 emoji 均按一个宽字符计
+/// Display width of text, per grapheme cluster: ZWJ emoji sequences, flags and emoji with VS16 count as one wide character
+pub(crate) fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut cluster_width = 0;
+    let mut joined = false;
+    let mut pending_flag = false;
+    for c in text.chars() {
+        if joined {
+            joined = false;
+            continue;
+        }
+        match c {
+            '\u{200D}' => joined = true,
+            '\u{FE0F}' if cluster_width == 1 => {
+                width += 1;
+                cluster_width = 2;
+            }
+            // 两个区域指示符组成一个国旗
+            // Two regional indicators form one flag
+            '\u{1F1E6}'..='\u{1F1FF}' => {
+                if !pending_flag {
+                    width += 2;
+                    cluster_width = 2;
+                }
+                pending_flag = !pending_flag;
+                continue;
+            }
+            _ => {
+                let char_width = char_width(c);
+                if char_width > 0 {
+                    cluster_width = char_width;
+                }
+                width += char_width;
+            }
+        }
+        pending_flag = false;
+    }
+    width
+}
@@ -0,0 +1,113 @@
+//! 目录遍历
+//! Directory traversal
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use crate::*;
+
+/// 目录遍历的控制选项
+/// Controls for directory traversal
+pub(crate) struct WalkOptions {
+    pub(crate) follow_symlinks: bool,
+    pub(crate) hidden: bool,
+    /// 最大深度：目录中直接包含的文件深度为 1
+    /// Maximum depth: files directly inside a directory are at depth 1
+    pub(crate) max_depth: Option<usize>,
+    /// 显式指定的配置文件；未指定时每个目录向上查找默认配置文件
+    /// The explicit config file; without one each directory searches upwards for the default file
+    pub(crate) config: Option<String>,
+}
+
+/// 解析文件大小：纯数字为字节，可带 K/M/G 后缀（按 1024 进位）
+/// Parse a file size: a bare number is bytes, K/M/G suffixes are allowed (powers of 1024)
+pub(crate) fn parse_size(text: &str) -> std::result::Result<u64, String> {
+    let upper = text.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches(['B', 'I']);
+    let (number, unit) = match digits.char_indices().last() {
+        Some((index, 'K')) => (&digits[..index], 1u64 << 10),
+        Some((index, 'M')) => (&digits[..index], 1 << 20),
+        Some((index, 'G')) => (&digits[..index], 1 << 30),
+        _ => (digits, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| tr!("无效的文件大小：{}（例如 500K、2M）", "invalid file size: {} (e.g. 500K, 2M)", text))
+}
+
+/// 把输入中的目录展开为其中可处理的文件（按路径排序），其他输入原样保留；
+/// 可处理指扩展名可识别（含配置的 `[extensions]`）或匹配配置的 `[external]` 模式
+/// Expand directories among the inputs into the files inside them that can be handled (sorted by path); other inputs
+/// are kept as they are. A file can be handled when its extension is recognized (including the config's
+/// `[extensions]`) or it matches one of the config's `[external]` patterns
+pub(crate) fn expand_inputs(inputs: &[String], walk: &WalkOptions) -> Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for input in inputs {
+        if input != "-" && Path::new(input).is_dir() {
+            let mut visited = Vec::new();
+            let mut files = Vec::new();
+            walk_directory(Path::new(input), 1, walk, &mut visited, &mut files)?;
+            // 按路径分量排序，顺序不受各平台路径分隔符的字符值影响
+            // Sort by path components so the order doesn't depend on the character value of each platform's separator
+            files.sort_by(|a, b| Path::new(a).cmp(Path::new(b)));
+            log(LogLevel::Verbose, tr!("[INFO] 目录 {}：找到 {} 个文件", "[INFO] Directory {}: found {} files", input, files.len()));
+            expanded.extend(files);
+        } else {
+            expanded.push(input.clone());
+        }
+    }
+    // 同一文件（如既直接给出又位于给出的目录中）只处理第一次出现
+    // The same file (such as one given directly and also inside a given directory) is only processed where it first appears
+    let mut seen = std::collections::HashSet::new();
+    expanded.retain(|input| input == "-" || seen.insert(std::fs::canonicalize(input).unwrap_or_else(|_| input.into())));
+    Ok(expanded)
+}
+
+/// 递归收集目录中的文件；`visited` 记录已进入的目录（规范路径），跟随符号链接时避免循环
+/// Collect the files of a directory recursively; `visited` holds the directories entered (canonical paths) so
+/// following symlinks can't loop
+fn walk_directory(dir: &Path, depth: usize, walk: &WalkOptions, visited: &mut Vec<std::path::PathBuf>, files: &mut Vec<String>) -> Result<()> {
+    let canonical = dir.canonicalize().with_context(|| tr!("无法读取目录：{}", "Cannot read directory: {}", dir.display()))?;
+    if visited.contains(&canonical) {
+        return Ok(());
+    }
+    visited.push(canonical);
+    // 适用于本目录中文件的配置，与之后逐个文件处理时找到的配置相同
+    // The config that applies to the files in this directory, the same one each file finds when it is processed
+    let config = Config::discover(walk.config.as_deref(), &dir.join(CONFIG_FILE_NAME).to_string_lossy())?;
+    // 按名称顺序遍历，结果与经由哪条符号链接到达某个目录都不依赖文件系统的顺序
+    // Walk in name order so the result, and which symlink reaches a directory, don't depend on the file system's order
+    let mut entries = std::fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+        .with_context(|| tr!("无法读取目录：{}", "Cannot read directory: {}", dir.display()))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if !walk.hidden && entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() && !walk.follow_symlinks {
+            log(LogLevel::Verbose, tr!("[INFO] 跳过符号链接：{}", "[INFO] Skipping symlink: {}", path.display()));
+            continue;
+        }
+        // 跟随符号链接时按链接目标判断类型；悬空的链接跳过
+        // When following symlinks the target decides the type; dangling links are skipped
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if walk.max_depth.is_none_or(|max| depth < max) {
+                walk_directory(&path, depth + 1, walk, visited, files)?;
+            }
+        } else if let Some(name) = path
+            .to_str()
+            .filter(|name| get_file_type(name, &config.extensions).is_ok() || external_command(name, &config.external).is_some())
+        {
+            files.push(name.to_string());
+        }
+    }
+    Ok(())
+}
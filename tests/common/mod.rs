@@ -0,0 +1,105 @@
+//! 集成测试共用的辅助函数
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use code_formatter::FormatOptions;
+
+/// 用默认选项格式化
+/// Format with the default options
+pub fn format(source: &str, file_type: &str) -> String {
+    format_with(source, file_type, &[])
+}
+
+/// 用给定的设置（配置文件的键与值）格式化
+/// Format with the given settings (config file keys and values)
+pub fn format_with(source: &str, file_type: &str, settings: &[(&str, &str)]) -> String {
+    let mut options = FormatOptions::default();
+    for (key, value) in settings {
+        options.set(key, value).unwrap();
+    }
+    code_formatter::format(source, file_type, &options).unwrap_or_else(|err| panic!("formatting failed: {:#}\n{}", err, source))
+}
+
+/// 系统临时目录下的空目录，离开作用域时连同内容一起删除（断言失败时也不会残留）
+/// An empty directory under the system temporary directory, removed with its contents when dropped (nothing is left behind when an assert fails)
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// 新建目录，`name` 区分各个测试
+    /// Create the directory, `name` tells the tests apart
+    pub fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("code_formatter_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    /// 在目录中写入文件，返回文件路径
+    /// Write a file into the directory, returning its path
+    pub fn file(&self, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 运行命令行程序，`stdin` 为标准输入
+/// Run the command line program with `stdin` as its standard input
+pub fn run_cli(args: &[&str], stdin: &str) -> Output {
+    run_cli_in(Path::new("."), args, stdin)
+}
+
+/// 在 `dir` 目录中运行命令行程序，`stdin` 为标准输入
+/// Run the command line program in the `dir` directory with `stdin` as its standard input
+pub fn run_cli_in(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_code_formatter"))
+        .current_dir(dir)
+        .args(args)
+        .env("LC_ALL", "en_US.UTF-8")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// 标准输出的文本
+/// Standard output as text
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// 用 `node --check` 检查 JS 语法；没有安装 node 时跳过
+/// Check JS syntax with `node --check`; skipped when node is not installed
+pub fn assert_valid_js(source: &str) {
+    let child = Command::new("node").arg("--check").arg("-").stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn();
+    let Ok(mut child) = child else {
+        eprintln!("node not found, skipping syntax check");
+        return;
+    };
+    child.stdin.take().unwrap().write_all(source.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "node --check failed:\n{}\n{}", source, String::from_utf8_lossy(&output.stderr));
+}
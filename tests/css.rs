@@ -0,0 +1,43 @@
+//! CSS：格式化与压缩的输出
+//! CSS: formatting and minifying output
+
+mod common;
+
+use common::{format, format_with, run_cli, stdout};
+
+#[test]
+fn banner_follows_charset() {
+    assert_eq!(format("/*! banner */\n@charset \"utf-8\";a{b:c}", "css"), "@charset \"utf-8\";\n/*! banner */\na { b: c; }\n");
+}
+
+#[test]
+fn banner_keeps_its_trailing_whitespace_after_charset() {
+    assert_eq!(
+        format("/*! banner  \n * x */\n@charset \"utf-8\";\na{b:c}", "css"),
+        "@charset \"utf-8\";\n/*! banner  \n * x */\na { b: c; }\n"
+    );
+}
+
+#[test]
+fn banner_without_charset_stays_first() {
+    assert_eq!(format("/*! banner */\na{b:c}", "css"), "/*! banner */\na { b: c; }\n");
+}
+
+#[test]
+fn minified_banner_follows_charset() {
+    let output = run_cli(&["minify", "-i", "-", "-o", "-", "--type", "css"], "/*! banner */\n@charset \"utf-8\";a{b:c}");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "@charset \"utf-8\";/*! banner */a{b:c}");
+}
+
+#[test]
+fn blank_lines_inside_comments_are_kept() {
+    assert_eq!(format("/* a\n\n\nb */\na{b:c}\n\n\n\nd{e:f}", "css"), "/* a\n\n\nb */\na { b: c; }\nd { e: f; }\n");
+}
+
+#[test]
+fn trailing_whitespace_in_comments_is_trimmed() {
+    let settings = [("trim_trailing_whitespace", "true")];
+    assert_eq!(format_with("/*! banner  */\n/* a   \n b */\na{b:c}", "css", &settings), "/*! banner  */\n/* a\n b */\na { b: c; }\n");
+    assert_eq!(format_with("/* a */\na{b:c}", "css", &settings), "/* a */\na { b: c; }\n");
+}
@@ -0,0 +1,26 @@
+//! debug-tokens 子命令：输入文件为位置参数
+//! The debug-tokens subcommand: the input file is a positional argument
+
+mod common;
+
+use common::{run_cli, stdout, TempDir};
+
+#[test]
+fn input_is_positional() {
+    let dir = TempDir::new("debug_tokens");
+    let path = dir.file("a.js", "a=1");
+    let output = run_cli(&["debug-tokens", path.to_str().unwrap()], "");
+    let text = stdout(&output);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(text.contains("(js)\n\n## Tokens\n"), "{}", text);
+    assert!(text.contains("Word         \"a\""), "{}", text);
+    assert!(text.ends_with("## Formatted output\na = 1\n"), "{}", text);
+}
+
+#[test]
+fn dash_reads_stdin() {
+    let output = run_cli(&["debug-tokens", "-", "--type", "css"], "a{b:c}");
+    let text = stdout(&output);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(text.contains("## Syntax tree\nBlock \"a\"\n  Declaration \"b\": \"c\"\n"), "{}", text);
+}
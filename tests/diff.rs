@@ -0,0 +1,52 @@
+//! --diff：统一差异格式的输出可以直接交给 `git apply`
+//! --diff: the unified diff output can be fed straight to `git apply`
+
+mod common;
+
+use std::process::{Command, Stdio};
+
+use common::{run_cli_in, stdout, TempDir};
+
+/// 对 `source` 运行 `--check --diff`，检查 `git apply` 能应用输出的差异，且应用后文件已格式化；没有安装 git 时跳过
+/// Run `--check --diff` on `source`, checking that `git apply` applies the printed diff and the file is formatted
+/// afterwards; skipped when git is not installed
+fn assert_diff_applies(name: &str, source: &str, extra: &[&str]) -> String {
+    let dir = TempDir::new(name);
+    dir.file("a.js", source);
+    let mut args = vec!["-i", "a.js", "--check", "--diff"];
+    args.extend_from_slice(extra);
+    let diff = stdout(&run_cli_in(&dir, &args, ""));
+    let patch = dir.file("a.patch", &diff);
+    let Ok(apply) = Command::new("git").arg("apply").arg(&patch).current_dir(&*dir).stderr(Stdio::piped()).output() else {
+        eprintln!("git not found, skipping git apply");
+        return diff;
+    };
+    assert!(apply.status.success(), "git apply failed:\n{}\n{}", diff, String::from_utf8_lossy(&apply.stderr));
+    let check = run_cli_in(&dir, &["-i", "a.js", "--check"], "");
+    assert!(check.status.success(), "{}", stdout(&check));
+    diff
+}
+
+#[test]
+fn missing_final_newline_is_marked() {
+    let diff = assert_diff_applies("diff_final_newline", "x = 1", &[]);
+    assert!(diff.ends_with("@@ -1,1 +1,1 @@\n-x = 1\n\\ No newline at end of file\n+x = 1\n"), "{}", diff);
+}
+
+#[test]
+fn crlf_lines_are_replaced() {
+    let diff = assert_diff_applies("diff_crlf", "a = 1\r\nb = 2\r\n", &[]);
+    assert!(diff.ends_with("@@ -1,2 +1,2 @@\n-a = 1\r\n-b = 2\r\n+a = 1\n+b = 2\n"), "{}", diff);
+}
+
+#[test]
+fn stripped_bom_is_a_difference() {
+    let diff = assert_diff_applies("diff_bom", "\u{feff}a = 1\n", &["--bom", "strip"]);
+    assert!(diff.ends_with("@@ -1,1 +1,1 @@\n-\u{feff}a = 1\n+a = 1\n"), "{}", diff);
+}
+
+#[test]
+fn context_lines_keep_the_missing_final_newline() {
+    let diff = assert_diff_applies("diff_context", "a=1\nb = 2\nc = 3\nd = 4\ne = 5\nf = 6\ng=7", &[]);
+    assert!(diff.ends_with("-g=7\n\\ No newline at end of file\n+g = 7\n"), "{}", diff);
+}
@@ -0,0 +1,86 @@
+//! 库接口 format_with_diff：格式化结果与相对原文的改动区间
+//! Library API format_with_diff: the formatted result and the ranges that changed relative to the source
+
+use code_formatter::{format_with_diff, FormatOptions, FormatOutcome};
+
+/// 按顺序把改动应用到原文
+/// Apply the changes to the original in order
+fn apply(source: &str, outcome: &FormatOutcome) -> String {
+    let mut result = String::new();
+    let mut offset = 0;
+    for span in &outcome.changed_ranges {
+        assert!(span.old.start >= offset, "spans overlap or are out of order: {:?}", outcome.changed_ranges);
+        result.push_str(&source[offset..span.old.start]);
+        result.push_str(&outcome.formatted[span.new.clone()]);
+        offset = span.old.end;
+    }
+    result.push_str(&source[offset..]);
+    result
+}
+
+#[test]
+fn unchanged_source_has_no_spans() {
+    let source = "function f() {\n    return 1;\n}\n";
+    let outcome = format_with_diff(source, "js", &FormatOptions::default()).unwrap();
+    assert_eq!(outcome.formatted, source);
+    assert!(outcome.changed_ranges.is_empty());
+}
+
+#[test]
+fn spans_are_minimal() {
+    let source = "function f() {\n    let a=1;\n    return a;\n}\n";
+    let outcome = format_with_diff(source, "js", &FormatOptions::default()).unwrap();
+    assert_eq!(outcome.formatted, "function f() {\n    let a = 1;\n    return a;\n}\n");
+    assert_eq!(outcome.changed_ranges.len(), 1);
+    let span = &outcome.changed_ranges[0];
+    assert_eq!(&source[span.old.clone()], "=");
+    assert_eq!(&outcome.formatted[span.new.clone()], " = ");
+    assert_eq!(span.lines, 2..3);
+    assert_eq!(apply(source, &outcome), outcome.formatted);
+}
+
+#[test]
+fn crlf_spans_refer_to_the_original_buffer() {
+    let source = "a{b:c}\r\nd{e:f}";
+    let outcome = format_with_diff(source, "css", &FormatOptions::default()).unwrap();
+    assert_eq!(outcome.formatted, "a { b: c; }\nd { e: f; }\n");
+    assert!(outcome.changed_ranges.iter().all(|span| span.old.end <= source.len()));
+    assert_eq!(apply(source, &outcome), outcome.formatted);
+}
+
+#[test]
+fn crlf_lines_with_unchanged_content_only_lose_the_carriage_return() {
+    let source = "function f() {\r\n    return 1;\r\n}\r\n";
+    let outcome = format_with_diff(source, "js", &FormatOptions::default()).unwrap();
+    assert_eq!(outcome.formatted, "function f() {\n    return 1;\n}\n");
+    assert_eq!(outcome.changed_ranges.len(), 3);
+    assert!(outcome.changed_ranges.iter().all(|span| &source[span.old.clone()] == "\r" && span.new.is_empty()));
+    assert_eq!(outcome.changed_ranges.iter().map(|span| span.lines.start).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(apply(source, &outcome), outcome.formatted);
+}
+
+#[test]
+fn bom_is_kept_and_spans_skip_it() {
+    let source = "\u{FEFF}a{b:c}";
+    let outcome = format_with_diff(source, "css", &FormatOptions::default()).unwrap();
+    assert_eq!(outcome.formatted, "\u{FEFF}a { b: c; }\n");
+    assert!(outcome.changed_ranges.iter().all(|span| span.old.start >= '\u{FEFF}'.len_utf8()));
+    assert_eq!(apply(source, &outcome), outcome.formatted);
+}
+
+#[test]
+fn options_are_set_by_config_key() {
+    let mut options = FormatOptions::default();
+    options.set("indent", "2").unwrap();
+    options.set("css_single_line_rules", "never").unwrap();
+    let outcome = format_with_diff("a{b:c}", "css", &options).unwrap();
+    assert_eq!(outcome.formatted, "a {\n  b: c;\n}\n");
+    assert!(options.set("indent", "0").is_err());
+    assert!(options.set("indent_style", "dots").is_err());
+    assert!(options.set("no_such_setting", "1").is_err());
+}
+
+#[test]
+fn unknown_type_is_an_error() {
+    assert!(format_with_diff("x", "cobol", &FormatOptions::default()).is_err());
+}
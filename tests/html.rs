@@ -0,0 +1,18 @@
+//! HTML：格式化的输出
+//! HTML: formatting output
+
+mod common;
+
+use common::format;
+
+#[test]
+fn single_line_template_script_stays_verbatim() {
+    let formatted = format("<div><script type=\"text/template\"><b> {{x}} </b></script></div>\n", "html");
+    assert_eq!(formatted, "<div>\n    <script type = \"text/template\"><b> {{x}} </b></script>\n</div>\n");
+}
+
+#[test]
+fn multi_line_template_script_stays_verbatim() {
+    let formatted = format("<div>\n<script type=\"text/template\">\n  <b>{{x}}</b>\n</script></div>\n", "html");
+    assert_eq!(formatted, "<div>\n    <script type = \"text/template\">\n  <b>{{x}}</b>\n    </script>\n</div>\n");
+}
@@ -0,0 +1,140 @@
+//! JS/TS：格式化的输出
+//! JS/TS: formatting output
+
+mod common;
+
+use common::{assert_valid_js, format};
+
+#[test]
+fn private_fields_without_semicolons_stay_on_their_own_lines() {
+    let source = "class Tracker {\n  #count = 0\n  #ending = false\n  #progress = new Map()\n  #createTracker(key, name) {\n    return this.#progress.get(key) ?? name\n  }\n}\n";
+    let formatted = format(source, "js");
+    assert_eq!(
+        formatted,
+        "class Tracker {\n    #count = 0\n    #ending = false\n    #progress = new Map()\n\n    #createTracker(key, name) {\n        return this.#progress.get(key) ?? name\n    }\n}\n"
+    );
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn class_fields_are_separated_from_the_following_method() {
+    let formatted = format("class A{static x=1;y;constructor(){this.z=2}m(){}}", "js");
+    assert_eq!(formatted, "class A {\n    static x = 1;\n    y;\n\n    constructor() {\n        this.z = 2\n    }\n\n    m() {}\n}\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn arrow_function_fields_are_not_methods() {
+    let formatted = format("class A{a=()=>{return 1};b=2;m(){}}", "js");
+    assert_eq!(formatted, "class A {\n    a = () => {\n        return 1\n    };\n    b = 2;\n\n    m() {}\n}\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn generator_method_after_a_field_starts_a_new_member() {
+    let formatted = format("class A {\n  a = 1;\n  *gen() { yield* other() }\n}", "js");
+    assert_eq!(formatted, "class A {\n    a = 1;\n\n    *gen() {\n        yield* other()\n    }\n}\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn decorators_stay_next_to_their_member() {
+    let formatted = format("class A {\n  a = 1\n  @dec\n  m() {}\n  @dec(1) b = 2\n}", "ts");
+    assert_eq!(formatted, "class A {\n    a = 1\n\n    @dec\n    m() {}\n\n    @dec(1) b = 2\n}\n");
+}
+
+#[test]
+fn private_brand_check_on_a_new_line_is_not_joined() {
+    let formatted = format("class A {\n  static is(o) {\n    const x = o\n    #p in x\n  }\n  #p = 1\n}", "js");
+    assert!(formatted.contains("const x = o\n        #p in x\n"), "{}", formatted);
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn else_after_an_object_literal_keeps_its_line_break() {
+    let formatted = format("if (a)\n  var copy = { b: 1 }\nelse\n  var copy = 2\nif (a) f = () => { g() }\nelse h()\n", "js");
+    assert_eq!(formatted, "if (a)\n    var copy = {\n        b: 1\n    }\nelse\n    var copy = 2\nif (a) f = () => {\n    g()\n}\nelse h()\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn single_statement_bodies_on_their_own_line_are_indented() {
+    let formatted = format("if (a)\nif (b)\nc() // c\nelse\nd()\nfor (;;)\nx++\ndo\ny()\nwhile (a)\nz()\n", "js");
+    assert_eq!(formatted, "if (a)\n    if (b)\n        c() // c\n    else\n        d()\nfor (;;)\n    x++\ndo\n    y()\nwhile (a)\nz()\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn else_catch_and_finally_follow_statement_blocks() {
+    let formatted = format("if (a) { b() } else { c() }\ntry { x() } catch (e) { y() } finally { z() }\ndo { i++ } while (i < 3)", "js");
+    assert_eq!(
+        formatted,
+        "if (a) {\n    b()\n} else {\n    c()\n}\ntry {\n    x()\n} catch (e) {\n    y()\n} finally {\n    z()\n}\ndo {\n    i++\n} while (i < 3)\n"
+    );
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn hashbang_line_is_kept_verbatim() {
+    assert_eq!(format("#!/usr/bin/env node --harmony\nvar a=1", "js"), "#!/usr/bin/env node --harmony\nvar a = 1\n");
+}
+
+#[test]
+fn ternary_branches_spanning_lines_keep_their_spacing() {
+    assert_eq!(format("var y = a ? {k: 1} : b;", "js"), "var y = a ? {\n    k: 1\n} : b;\n");
+    assert_eq!(format("x = y ? function () {} : null", "js"), "x = y ? function() {} : null\n");
+    assert_eq!(format("z = c ? function () { return 1 } : null", "js"), "z = c ? function() {\n    return 1\n} : null\n");
+    assert_eq!(format("let t = x ? y ? 1 : 2 : 3;", "js"), "let t = x ? y ? 1 : 2 : 3;\n");
+    assert_valid_js(&format("var y = a ? {k: 1} : b;\nx = y ? function () {} : null\n", "js"));
+}
+
+#[test]
+fn colons_are_spaced_by_context() {
+    assert_eq!(format("obj = {a:b?c:d}", "js"), "obj = {\n    a: b ? c : d\n}\n");
+    assert_eq!(format("switch (x) { case a ? 1 : 2: y() }", "js"), "switch (x) {\ncase a ? 1 : 2:\n    y()\n}\n");
+    assert_eq!(format("outer: for (;;) { break outer }", "js"), "outer: for (;;) {\n    break outer\n}\n");
+}
+
+#[test]
+fn typescript_type_arguments_are_not_spaced_like_comparisons() {
+    let source = "function k<T extends object>(a: T, b?: string): Map<string, T> { return m }\nlet v: Array<Map<string, number>> = [];\nconst f = <T,>(x: T): T => x;\nclass Q<K, V> implements R<K> {}\nconst p: Promise<Array<T>> = load<T>();\n";
+    assert_eq!(
+        format(source, "ts"),
+        "function k<T extends object>(a: T, b?: string): Map<string, T> {\n    return m\n}\nlet v: Array<Map<string, number>> = [];\nconst f = <T,>(x: T): T => x;\nclass Q<K, V> implements R<K> {}\nconst p: Promise<Array<T>> = load<T>();\n"
+    );
+}
+
+#[test]
+fn typescript_optional_markers_are_not_ternaries() {
+    assert_eq!(format("interface I { m?(): void; n?: number; f(a?, b?: T): void }", "ts"), "interface I {\n    m?(): void;\n    n?: number;\n    f(a?, b?: T): void\n}\n");
+}
+
+#[test]
+fn comparisons_keep_their_spaces() {
+    assert_eq!(format("let r = a<b && c>d; for (let i=0;i<n;i++) {} x = a < b > c", "js"), "let r = a < b && c > d;\nfor (let i = 0; i < n; i++) {}\nx = a < b > c\n");
+}
+
+#[test]
+fn long_lines_are_not_broken_inside_type_arguments() {
+    let formatted = format("function someLongFunctionName<T extends object>(firstArgument: T, secondArgument: string): Map<string, T> { return m }", "ts");
+    assert_eq!(formatted, "function someLongFunctionName<T extends object>(firstArgument: T,\n    secondArgument: string): Map<string, T> {\n    return m\n}\n");
+}
+
+#[test]
+fn destructuring_declarations_keep_the_space_before_the_pattern() {
+    let formatted = format("let [a, b] = x; var [d] = e; for (const [k, v] of m) f(k)", "js");
+    assert_eq!(formatted, "let [a, b] = x;\nvar [d] = e;\nfor (const [k, v] of m) f(k)\n");
+    assert_valid_js(&formatted);
+}
+
+#[test]
+fn non_null_assertions_stick_to_their_operand() {
+    assert_eq!(format("x!.length; f(x)!.y; let y = a[0]!; z = x! + !y", "ts"), "x!.length;\nf(x)!.y;\nlet y = a[0]!;\nz = x! + !y\n");
+}
+
+#[test]
+fn continuations_stay_on_the_closing_brace_line() {
+    let formatted = format("import {a, b} from \"./m\";\nexport {d} from \"e\";\nconst {c} = y;\n", "js");
+    assert_eq!(formatted, "import {\n    a, b\n} from \"./m\";\nexport {\n    d\n} from \"e\";\nconst {\n    c\n} = y;\n");
+    assert_eq!(format("x = {a: 1} as const", "ts"), "x = {\n    a: 1\n} as const\n");
+}
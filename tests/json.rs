@@ -0,0 +1,82 @@
+//! JSON：格式化结果与由解析器给出的语法错误
+//! JSON: formatting output and the syntax errors reported by the parser
+
+mod common;
+
+use common::{format, run_cli};
+
+#[test]
+fn formats_nested_values() {
+    assert_eq!(format(r#"{"a":[1,2],"b":{}}"#, "json"), "{\n    \"a\": [\n        1,\n        2\n    ],\n    \"b\": {}\n}\n");
+}
+
+#[test]
+fn validate_rejects_trailing_comma() {
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], "{\"a\":1,}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains("JSON syntax error: expected a string key, found `}` at 1:8"), "{}", stderr);
+    assert!(stderr.contains("1 | {\"a\":1,}\n  |        ^"), "{}", stderr);
+}
+
+#[test]
+fn validate_rejects_comments() {
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], "{\n  // note\n  \"a\": 1\n}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains("at 2:3"), "{}", stderr);
+}
+
+#[test]
+fn validate_accepts_valid_json() {
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], "{\"a\": [1, \"x\", null]}");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn format_error_points_at_the_offending_character() {
+    let err = code_formatter::format("[1, 2 3]", "json", &Default::default()).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("`3`") && message.ends_with("1:7"), "{}", message);
+}
+
+/// 用 validate 检查 JSON，返回标准错误输出
+/// Validate JSON, returning stderr
+fn validate_error(source: &str) -> String {
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], source);
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    stderr
+}
+
+#[test]
+fn validate_rejects_numbers_outside_the_json_grammar() {
+    assert!(validate_error("01").contains("expected the end of the number, found `1` at 1:2"));
+    assert!(validate_error("[1.]").contains("expected a digit, found `]` at 1:4"));
+    assert!(validate_error("-").contains("expected a digit, found end of file at 1:2"));
+    assert!(validate_error("1e+").contains("expected a digit, found end of file at 1:4"));
+    assert!(validate_error("[.5]").contains("expected a value, found `.` at 1:2"));
+}
+
+#[test]
+fn validate_accepts_numbers_in_the_json_grammar() {
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], "[0, -0, 10, 1.25, -2.5e10, 3E-2, 4e+7]");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn validate_rejects_raw_control_characters_in_strings() {
+    assert!(validate_error("{\"a\": \"x\ty\"}").contains("expected an escaped control character, found U+0009 at 1:9"));
+    assert!(validate_error("[\"\u{1}\"]").contains("found U+0001 at 1:3"));
+}
+
+#[test]
+fn deeply_nested_input_does_not_overflow_the_stack() {
+    let source = format!("{}{}", "[".repeat(200_000), "]".repeat(200_000));
+    let output = run_cli(&["validate", "-i", "-", "--type", "json"], &source);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = run_cli(&["-i", "-", "-o", "-", "--type", "json"], &source);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains("nesting deeper than 256 levels (--max-nesting) at 1:257"), "{}", stderr);
+}
@@ -0,0 +1,56 @@
+//! 压缩：压缩后的输出与语法有效性
+//! Minify: minified output and its syntactic validity
+
+mod common;
+
+use common::{assert_valid_js, run_cli, stdout};
+
+/// 压缩 JS，返回标准输出
+/// Minify JS, returning stdout
+fn minify_js(source: &str) -> String {
+    let output = run_cli(&["minify", "-i", "-", "-o", "-", "--type", "js"], source);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    stdout(&output)
+}
+
+#[test]
+fn preserved_comment_keeps_the_line_break_asi_needs() {
+    let minified = minify_js("x\n/*! k */\ny\n");
+    assert_eq!(minified, "x/*! k */\ny");
+    assert_valid_js(&minified);
+}
+
+#[test]
+fn multi_line_preserved_comment_is_the_line_break() {
+    let minified = minify_js("x\n/*! a\n b */\ny\n");
+    assert_eq!(minified, "x/*! a\n b */y");
+    assert_valid_js(&minified);
+}
+
+#[test]
+fn private_fields_without_semicolons_are_not_joined() {
+    let minified = minify_js("class Tracker {\n  #count = 0\n  #ending = false\n  #createTracker(key) {\n    return key\n  }\n}\n");
+    assert_eq!(minified, "class Tracker{#count=0\n#ending=false\n#createTracker(key){return key}}");
+    assert_valid_js(&minified);
+}
+
+#[test]
+fn ordinary_comments_are_dropped_without_joining_statements() {
+    let minified = minify_js("a = 1 // one\nb = 2 /* two */\n/* three */ c = 3\nreturn\nx\n");
+    assert_eq!(minified, "a=1\nb=2\nc=3\nreturn\nx");
+}
+
+#[test]
+fn css_and_html_comments_are_stripped() {
+    let css = run_cli(&["minify", "-i", "-", "-o", "-", "--type", "css"], "/*! keep */\na {\n  color: red; /* drop */\n}\n");
+    assert_eq!(stdout(&css), "/*! keep */a{color:red}");
+    let html = run_cli(&["minify", "-i", "-", "-o", "-", "--type", "html"], "<div>\n  <!-- drop -->\n  <p>hi</p>\n</div>\n");
+    assert_eq!(stdout(&html), "<div><p>hi</p></div>");
+}
+
+#[test]
+fn hashbang_line_is_kept_on_its_own_line() {
+    let minified = minify_js("#!/usr/bin/env node\n/*!\n * banner\n */\nvar a = 1\n");
+    assert_eq!(minified, "#!/usr/bin/env node\n/*!\n * banner\n */var a=1");
+    assert_valid_js(&minified);
+}
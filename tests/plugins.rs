@@ -0,0 +1,166 @@
+//! WASM 插件：从 --plugins 目录加载的格式化器与内置语言走同一套命令行、配置与校验流程
+//! WASM plugins: formatters loaded from the --plugins directory go through the same CLI, config and validation
+//! pipeline as the built-in languages
+
+mod common;
+
+use std::fs;
+use common::{run_cli, stdout, TempDir};
+
+/// 测试插件：类型 `upper`（扩展名 .up、.upr），把 ASCII 小写字母转为大写，遇到 `!` 时报错；
+/// 压缩去掉所有空白
+/// Test plugin: type `upper` (extensions .up and .upr), turns ASCII lowercase letters into uppercase and fails on `!`;
+/// minifying drops all whitespace
+const UPPER_PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (global $out_ptr (mut i32) (i32.const 0))
+  (global $out_len (mut i32) (i32.const 0))
+  (data (i32.const 0) "upper up upr")
+  (data (i32.const 16) "unexpected !")
+
+  (func (export "language") (result i64)
+    (i64.const 12))
+
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (block $done
+      (loop $grow
+        (br_if $done (i32.le_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536))))
+        (drop (memory.grow (i32.const 1)))
+        (br $grow)))
+    (local.get $ptr))
+
+  (func $finish (param $ptr i32) (param $len i32)
+    (global.set $out_ptr (local.get $ptr))
+    (global.set $out_len (local.get $len)))
+
+  (func (export "format") (param $ptr i32) (param $len i32) (param $indent i32) (param $tabs i32) (param $width i32) (result i32)
+    (local $i i32) (local $c i32) (local $out i32)
+    (local.set $out (call $alloc (local.get $len)))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (local.set $c (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+        (if (i32.eq (local.get $c) (i32.const 33))
+          (then
+            (call $finish (i32.const 16) (i32.const 12))
+            (return (i32.const 1))))
+        (if (i32.and (i32.ge_u (local.get $c) (i32.const 97)) (i32.le_u (local.get $c) (i32.const 122)))
+          (then (local.set $c (i32.sub (local.get $c) (i32.const 32)))))
+        (i32.store8 (i32.add (local.get $out) (local.get $i)) (local.get $c))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (call $finish (local.get $out) (local.get $len))
+    (i32.const 0))
+
+  (func (export "minify") (param $ptr i32) (param $len i32) (result i32)
+    (local $i i32) (local $c i32) (local $out i32) (local $n i32)
+    (local.set $out (call $alloc (local.get $len)))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (local.set $c (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+        (if (i32.gt_u (local.get $c) (i32.const 32))
+          (then
+            (i32.store8 (i32.add (local.get $out) (local.get $n)) (local.get $c))
+            (local.set $n (i32.add (local.get $n) (i32.const 1)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (call $finish (local.get $out) (local.get $n))
+    (i32.const 0))
+
+  (func (export "output") (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (global.get $out_ptr)) (i64.const 32))
+      (i64.extend_i32_u (global.get $out_len)))))
+"#;
+
+/// 新建一个临时目录，其中 plugins/ 子目录放着编译好的测试插件
+/// Create a temporary directory whose plugins/ subdirectory holds the compiled test plugin
+fn plugin_dir(name: &str) -> TempDir {
+    let dir = TempDir::new(name);
+    fs::create_dir_all(dir.join("plugins")).unwrap();
+    fs::write(dir.join("plugins/upper.wasm"), wat::parse_str(UPPER_PLUGIN).unwrap()).unwrap();
+    dir
+}
+
+/// 带上 `--plugins` 运行命令行程序
+/// Run the command line program with `--plugins`
+fn run_with_plugins(dir: &std::path::Path, args: &[&str], stdin: &str) -> std::process::Output {
+    let plugins = dir.join("plugins");
+    let mut all = args.to_vec();
+    all.extend_from_slice(&["--plugins", plugins.to_str().unwrap()]);
+    run_cli(&all, stdin)
+}
+
+#[test]
+fn formats_a_file_by_plugin_extension() {
+    let dir = plugin_dir("plugin_extension");
+    let input = dir.join("a.up");
+    fs::write(&input, "hello world\n").unwrap();
+    let output = run_with_plugins(&dir, &["-i", input.to_str().unwrap(), "-o", "-"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "HELLO WORLD\n");
+}
+
+#[test]
+fn formats_stdin_by_plugin_type_name() {
+    let dir = plugin_dir("plugin_type");
+    let output = run_with_plugins(&dir, &["-i", "-", "-o", "-", "--type", "upper"], "  abc  ");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "ABC\n");
+}
+
+#[test]
+fn plugin_errors_are_reported() {
+    let dir = plugin_dir("plugin_error");
+    let output = run_with_plugins(&dir, &["-i", "-", "-o", "-", "--type", "upper"], "hi!");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains("unexpected !"), "{}", stderr);
+}
+
+#[test]
+fn minifies_with_the_plugin() {
+    let dir = plugin_dir("plugin_minify");
+    let output = run_with_plugins(&dir, &["minify", "-i", "-", "-o", "-", "--type", "upper"], "a b\nc");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "abc");
+}
+
+#[test]
+fn config_extensions_can_map_to_plugin_types() {
+    let dir = plugin_dir("plugin_config");
+    fs::write(dir.join(".code_formatter.toml"), "[extensions]\ntxt = \"upper\"\n").unwrap();
+    fs::write(dir.join("a.txt"), "abc\n").unwrap();
+    fs::write(dir.join("b.upr"), "def\n").unwrap();
+    let output = run_with_plugins(&dir, &["-i", dir.to_str().unwrap(), "--write"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "ABC\n");
+    assert_eq!(fs::read_to_string(dir.join("b.upr")).unwrap(), "DEF\n");
+}
+
+#[test]
+fn debug_tokens_dispatches_to_the_plugin() {
+    let dir = plugin_dir("plugin_debug");
+    let input = dir.join("a.up");
+    fs::write(&input, "abc").unwrap();
+    let output = run_with_plugins(&dir, &["debug-tokens", input.to_str().unwrap()], "");
+    let text = stdout(&output);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(text.contains("(upper)") && text.contains("provides no tokens") && text.ends_with("ABC\n"), "{}", text);
+}
+
+#[test]
+fn plugin_cannot_take_a_built_in_extension() {
+    let dir = plugin_dir("plugin_conflict");
+    fs::write(dir.join("plugins/css.wasm"), wat::parse_str(UPPER_PLUGIN.replace("upper up upr", "style css css")).unwrap()).unwrap();
+    let output = run_with_plugins(&dir, &["-i", "-", "-o", "-", "--type", "css"], "a{}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr);
+    assert!(stderr.contains("css"), "{}", stderr);
+}
@@ -0,0 +1,39 @@
+//! --report-eol：空白规范检查及其 JSON 报告
+//! --report-eol: the whitespace hygiene check and its JSON report
+
+mod common;
+
+use std::fs;
+
+use common::{run_cli, stdout, TempDir};
+
+#[test]
+fn json_report_lists_issues_per_file() {
+    let dir = TempDir::new("eol_report");
+    dir.file("a.js", "a\r\nb\nc \n");
+    dir.file("b.js", "x\n");
+    let output = run_cli(&["-i", dir.to_str().unwrap(), "--report-eol", "--report", "json", "--seedless"], "");
+    assert_eq!(output.status.code(), Some(1), "{}", String::from_utf8_lossy(&output.stderr));
+    let report = stdout(&output);
+    assert!(report.starts_with("{\n  \"summary\": {\n    \"checked\": 2,\n    \"clean\": 1,\n    \"issues\": 1,\n    \"fixed\": 0,\n    \"errored\": 0\n  },"), "{}", report);
+    assert!(report.contains("\"status\": \"issues\",\n      \"issues\": [{\"kind\": \"mixed_line_endings\", \"line\": 1, "), "{}", report);
+    assert!(report.contains("{\"kind\": \"trailing_whitespace\", \"line\": 3, "), "{}", report);
+    assert!(report.contains("\"path\": \"") && report.contains("b.js\",\n      \"status\": \"clean\",\n      \"issues\": []"), "{}", report);
+}
+
+#[test]
+fn json_report_records_fixes() {
+    let dir = TempDir::new("eol_fix");
+    let path = dir.file("a.css", "a { b: c; }  \n");
+    let output = run_cli(&["-i", path.to_str().unwrap(), "--report-eol", "--write", "--report", "json"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("\"status\": \"fixed\""), "{}", stdout(&output));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a { b: c; }\n");
+}
+
+#[test]
+fn other_report_formats_are_rejected() {
+    let output = run_cli(&["-i", "-", "--report-eol", "--report", "sarif"], "a\n");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--report-eol only supports --report json"));
+}
@@ -0,0 +1,15 @@
+//! --since：跳过未修改的文件
+//! --since: skipping files that were not modified
+
+mod common;
+
+use common::{run_cli, stdout, TempDir};
+
+#[test]
+fn skip_message_shows_the_cutoff() {
+    let dir = TempDir::new("since_cutoff");
+    let path = dir.file("a.js", "a=1");
+    let output = run_cli(&["-i", path.to_str().unwrap(), "--check", "-v", "--since", "2999-01-02T03:04:05Z"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("a.js: not modified since 2999-01-02T03:04:05Z\n"), "{}", stdout(&output));
+}
@@ -0,0 +1,46 @@
+//! 目录遍历：配置文件中的扩展名映射与外部格式化器同样决定收集哪些文件
+//! Directory traversal: the config's extension map and external formatters also decide which files are collected
+
+mod common;
+
+use std::fs;
+
+use common::{run_cli, stdout, TempDir};
+
+#[test]
+fn walk_uses_config_extensions_and_external_patterns() {
+    let dir = TempDir::new("walk_config");
+    fs::write(dir.join(".code_formatter.toml"), "[extensions]\ninc = \"html\"\n\n[external]\n\"*.py\" = \"cat\"\n").unwrap();
+    fs::write(dir.join("a.inc"), "<p>x</p>\n").unwrap();
+    fs::write(dir.join("p.py"), "x = 1\n").unwrap();
+    fs::write(dir.join("skip.txt"), "text\n").unwrap();
+    let output = run_cli(&["-i", dir.to_str().unwrap(), "--check", "-v"], "");
+    let log = format!("{}{}", stdout(&output), String::from_utf8_lossy(&output.stderr));
+    assert!(log.contains("found 2 files"), "{}", log);
+    assert!(log.contains("a.inc") && log.contains("p.py"), "{}", log);
+    assert!(!log.contains("skip.txt"), "{}", log);
+}
+
+#[test]
+fn walk_uses_the_config_of_each_subdirectory() {
+    let dir = TempDir::new("walk_nested");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("sub/.code_formatter.toml"), "[extensions]\ninc = \"css\"\n").unwrap();
+    fs::write(dir.join("top.inc"), "a { b: c; }\n").unwrap();
+    fs::write(dir.join("sub/nested.inc"), "a { b: c; }\n").unwrap();
+    let output = run_cli(&["-i", dir.to_str().unwrap(), "--check", "-v"], "");
+    let log = format!("{}{}", stdout(&output), String::from_utf8_lossy(&output.stderr));
+    assert!(log.contains("found 1 files"), "{}", log);
+    assert!(log.contains("nested.inc") && !log.contains("top.inc"), "{}", log);
+}
+
+#[test]
+fn failing_external_formatter_names_the_file() {
+    let dir = TempDir::new("walk_external_error");
+    dir.file(".code_formatter.toml", "[external]\n\"*.py\" = \"false\"\n");
+    dir.file("p.py", "x = 1\n");
+    let output = run_cli(&["-i", dir.to_str().unwrap(), "--check"], "");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(stderr.contains(&format!("Error: {}: External formatter false failed", dir.join("p.py").display())), "{}", stderr);
+}
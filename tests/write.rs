@@ -0,0 +1,29 @@
+//! --write：原地写回时的日志与差异计数
+//! --write: logging and difference counts when writing in place
+
+mod common;
+
+use std::fs;
+
+use common::{run_cli, stdout, TempDir};
+
+#[test]
+fn final_newline_only_change_counts_as_a_difference() {
+    let dir = TempDir::new("write_final_newline");
+    let path = dir.file("a.js", "a = 1");
+    let output = run_cli(&["-i", path.to_str().unwrap(), "--write", "-v"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains(": 1 differences"), "{}", stdout(&output));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "a = 1\n");
+}
+
+#[test]
+fn unchanged_file_is_reported_at_default_verbosity() {
+    let dir = TempDir::new("write_unchanged");
+    let path = dir.file("a.js", "a = 1\n");
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+    let output = run_cli(&["-i", path.to_str().unwrap(), "--write"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("[SUCCESS] Unchanged, not written: "), "{}", stdout(&output));
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+}